@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The main crate is a binary, so the parser is pulled in by path.
+#[path = "../../src/seed/parse.rs"]
+mod parse;
+
+fuzz_target!(|data: &[u8]| {
+    let content = String::from_utf8_lossy(data);
+    let _ = parse::parse(&content, parse::MAX_DEFINITION_LEN);
+});
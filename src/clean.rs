@@ -0,0 +1,230 @@
+use crate::db::models::Word;
+use crate::db::store::{WordFilter, WordStore};
+use crate::db::with_tx;
+use crate::lint::{decode_entities, tags};
+use anyhow::Result;
+use rusqlite::{Connection, params};
+
+const PAGE_SIZE: usize = 1000;
+
+/// Formatting tags web definitions carry. Anything else is left for
+/// `lint` to report.
+const ALLOWED_TAGS: [&str; 16] = [
+    "a", "abbr", "b", "br", "cite", "div", "em", "font", "i", "li", "p", "small", "span", "strong",
+    "sub", "sup",
+];
+/// Tags that end a line, so the text on either side becomes two senses.
+const LINE_TAGS: [&str; 4] = ["br", "div", "li", "p"];
+
+/// `def` as plain text: allowed tags stripped (`<br>` and block tags
+/// become line breaks), entities decoded, markdown emphasis dropped and
+/// whitespace collapsed. Senses stay one per line; blank lines go.
+/// Repeats until nothing changes, so `&amp;lt;i&amp;gt;` ends up stripped
+/// too and cleaning a clean definition is a no-op.
+pub fn clean_definition(def: &str) -> String {
+    let mut text = def.to_string();
+    loop {
+        let next = clean_once(&text);
+        if next == text {
+            return text;
+        }
+        text = next;
+    }
+}
+
+fn clean_once(def: &str) -> String {
+    let mut text = String::with_capacity(def.len());
+    let mut last = 0;
+    for (start, end, name) in tags(def) {
+        if !ALLOWED_TAGS.contains(&name.as_str()) {
+            continue;
+        }
+        text.push_str(&def[last..start]);
+        if LINE_TAGS.contains(&name.as_str()) {
+            text.push('\n');
+        }
+        last = end;
+    }
+    text.push_str(&def[last..]);
+
+    decode_entities(&text)
+        .lines()
+        .map(|line| {
+            let line = ["**", "__", "*", "_"]
+                .iter()
+                .fold(line.to_string(), |line, marker| strip_marker(&line, marker));
+            line.split_whitespace().collect::<Vec<_>>().join(" ")
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn strip_marker(text: &str, marker: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut from = 0;
+    while let Some((open, close)) = emphasis(text, from, marker) {
+        out.push_str(&text[from..open]);
+        out.push_str(&text[open + marker.len()..close]);
+        from = close + marker.len();
+    }
+    out.push_str(&text[from..]);
+    out
+}
+
+/// Next span wrapped in `marker` at or after `from`. The opening marker
+/// has to start a word and the closing one end it, so `2 * 3 * 4` and
+/// `snake_case` are left alone.
+fn emphasis(text: &str, from: usize, marker: &str) -> Option<(usize, usize)> {
+    let plain = |c: char| !c.is_alphanumeric() && !marker.contains(c);
+    let before = |i: usize| text[..i].chars().next_back();
+    let after = |i: usize| text[i + marker.len()..].chars().next();
+
+    let opens = text[from..]
+        .match_indices(marker)
+        .map(|(i, _)| from + i)
+        .filter(|&i| {
+            before(i).is_none_or(plain)
+                && after(i).is_some_and(|c| !c.is_whitespace() && !marker.contains(c))
+        });
+    for open in opens {
+        let body = open + marker.len();
+        let close = text[body..]
+            .match_indices(marker)
+            .map(|(i, _)| body + i)
+            .find(|&i| {
+                i > body
+                    && before(i).is_some_and(|c| !c.is_whitespace() && !marker.contains(c))
+                    && after(i).is_none_or(plain)
+            });
+        if let Some(close) = close {
+            return Some((open, close));
+        }
+    }
+    None
+}
+
+fn label(word: &Word) -> String {
+    format!("{} (#{})", word.word, word.id)
+}
+
+/// Cleans every definition in the database with `clean_definition`, or
+/// without `apply` just lists the ones it would change. Returns one line
+/// per definition.
+pub fn run(conn: &Connection, apply: bool) -> Result<Vec<String>> {
+    let mut changed = Vec::new();
+    WordStore::new(conn).for_each(WordFilter::All, PAGE_SIZE, |word| {
+        let cleaned = clean_definition(&word.definition);
+        if cleaned != word.definition {
+            changed.push((word, cleaned));
+        }
+        Ok(())
+    })?;
+    if changed.is_empty() {
+        return Ok(vec!["Nothing to clean".into()]);
+    }
+
+    if apply {
+        with_tx(conn, |tx| {
+            for (word, cleaned) in &changed {
+                tx.execute(
+                    "UPDATE words SET definition=?1 WHERE id=?2",
+                    params![cleaned, word.id],
+                )?;
+            }
+            Ok(())
+        })?;
+    }
+    let verb = if apply { "cleaned" } else { "would clean" };
+    Ok(changed
+        .iter()
+        .map(|(word, _)| format!("{}: {verb}", label(word)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+
+    const MESSY: [(&str, &str); 16] = [
+        ("<i>lessen</i>; <b>reduce</b>", "lessen; reduce"),
+        ("strict &amp; stern", "strict & stern"),
+        (
+            "caf&eacute; &#8212; a small restaurant",
+            "café — a small restaurant",
+        ),
+        (
+            "**bold** claims and *italic* asides",
+            "bold claims and italic asides",
+        ),
+        ("__strong__ and _weak_", "strong and weak"),
+        ("strict\n\n\nplain", "strict\nplain"),
+        ("  strict   and \t stern  ", "strict and stern"),
+        ("strict<br>plain<br/>harsh", "strict\nplain\nharsh"),
+        ("<p>strict</p><p>plain</p>", "strict\nplain"),
+        ("(of a person) strict", "(of a person) strict"),
+        ("(<i>of a person</i>) strict", "(of a person) strict"),
+        (
+            "2 * 3 * 4 and snake_case_name",
+            "2 * 3 * 4 and snake_case_name",
+        ),
+        ("x < y but y > z", "x < y but y > z"),
+        (
+            "<script>alert()</script> kept for lint",
+            "<script>alert()</script> kept for lint",
+        ),
+        ("&amp;lt;i&amp;gt;nested&amp;lt;/i&amp;gt;", "nested"),
+        ("a&nbsp;&nbsp;gap &unknown; here", "a gap &unknown; here"),
+    ];
+
+    #[test]
+    fn test_clean_messy_definitions() {
+        for (messy, clean) in MESSY {
+            assert_eq!(clean_definition(messy), clean, "{messy:?}");
+            assert_eq!(clean_definition(clean), clean, "{clean:?}");
+        }
+    }
+
+    #[test]
+    fn test_bundled_list_is_already_clean() {
+        let bundled = include_str!("../data/vocab.txt");
+        for line in bundled.lines() {
+            assert_eq!(clean_definition(line), line.trim());
+        }
+    }
+
+    #[test]
+    fn test_apply_is_idempotent() {
+        let conn = test_conn();
+        for (i, (messy, _)) in MESSY.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO words(word,definition,group_id) VALUES(?1,?2,1)",
+                params![format!("w{i}"), messy],
+            )
+            .unwrap();
+        }
+        let count = || -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        let preview = run(&conn, false).unwrap();
+        let applied = run(&conn, true).unwrap();
+        assert_eq!(applied.len(), preview.len());
+        assert_eq!(applied.len(), 12);
+        assert_eq!(count(), 16);
+
+        assert_eq!(run(&conn, true).unwrap(), vec!["Nothing to clean"]);
+        assert_eq!(count(), 16);
+        let definitions: Vec<String> = conn
+            .prepare("SELECT definition FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let expected: Vec<&str> = MESSY.iter().map(|(_, clean)| *clean).collect();
+        assert_eq!(definitions, expected);
+    }
+}
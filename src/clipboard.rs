@@ -0,0 +1,113 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::fmt;
+use std::io::{self, Write};
+
+pub trait Clipboard: fmt::Debug {
+    fn set_text(&mut self, text: &str) -> Result<()>;
+    fn get_text(&mut self) -> Result<String>;
+}
+
+/// System clipboard through `arboard`, falling back to an OSC 52 escape
+/// sequence when none is reachable (e.g. over ssh).
+#[derive(Default)]
+pub struct SystemClipboard {
+    // Kept alive for the whole run: on X11 the copied text is only served
+    // while the owning handle exists.
+    inner: Option<arboard::Clipboard>,
+}
+
+impl fmt::Debug for SystemClipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemClipboard")
+            .field("connected", &self.inner.is_some())
+            .finish()
+    }
+}
+
+impl SystemClipboard {
+    fn connect(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.inner.is_none() {
+            self.inner = arboard::Clipboard::new().ok();
+        }
+        self.inner.as_mut()
+    }
+}
+
+impl Clipboard for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        self.connect();
+        let copied = self
+            .inner
+            .as_mut()
+            .is_some_and(|clipboard| clipboard.set_text(text).is_ok());
+        if copied {
+            Ok(())
+        } else {
+            osc52(&mut io::stdout(), text)
+        }
+    }
+
+    // OSC 52 reads are rarely allowed by terminals, so there is no fallback.
+    fn get_text(&mut self) -> Result<String> {
+        let clipboard = self
+            .connect()
+            .ok_or_else(|| anyhow!("no clipboard available"))?;
+        Ok(clipboard.get_text()?)
+    }
+}
+
+fn osc52(out: &mut impl Write, text: &str) -> Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub use mock::MockClipboard;
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use anyhow::bail;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records copied text; clones share the same log so a test can keep one
+    /// after handing the other to the app. Pasting returns `contents`.
+    #[derive(Debug, Default, Clone)]
+    pub struct MockClipboard {
+        pub copied: Rc<RefCell<Vec<String>>>,
+        pub contents: Option<String>,
+        pub fail: bool,
+    }
+
+    impl Clipboard for MockClipboard {
+        fn set_text(&mut self, text: &str) -> Result<()> {
+            if self.fail {
+                bail!("clipboard unavailable");
+            }
+            self.copied.borrow_mut().push(text.to_string());
+            Ok(())
+        }
+
+        fn get_text(&mut self) -> Result<String> {
+            match &self.contents {
+                Some(text) if !self.fail => Ok(text.clone()),
+                _ => bail!("clipboard unavailable"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        let mut out = Vec::new();
+        osc52(&mut out, "abate").unwrap();
+        assert_eq!(out, b"\x1b]52;c;YWJhdGU=\x07");
+    }
+}
@@ -0,0 +1,122 @@
+use crate::core::stats::LeechPolicy;
+use crate::db::models::WordOrder;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Binding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Binding {
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            Binding::One(k) => vec![k.as_str()],
+            Binding::Many(ks) => ks.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub keys: HashMap<String, Binding>,
+    /// Profile used when `--profile` isn't given.
+    pub profile: Option<String>,
+    /// Where stats exports are written; the working directory when unset.
+    pub export_dir: Option<PathBuf>,
+    /// Let a held-down up/down key keep scrolling lists. Off by default:
+    /// terminals that report key repeats would otherwise skip past items.
+    pub repeat_navigation: bool,
+    pub leeches: LeechPolicy,
+    /// Marked words from other groups to mix into each Group session; 0
+    /// leaves them to Review Marks.
+    pub mix_marked: usize,
+    /// Reviews a day `vocabulator remind` checks for; 0 means no goal.
+    pub daily_goal: u32,
+    /// `"frequency"` works through each group most common words first.
+    pub order: WordOrder,
+    /// Keep mastered words in Group, Weak and mixed-in picks instead of
+    /// retiring them.
+    pub include_mastered: bool,
+    /// Hide a word's reading in practice until the definition is shown, as
+    /// test mode always does.
+    pub hide_reading: bool,
+    /// Minutes a Quick Review lasts; `QUICK_REVIEW_MINUTES` when unset.
+    pub quick_review_minutes: Option<u32>,
+    /// Text-to-speech command, given the text to speak as its last argument,
+    /// e.g. `"espeak"`. Read-aloud needs it.
+    pub tts_command: Option<String>,
+    /// Seconds read-aloud waits after a word before its definition, and
+    /// after the definition before the next word; `READ_ALOUD_PAUSE_SECS`
+    /// when unset.
+    pub read_aloud_pause_secs: Option<u32>,
+    /// Draw the interface with ASCII only and no color, as `--ascii` does.
+    pub ascii: bool,
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("vocabulator").join("config.toml"))
+}
+
+pub fn load() -> Result<Config> {
+    match config_path() {
+        Some(path) if path.exists() => load_from(&path),
+        _ => Ok(Config::default()),
+    }
+}
+
+pub fn load_from(path: &Path) -> Result<Config> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config {}", path.display()))?;
+    parse(&content).with_context(|| format!("Invalid config {}", path.display()))
+}
+
+pub fn parse(content: &str) -> Result<Config> {
+    Ok(toml::from_str(content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty() {
+        let config = parse("").unwrap();
+        assert!(config.keys.is_empty());
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        let config = parse(
+            r#"
+[keys]
+show = "d"
+quit = ["x", "esc"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.keys["show"].keys(), vec!["d"]);
+        assert_eq!(config.keys["quit"].keys(), vec!["x", "esc"]);
+    }
+
+    #[test]
+    fn test_parse_leeches() {
+        assert_eq!(parse("").unwrap().leeches, LeechPolicy::default());
+
+        let config = parse("[leeches]\nsuspend = true").unwrap();
+        assert_eq!(config.leeches.threshold, 8);
+        assert!(config.leeches.suspend);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse("[keys]\nshow = 3").is_err());
+    }
+}
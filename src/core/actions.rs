@@ -1,4 +1,5 @@
 use crate::core::{progress, session};
+use crate::db::queries;
 use crate::ui::app::{App, Screen};
 use anyhow::{Result, anyhow};
 
@@ -9,36 +10,145 @@ pub fn handle_enter(app: &mut App) -> Result<()> {
         return Ok(());
     }
 
-    let correct = session.graded.unwrap();
-    let word = session.current_mut();
-    progress::update_word_stats(&app.conn, word, correct)?;
+    let Some(correct) = session.graded else {
+        unreachable!("guarded above");
+    };
+    let next_index = (session.index + 1) % session.words.len().max(1);
+    // The first word is always from the session's own group; see
+    // `Session::is_mixed_in`.
+    let group_id = session
+        .words
+        .first()
+        .ok_or_else(|| anyhow!("Session has no words"))?
+        .group_id;
+    let next = (session.session_type == session::Type::Group).then_some((
+        app.current_screen,
+        group_id,
+        session.group_index(next_index),
+    ));
 
-    let finished = session.advance();
-
-    if session.session_type == session::Type::Group {
-        progress::save_progress(
+    let persist = session.persist;
+    let session_type = session.session_type;
+    let answer =
+        (app.current_screen == Screen::Test).then(|| session.input_buffer.text().to_string());
+    let word = session
+        .current_mut()
+        .ok_or_else(|| anyhow!("Session has no words"))?;
+    let mut mastered = None;
+    if persist {
+        let newly_mastered = progress::record_grade(
             &app.conn,
-            (
-                app.current_screen,
-                session.current().group_id,
-                session.index,
-            ),
+            app.clock.as_ref(),
+            app.leeches,
+            word,
+            correct,
+            app.current_screen,
+            next,
         )?;
+        mastered = newly_mastered.then(|| word.word.clone());
+        // Only the end-of-test summary reads it, so it needn't share the
+        // grade's transaction.
+        if let Some(answer) = &answer {
+            queries::save_answer(&app.conn, word.id, answer)?;
+        }
+    } else {
+        progress::apply_grade(word, correct, app.clock.now());
     }
 
+    let finished = session.advance();
+    // The next autosave picks up the new position for the session snapshot.
+    session.unsaved = true;
+    session.load_history(&app.conn)?;
+
     if finished {
         if app.current_screen == Screen::Test {
-            if session.session_type == session::Type::Group {
-                progress::save_progress(
-                    &app.conn,
-                    (Screen::Practice, session.current().group_id + 1, 0),
-                )?;
+            if persist && session_type == session::Type::Group {
+                let next = progress::next_group(&app.conn, group_id)?.unwrap_or(group_id + 1);
+                progress::save_progress(&app.conn, (Screen::Practice, next, 0))?;
+            }
+            app.return_to_menu();
+            if session_type == session::Type::GroupTest {
+                app.show_test_summary();
             }
-            app.current_screen = Screen::Menu;
         } else {
             app.current_screen = Screen::Test;
         }
     }
+    if let Some(word) = mastered {
+        app.notify(format!("Mastered \"{word}\"!"), false);
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graded_app(correct: bool) -> App {
+        let mut app = App::new_test_seeded(&[("abate", "lessen", 1), ("abound", "plentiful", 1)]);
+        let session = app.session.as_mut().unwrap();
+        session.show_definition = true;
+        session.graded = Some(correct);
+        app
+    }
+
+    fn counters(app: &App) -> (i64, i64) {
+        app.conn
+            .query_row(
+                "SELECT times_seen, success_count FROM words WHERE id=1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_grade_reaches_word_stats() {
+        let mut app = graded_app(true);
+        handle_enter(&mut app).unwrap();
+        assert_eq!(counters(&app), (1, 1));
+
+        let mut app = graded_app(false);
+        handle_enter(&mut app).unwrap();
+        assert_eq!(counters(&app), (1, 0));
+        assert_eq!(app.session.as_ref().unwrap().index, 1);
+    }
+
+    #[test]
+    fn test_toast_when_a_word_is_mastered() {
+        let graded_after = |history: &str| {
+            let mut app = graded_app(true);
+            app.conn
+                .execute_batch(&format!(
+                    "INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES{history}"
+                ))
+                .unwrap();
+            handle_enter(&mut app).unwrap();
+            app
+        };
+
+        // Four right after a miss: one short.
+        let app = graded_after("(1,1,0,0), (1,2,1,0), (1,3,1,0), (1,4,1,0)");
+        assert!(app.toast.is_none());
+
+        let app = graded_after("(1,1,1,0), (1,2,1,0), (1,3,1,0), (1,4,1,0)");
+        let toast = app.toast.as_ref().unwrap();
+        assert_eq!(toast.message, "Mastered \"abate\"!");
+        assert!(!toast.is_error);
+    }
+
+    #[test]
+    fn test_failed_write_keeps_grade() {
+        let mut app = graded_app(true);
+        app.conn.execute_batch("DROP TABLE reviews").unwrap();
+
+        assert!(handle_enter(&mut app).is_err());
+
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.index, 0);
+        assert_eq!(session.graded, Some(true));
+        assert!(session.show_definition);
+        assert_eq!(counters(&app), (0, 0));
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::Utc;
+use std::fmt;
+
+/// How far ahead of the clock a stored timestamp may be before it counts as
+/// skew rather than the clock having been a little slow.
+pub const SKEW_TOLERANCE: i64 = 5 * 60;
+
+/// Source of the current time as unix seconds, so tests can fake it.
+pub trait Clock: fmt::Debug {
+    fn now(&self) -> i64;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        Utc::now().timestamp()
+    }
+}
+
+#[cfg(test)]
+pub use mock::MockClock;
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Fixed time that a test moves by hand; clones share it.
+    #[derive(Debug, Default, Clone)]
+    pub struct MockClock(Rc<Cell<i64>>);
+
+    impl MockClock {
+        pub fn at(now: i64) -> Self {
+            Self(Rc::new(Cell::new(now)))
+        }
+
+        pub fn set(&self, now: i64) {
+            self.0.set(now);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> i64 {
+            self.0.get()
+        }
+    }
+}
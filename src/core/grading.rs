@@ -0,0 +1,178 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Match,
+    /// In the expected word but not in the answer.
+    Missing,
+    /// In the answer but not in the expected word.
+    Extra,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffSegment {
+    pub text: String,
+    pub kind: DiffKind,
+}
+
+/// Expected word and typed answer split into matching and differing runs.
+/// `expected` only holds `Match`/`Missing` segments, `answer` only
+/// `Match`/`Extra`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnswerDiff {
+    pub expected: Vec<DiffSegment>,
+    pub answer: Vec<DiffSegment>,
+}
+
+/// Whether `answer` is the word, or its reading when it has one.
+pub fn is_correct(answer: &str, word: &str, reading: Option<&str>) -> bool {
+    let answer = answer.trim();
+    answer.eq_ignore_ascii_case(word)
+        || reading.is_some_and(|r| !r.is_empty() && answer.eq_ignore_ascii_case(r))
+}
+
+/// Grapheme-level diff of `answer` against `expected` using a longest common
+/// subsequence, compared case-insensitively like `is_correct`.
+pub fn diff_answer(expected: &str, answer: &str) -> AnswerDiff {
+    let exp: Vec<&str> = expected.graphemes(true).collect();
+    let ans: Vec<&str> = answer.trim().graphemes(true).collect();
+    let same = |a: &str, b: &str| a.to_lowercase() == b.to_lowercase();
+
+    // lcs[i][j] = LCS length of exp[i..] and ans[j..]
+    let mut lcs = vec![vec![0usize; ans.len() + 1]; exp.len() + 1];
+    for i in (0..exp.len()).rev() {
+        for j in (0..ans.len()).rev() {
+            lcs[i][j] = if same(exp[i], ans[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = AnswerDiff::default();
+    let (mut i, mut j) = (0, 0);
+    while i < exp.len() || j < ans.len() {
+        if i < exp.len() && j < ans.len() && same(exp[i], ans[j]) {
+            push(&mut diff.expected, exp[i], DiffKind::Match);
+            push(&mut diff.answer, ans[j], DiffKind::Match);
+            i += 1;
+            j += 1;
+        } else if j < ans.len() && (i == exp.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            push(&mut diff.answer, ans[j], DiffKind::Extra);
+            j += 1;
+        } else {
+            push(&mut diff.expected, exp[i], DiffKind::Missing);
+            i += 1;
+        }
+    }
+
+    diff
+}
+
+fn push(segments: &mut Vec<DiffSegment>, grapheme: &str, kind: DiffKind) {
+    match segments.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(grapheme),
+        _ => segments.push(DiffSegment {
+            text: grapheme.to_string(),
+            kind,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DiffKind::*;
+
+    fn seg(text: &str, kind: DiffKind) -> DiffSegment {
+        DiffSegment {
+            text: text.into(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let diff = diff_answer("abound", " Abound ");
+        assert_eq!(diff.expected, vec![seg("abound", Match)]);
+        assert_eq!(diff.answer, vec![seg("Abound", Match)]);
+    }
+
+    #[test]
+    fn test_missing_character() {
+        let diff = diff_answer("abound", "abond");
+        assert_eq!(
+            diff.expected,
+            vec![seg("abo", Match), seg("u", Missing), seg("nd", Match)]
+        );
+        assert_eq!(diff.answer, vec![seg("abond", Match)]);
+    }
+
+    #[test]
+    fn test_extra_character() {
+        let diff = diff_answer("abate", "abbate");
+        assert_eq!(diff.expected, vec![seg("abate", Match)]);
+        assert_eq!(
+            diff.answer,
+            vec![seg("ab", Match), seg("b", Extra), seg("ate", Match)]
+        );
+    }
+
+    #[test]
+    fn test_substitution() {
+        let diff = diff_answer("austere", "austire");
+        assert_eq!(
+            diff.expected,
+            vec![seg("aust", Match), seg("e", Missing), seg("re", Match)]
+        );
+        assert_eq!(
+            diff.answer,
+            vec![seg("aust", Match), seg("i", Extra), seg("re", Match)]
+        );
+    }
+
+    #[test]
+    fn test_empty_answer() {
+        let diff = diff_answer("abate", "");
+        assert_eq!(diff.expected, vec![seg("abate", Missing)]);
+        assert!(diff.answer.is_empty());
+    }
+
+    #[test]
+    fn test_graphemes_are_not_split() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let diff = diff_answer(&format!("a{family}b"), "a👨b");
+        assert_eq!(
+            diff.expected,
+            vec![seg("a", Match), seg(family, Missing), seg("b", Match)]
+        );
+        assert_eq!(
+            diff.answer,
+            vec![seg("a", Match), seg("👨", Extra), seg("b", Match)]
+        );
+
+        // e + combining acute stays one unit
+        let diff = diff_answer("cafe\u{301}", "cafe");
+        assert_eq!(
+            diff.expected,
+            vec![seg("caf", Match), seg("e\u{301}", Missing)]
+        );
+        assert_eq!(diff.answer, vec![seg("caf", Match), seg("e", Extra)]);
+    }
+
+    #[test]
+    fn test_is_correct() {
+        assert!(is_correct("  Abound ", "abound", None));
+        assert!(!is_correct("abond", "abound", None));
+    }
+
+    #[test]
+    fn test_headword_or_reading_is_correct() {
+        assert!(is_correct("日本", "日本", Some("にほん")));
+        assert!(is_correct(" にほん ", "日本", Some("にほん")));
+        assert!(!is_correct("にっぽん", "日本", Some("にほん")));
+        assert!(!is_correct("", "日本", Some("")));
+        assert!(is_correct("Kyoto", "京都", Some("kyoto")));
+    }
+}
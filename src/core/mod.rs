@@ -1,4 +1,8 @@
 pub mod actions;
+pub mod clock;
+pub mod grading;
+pub mod preview;
 pub mod progress;
 pub mod session;
+pub mod stats;
 pub mod utils;
@@ -0,0 +1,200 @@
+use crate::core::session::Type;
+use crate::core::stats::{WEAK_LIMIT, WEAK_MIN_SEEN};
+use crate::db::queries;
+use crate::error::Result;
+use rusqlite::Connection;
+use std::time::Duration;
+
+/// Pace assumed until there are reviews to measure it from.
+pub const DEFAULT_SECONDS_PER_WORD: f64 = 8.0;
+
+/// What starting a session would give, from counts alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionPreview {
+    pub words: i64,
+    /// Never seen before; the rest are reviews.
+    pub new: i64,
+    /// Group a Group session works through, and its name.
+    pub group: Option<(i32, Option<String>)>,
+    pub seconds_per_word: f64,
+}
+
+impl SessionPreview {
+    pub fn review(&self) -> i64 {
+        self.words - self.new
+    }
+
+    pub fn estimate(&self) -> Duration {
+        Duration::from_secs_f64(self.words as f64 * self.seconds_per_word)
+    }
+}
+
+/// Average seconds per word over past sessions, or
+/// `DEFAULT_SECONDS_PER_WORD` before there are any.
+pub fn seconds_per_word(conn: &Connection) -> Result<f64> {
+    let (seconds, words) = queries::fetch_review_pace(conn)?;
+    Ok(pace(seconds, words))
+}
+
+fn pace(seconds: i64, words: i64) -> f64 {
+    if seconds > 0 && words > 0 {
+        seconds as f64 / words as f64
+    } else {
+        DEFAULT_SECONDS_PER_WORD
+    }
+}
+
+/// Words a Quick Review of `limit` holds at `seconds_per_word`; at least
+/// one, so a short limit still reviews something.
+pub fn quick_size(seconds_per_word: f64, limit: Duration) -> usize {
+    ((limit.as_secs_f64() / seconds_per_word).floor() as usize).max(1)
+}
+
+/// "under a minute", "~4 min" or "~1 h 5 min".
+pub fn duration_label(duration: Duration) -> String {
+    let minutes = (duration.as_secs_f64() / 60.0).round() as u64;
+    match minutes {
+        0 => "under a minute".into(),
+        1..60 => format!("~{minutes} min"),
+        _ if minutes.is_multiple_of(60) => format!("~{} h", minutes / 60),
+        _ => format!("~{} h {} min", minutes / 60, minutes % 60),
+    }
+}
+
+/// Preview of a `session_type` session, matching what `start_session`
+/// would pick, or `None` when it would have no words.
+pub fn load(
+    conn: &Connection,
+    session_type: Type,
+    mix_marked: usize,
+    quick_review: Duration,
+) -> Result<Option<SessionPreview>> {
+    let seconds_per_word = seconds_per_word(conn)?;
+    let mut group = None;
+    let (words, new) = match session_type {
+        Type::Group => {
+            let ((_, group_id, index), _) = queries::recover_progress(conn)?;
+            let (words, new) = queries::count_group_from(conn, group_id, index)?;
+            // One marked word fits after each of the group's words.
+            let (mixed, mixed_new) = if mix_marked > 0 && words > 0 {
+                let limit = mix_marked.min(words as usize);
+                queries::count_marked_outside_group(conn, group_id, limit)?
+            } else {
+                (0, 0)
+            };
+            group = Some((group_id, queries::fetch_group_name(conn, group_id)?));
+            (words + mixed, new + mixed_new)
+        }
+        Type::Marked => queries::count_marked_session(conn)?,
+        // Both need misses, so none of their words are new.
+        Type::Weak => (
+            queries::count_weak_words(conn, WEAK_MIN_SEEN, WEAK_LIMIT)?,
+            0,
+        ),
+        Type::Leech => (queries::count_leech_words(conn)?, 0),
+        Type::Quick => {
+            let size = quick_size(seconds_per_word, quick_review) as i64;
+            (queries::count_seen_words(conn)?.min(size), 0)
+        }
+        Type::GroupTest | Type::Custom => return Ok(None),
+    };
+    if words == 0 {
+        return Ok(None);
+    }
+    Ok(Some(SessionPreview {
+        words,
+        new,
+        group,
+        seconds_per_word,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use crate::ui::app::Screen;
+
+    const QUICK: Duration = Duration::from_secs(300);
+
+    #[test]
+    fn test_estimate_math() {
+        assert_eq!(pace(0, 0), DEFAULT_SECONDS_PER_WORD);
+        assert_eq!(pace(0, 5), DEFAULT_SECONDS_PER_WORD);
+        assert_eq!(pace(300, 20), 15.0);
+
+        let preview = SessionPreview {
+            words: 30,
+            new: 12,
+            group: None,
+            seconds_per_word: 15.0,
+        };
+        assert_eq!(preview.review(), 18);
+        assert_eq!(preview.estimate(), Duration::from_secs(450));
+        assert_eq!(duration_label(preview.estimate()), "~8 min");
+
+        assert_eq!(duration_label(Duration::from_secs(20)), "under a minute");
+        assert_eq!(duration_label(Duration::from_secs(40)), "~1 min");
+        assert_eq!(duration_label(Duration::from_secs(3600)), "~1 h");
+        assert_eq!(duration_label(Duration::from_secs(3900)), "~1 h 5 min");
+
+        assert_eq!(quick_size(15.0, QUICK), 20);
+        assert_eq!(quick_size(7.0, QUICK), 42);
+        assert_eq!(quick_size(90.0, Duration::from_secs(60)), 1);
+    }
+
+    #[test]
+    fn test_pace_from_review_history() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'a','d',1), (2,'b','d',1), (3,'c','d',1);
+             INSERT INTO sessions(id,started_at,type) VALUES(1,0,0), (2,0,0), (3,0,0);
+             -- 60s over two words, practice and test.
+             INSERT INTO reviews(word_id,reviewed_at,correct,mode,session_id) VALUES
+                 (1,1000,1,0,1), (2,1020,1,0,1), (1,1040,1,1,1), (2,1060,1,1,1),
+             -- 30s over one word.
+                 (3,5000,0,0,2), (3,5030,1,1,2),
+             -- A single review says nothing about pace.
+                 (1,9000,1,0,3),
+                 (2,9999,1,0,NULL)",
+        )
+        .unwrap();
+        assert_eq!(seconds_per_word(&conn).unwrap(), 30.0);
+    }
+
+    #[test]
+    fn test_group_preview_counts_from_saved_position() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,times_seen,marked,last_seen) VALUES
+                 (1,'a','d',2,1,0,NULL), (2,'b','d',2,0,0,NULL), (3,'c','d',2,0,0,NULL),
+                 (4,'e','d',2,3,0,NULL), (5,'x','d',1,0,1,NULL), (6,'y','d',1,2,1,100),
+                 (7,'z','d',3,0,1,NULL);
+             INSERT INTO group_names(group_id,name) VALUES(2,'Verbs')",
+        )
+        .unwrap();
+        queries::save_progress(&conn, (Screen::Practice, 2, 1)).unwrap();
+
+        let preview = load(&conn, Type::Group, 0, QUICK).unwrap().unwrap();
+        assert_eq!((preview.words, preview.new), (3, 2));
+        assert_eq!(preview.group, Some((2, Some("Verbs".into()))));
+        assert_eq!(preview.seconds_per_word, DEFAULT_SECONDS_PER_WORD);
+
+        // Unseen marked words are mixed in first.
+        let preview = load(&conn, Type::Group, 2, QUICK).unwrap().unwrap();
+        assert_eq!((preview.words, preview.new), (5, 4));
+
+        let marked = load(&conn, Type::Marked, 0, QUICK).unwrap().unwrap();
+        assert_eq!((marked.words, marked.new, marked.group), (3, 2, None));
+        let weak = load(&conn, Type::Weak, 0, QUICK).unwrap().unwrap();
+        assert_eq!((weak.words, weak.new), (2, 0));
+        assert_eq!(load(&conn, Type::Leech, 0, QUICK).unwrap(), None);
+        // Only words already seen, as many as fit in the time.
+        let quick = load(&conn, Type::Quick, 0, QUICK).unwrap().unwrap();
+        assert_eq!((quick.words, quick.new), (3, 0));
+        let short = Duration::from_secs(16);
+        let quick = load(&conn, Type::Quick, 0, short).unwrap().unwrap();
+        assert_eq!(quick.words, 2);
+    }
+}
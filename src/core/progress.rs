@@ -1,10 +1,14 @@
 use crate::{
-    db::{models::Word, queries},
+    core::clock::Clock,
+    core::stats::{LEECH_RECOVERY_STREAK, LeechPolicy, MASTERED_PERCENT, MASTERED_STREAK},
+    db::{
+        models::{DeletedWord, Word},
+        queries, with_tx,
+    },
+    error::Result,
     ui::app::Screen,
 };
-use anyhow::Result;
 use rusqlite::Connection;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn save_progress(conn: &Connection, progress: (Screen, i32, usize)) -> Result<()> {
     let (screen, mut group_id, index) = progress;
@@ -15,12 +19,373 @@ pub fn save_progress(conn: &Connection, progress: (Screen, i32, usize)) -> Resul
         group_id = 1;
     }
 
+    tracing::debug!(?screen, group_id, index, "saving progress");
+
     queries::save_progress(conn, (screen, group_id, index))
 }
 
-pub fn update_word_stats(conn: &Connection, word: &mut Word, correct: bool) -> Result<()> {
-    word.last_seen = Some(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i32);
-    word.times_seen += 1;
-    word.success_count += correct as u8;
-    queries::update_word_stats(conn, &word)
+/// The group progress moves on to after `group_id`: the next one that isn't
+/// archived, wrapping around to the first. None if every group is.
+pub fn next_group(conn: &Connection, group_id: i32) -> Result<Option<i32>> {
+    match queries::fetch_next_group(conn, group_id)? {
+        Some(next) => Ok(Some(next)),
+        None => queries::fetch_next_group(conn, 0),
+    }
+}
+
+/// Counts a graded attempt on `word` in memory only, as sessions that don't
+/// persist do. Returns the time it was recorded at.
+pub fn apply_grade(word: &mut Word, correct: bool, now: i64) -> i32 {
+    let now = (now as i32).max(word.last_seen.unwrap_or(0));
+    word.last_seen = Some(now);
+    word.times_seen = word.times_seen.saturating_add(1);
+    word.success_count = word.success_count.saturating_add(correct.into());
+    now
+}
+
+/// Records a graded attempt made on `screen`, both on the word's counters
+/// and in the review history. The time never goes back before the word's
+/// `last_seen`, so a clock set back briefly can't reorder its history.
+///
+/// A miss that takes the word's total misses to `leeches.threshold` flags
+/// it as a leech; `LEECH_RECOVERY_STREAK` correct answers in a row clear
+/// the flag again. Its total stays over the threshold, so a recovered word
+/// is flagged again by its next miss.
+///
+/// `MASTERED_STREAK` correct answers in a row at `MASTERED_PERCENT`
+/// accuracy or better mark the word mastered, and any miss after that puts
+/// it back in rotation. Returns true when this attempt mastered it.
+pub fn update_word_stats(
+    conn: &Connection,
+    clock: &dyn Clock,
+    leeches: LeechPolicy,
+    word: &mut Word,
+    correct: bool,
+    screen: Screen,
+) -> Result<bool> {
+    let now = apply_grade(word, correct, clock.now());
+    tracing::debug!(word_id = word.id, correct, ?screen, "graded");
+    queries::update_word_stats(conn, word)?;
+    queries::insert_review(conn, word.id, now, correct, screen)?;
+
+    let misses = word.times_seen - word.success_count;
+    if !correct {
+        if misses >= u32::from(leeches.threshold)
+            && queries::flag_leech(conn, word.id, leeches.suspend)?
+        {
+            tracing::info!(word_id = word.id, misses, "flagged as leech");
+        }
+        if queries::set_mastered(conn, word.id, None)? {
+            tracing::info!(word_id = word.id, "no longer mastered");
+        }
+        return Ok(false);
+    }
+
+    let streak = LEECH_RECOVERY_STREAK.max(MASTERED_STREAK);
+    let recent = queries::fetch_recent_outcomes(conn, word.id, streak)?;
+    let run = recent.iter().rev().take_while(|&&c| c).count();
+    if run >= LEECH_RECOVERY_STREAK && queries::clear_leech(conn, word.id)? {
+        tracing::info!(word_id = word.id, "no longer a leech");
+    }
+    let accurate =
+        i64::from(word.success_count) * 100 >= i64::from(word.times_seen) * MASTERED_PERCENT;
+    let mastered = run >= MASTERED_STREAK
+        && accurate
+        && queries::set_mastered(conn, word.id, Some(now.into()))?;
+    if mastered {
+        tracing::info!(word_id = word.id, "mastered");
+    }
+    Ok(mastered)
+}
+
+/// Applies a grade in one transaction: the word's counters, its review
+/// row, the last-graded marker and, for Group sessions, the position to
+/// resume at. On error nothing is written and `word` is left as it was.
+/// Returns true when the grade mastered the word.
+pub fn record_grade(
+    conn: &Connection,
+    clock: &dyn Clock,
+    leeches: LeechPolicy,
+    word: &mut Word,
+    correct: bool,
+    screen: Screen,
+    next: Option<(Screen, i32, usize)>,
+) -> anyhow::Result<bool> {
+    let mut updated = word.clone();
+    let mastered = with_tx(conn, |tx| {
+        // Saved first, while `next`'s index still counts this word: the
+        // grade may take it out of the group.
+        if let Some(next) = next {
+            save_progress(tx, next)?;
+        }
+        let mastered = update_word_stats(tx, clock, leeches, &mut updated, correct, screen)?;
+        queries::save_last_graded(tx, updated.id)?;
+        Ok(mastered)
+    })?;
+    *word = updated;
+    Ok(mastered)
+}
+
+/// Deletes a word and its review history in one transaction, moving saved
+/// progress off it. Returns what `restore_word` needs to undo it, or
+/// `None` if the word was already gone.
+pub fn delete_word(conn: &Connection, word_id: i32) -> anyhow::Result<Option<DeletedWord>> {
+    let deleted = with_tx(conn, |tx| Ok(queries::delete_word(tx, word_id)?))?;
+    if deleted.is_some() {
+        tracing::info!(word_id, "deleted word");
+    }
+    Ok(deleted)
+}
+
+pub fn restore_word(conn: &Connection, deleted: &DeletedWord) -> anyhow::Result<()> {
+    with_tx(conn, |tx| Ok(queries::restore_word(tx, deleted)?))?;
+    tracing::info!(word_id = deleted.word_id, "restored deleted word");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::{MockClock, SystemClock};
+    use crate::db::test_conn;
+    use crate::logging::testing::Captured;
+
+    #[test]
+    fn test_grading_is_logged() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(id,word,definition,group_id) VALUES(7,'abate','lessen',1)",
+            [],
+        )
+        .unwrap();
+        let mut word = queries::fetch_words_by_group(&conn, 1).unwrap().remove(0);
+
+        let log = Captured::default();
+        log.run(|| {
+            update_word_stats(
+                &conn,
+                &SystemClock,
+                LeechPolicy::default(),
+                &mut word,
+                true,
+                Screen::Test,
+            )
+            .unwrap()
+        });
+
+        let text = log.text();
+        assert!(text.contains("graded"), "{text}");
+        assert!(text.contains("word_id=7 correct=true"), "{text}");
+    }
+
+    fn one_word() -> (Connection, Word) {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(id,word,definition,group_id) VALUES(7,'abate','lessen',1)",
+            [],
+        )
+        .unwrap();
+        let word = queries::fetch_words_by_group(&conn, 1).unwrap().remove(0);
+        (conn, word)
+    }
+
+    #[test]
+    fn test_failed_grade_rolls_back_every_write() {
+        let (conn, mut word) = one_word();
+        // The progress save goes to app_state; make it fail.
+        conn.execute_batch("DROP TABLE app_state").unwrap();
+
+        let result = record_grade(
+            &conn,
+            &SystemClock,
+            LeechPolicy::default(),
+            &mut word,
+            true,
+            Screen::Practice,
+            Some((Screen::Practice, 1, 0)),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(word.times_seen, 0);
+        let (seen, reviews): (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT times_seen FROM words WHERE id=7), (SELECT COUNT(*) FROM reviews)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((seen, reviews), (0, 0));
+    }
+
+    #[test]
+    fn test_timestamps_never_go_backwards() {
+        let (conn, mut word) = one_word();
+        let clock = MockClock::at(1_700_000_000);
+        let reviewed_at = |conn: &Connection| -> Vec<i32> {
+            conn.prepare("SELECT reviewed_at FROM reviews ORDER BY id")
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+
+        record_grade(
+            &conn,
+            &clock,
+            LeechPolicy::default(),
+            &mut word,
+            true,
+            Screen::Practice,
+            None,
+        )
+        .unwrap();
+        // Clock set back an hour: the grade keeps the earlier time.
+        clock.set(1_700_000_000 - 3600);
+        record_grade(
+            &conn,
+            &clock,
+            LeechPolicy::default(),
+            &mut word,
+            true,
+            Screen::Practice,
+            None,
+        )
+        .unwrap();
+        assert_eq!(word.last_seen, Some(1_700_000_000));
+
+        // Clock jumps ahead: later grades follow it.
+        clock.set(1_700_000_000 + 60);
+        record_grade(
+            &conn,
+            &clock,
+            LeechPolicy::default(),
+            &mut word,
+            false,
+            Screen::Practice,
+            None,
+        )
+        .unwrap();
+        assert_eq!(word.last_seen, Some(1_700_000_060));
+        assert_eq!(
+            reviewed_at(&conn),
+            vec![1_700_000_000, 1_700_000_000, 1_700_000_060]
+        );
+    }
+
+    fn grade(conn: &Connection, policy: LeechPolicy, word: &mut Word, correct: bool) -> bool {
+        record_grade(
+            conn,
+            &SystemClock,
+            policy,
+            word,
+            correct,
+            Screen::Practice,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_leech_flagged_at_threshold() {
+        let (conn, mut word) = one_word();
+        let policy = LeechPolicy {
+            threshold: 3,
+            suspend: false,
+        };
+
+        for correct in [false, true, false] {
+            grade(&conn, policy, &mut word, correct);
+        }
+        assert!(!queries::is_leech(&conn, 7).unwrap());
+
+        grade(&conn, policy, &mut word, false);
+        assert!(queries::is_leech(&conn, 7).unwrap());
+        assert_eq!(queries::count_leech_words(&conn).unwrap(), 1);
+        // Not suspended: still part of its group.
+        assert_eq!(queries::fetch_words_by_group(&conn, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_suspended_leech_leaves_sessions_until_recovered() {
+        let (conn, mut word) = one_word();
+        let policy = LeechPolicy {
+            threshold: 2,
+            suspend: true,
+        };
+        grade(&conn, policy, &mut word, false);
+        grade(&conn, policy, &mut word, false);
+
+        assert!(queries::fetch_words_by_group(&conn, 1).unwrap().is_empty());
+        assert_eq!(queries::fetch_leech_words(&conn).unwrap().len(), 1);
+
+        // Correct answers in a row clear the flag, but only a full streak.
+        for _ in 1..LEECH_RECOVERY_STREAK {
+            grade(&conn, policy, &mut word, true);
+        }
+        assert!(queries::is_leech(&conn, 7).unwrap());
+        grade(&conn, policy, &mut word, true);
+        assert!(!queries::is_leech(&conn, 7).unwrap());
+        assert_eq!(queries::fetch_words_by_group(&conn, 1).unwrap().len(), 1);
+        assert!(queries::fetch_leech_words(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mastered_after_streak_until_a_miss() {
+        let (conn, mut word) = one_word();
+        let policy = LeechPolicy::default();
+        let mastered_at = |conn: &Connection| -> Option<i64> {
+            conn.query_row("SELECT mastered_at FROM words WHERE id=7", [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+
+        grade(&conn, policy, &mut word, false);
+        // Five in a row, but 5 of 6 is under 90%.
+        for _ in 0..MASTERED_STREAK {
+            assert!(!grade(&conn, policy, &mut word, true));
+        }
+        assert_eq!(mastered_at(&conn), None);
+        for _ in 0..3 {
+            assert!(!grade(&conn, policy, &mut word, true));
+        }
+        // 9 of 10: mastered, and only the crossing grade says so.
+        assert!(grade(&conn, policy, &mut word, true));
+        assert_eq!(mastered_at(&conn), word.last_seen.map(i64::from));
+        assert!(!grade(&conn, policy, &mut word, true));
+        assert!(queries::fetch_words_by_group(&conn, 1).unwrap().is_empty());
+
+        grade(&conn, policy, &mut word, false);
+        assert_eq!(mastered_at(&conn), None);
+        assert_eq!(queries::fetch_words_by_group(&conn, 1).unwrap().len(), 1);
+    }
+
+    /// cargo test --release -- --ignored grading_throughput --nocapture
+    ///
+    /// In-memory db, release build: about 33µs per grade with plain
+    /// `prepare`, about 12µs with `prepare_cached`.
+    #[test]
+    #[ignore]
+    fn test_grading_throughput() {
+        let (conn, mut word) = one_word();
+        let rounds = 10_000;
+
+        let started = std::time::Instant::now();
+        for i in 0..rounds {
+            record_grade(
+                &conn,
+                &SystemClock,
+                LeechPolicy::default(),
+                &mut word,
+                i % 2 == 0,
+                Screen::Practice,
+                Some((Screen::Practice, 1, 0)),
+            )
+            .unwrap();
+        }
+        let per_grade = started.elapsed() / rounds;
+        println!("{per_grade:?} per grade");
+        assert!(per_grade < std::time::Duration::from_millis(1));
+    }
 }
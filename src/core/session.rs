@@ -1,8 +1,26 @@
-use crate::db::models::Word;
+use crate::core::clock::Clock;
+use crate::core::grading::AnswerDiff;
+use crate::core::preview;
+use crate::core::progress;
+use crate::core::stats;
+use crate::db::models::{SessionSnapshot, Word};
 use crate::db::queries;
+use crate::db::store::WordStore;
+use crate::error::{Result, VocabError};
 use crate::ui::app::Screen;
-use anyhow::Result;
+use crate::ui::widgets::LineEdit;
 use rusqlite::Connection;
+use std::time::Duration;
+
+/// Number of past outcomes shown for the current word.
+pub const HISTORY_LEN: usize = 10;
+
+/// Length of a Quick Review when the config doesn't set one.
+pub const QUICK_REVIEW_MINUTES: u32 = 5;
+
+/// Read-aloud pause between a word and its definition when the config
+/// doesn't set one.
+pub const READ_ALOUD_PAUSE_SECS: u32 = 3;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub enum Type {
@@ -10,6 +28,10 @@ pub enum Type {
     Group,
     Marked,
     Weak,
+    Leech,
+    Quick,
+    /// Typed answers over one group, picked from the stats screen.
+    GroupTest,
     #[allow(dead_code)]
     Custom,
 }
@@ -21,9 +43,40 @@ impl Type {
             Group => "Continue Learning",
             Marked => "Review Marks",
             Weak => "Revise Weak",
+            Leech => "Review Leeches",
+            Quick => "Quick Review",
+            GroupTest => "Group Test",
             Custom => "Custom Query",
         }
     }
+
+    /// Short name for session history lines.
+    pub fn short_label(&self) -> &'static str {
+        use Type::*;
+        match self {
+            Group => "Group",
+            Marked => "Marks",
+            Weak => "Weak",
+            Leech => "Leeches",
+            Quick => "Quick",
+            GroupTest => "Test",
+            Custom => "Custom",
+        }
+    }
+
+    /// What the session draws from, for error messages.
+    fn kind(&self) -> &'static str {
+        use Type::*;
+        match self {
+            Group => "group",
+            Marked => "marked list",
+            Weak => "weak list",
+            Leech => "leech list",
+            Quick => "review list",
+            GroupTest => "group",
+            Custom => "custom query",
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -35,8 +88,37 @@ pub struct Session {
     pub session_type: Type,
     pub show_definition: bool,
     pub graded: Option<bool>,
-    pub input_buffer: String,
+    pub input_buffer: LineEdit,
     pub insert_mode: bool,
+    pub answer_diff: Option<AnswerDiff>,
+    /// Recent outcomes of the current word, oldest first. Loaded once when
+    /// the word becomes current rather than on every frame.
+    pub history: Option<Vec<bool>>,
+    /// Current word is flagged as a leech; loaded with `history`.
+    pub leech: bool,
+    /// Current word's IPA transcription, if it has one; loaded with
+    /// `history`.
+    pub ipa: Option<String>,
+    /// Current word's reading, if it has one; loaded with `history`.
+    pub reading: Option<String>,
+    /// Changed since progress was last written; cleared by a save.
+    pub unsaved: bool,
+    /// Grades, marks and position are written to the database. Off in
+    /// read-only mode, where the session runs in memory.
+    pub persist: bool,
+    /// Clock time a Quick Review stops at, wherever it has got to, before
+    /// adding `paused_secs`.
+    pub deadline: Option<i64>,
+    /// Clock time the session was paused at, while it is.
+    pub paused_at: Option<i64>,
+    /// Seconds spent paused in earlier pauses.
+    pub paused_secs: i64,
+    /// Clock time of the next read-aloud step, before adding
+    /// `paused_secs`, while the session is read aloud. Grading is off then.
+    pub read_aloud: Option<i64>,
+    /// Words another process added while the session was open. They wait
+    /// for the next session; this one keeps its words.
+    pub words_arrived: i64,
 }
 
 impl Session {
@@ -45,16 +127,71 @@ impl Session {
             words,
             index,
             session_type,
+            persist: true,
             ..Default::default()
         }
     }
 
-    pub fn current(&self) -> &Word {
-        &self.words[self.index]
+    pub fn pause(&mut self, now: i64) {
+        self.paused_at.get_or_insert(now);
+    }
+
+    /// Ends a pause, adding its length to `paused_secs`.
+    pub fn unpause(&mut self, now: i64) {
+        if let Some(at) = self.paused_at.take() {
+            self.paused_secs += (now - at).max(0);
+        }
+    }
+
+    /// True once a Quick Review has run for its time, not counting pauses.
+    /// A paused session never runs out.
+    pub fn out_of_time(&self, now: i64) -> bool {
+        self.paused_at.is_none()
+            && self
+                .deadline
+                .is_some_and(|deadline| now >= deadline + self.paused_secs)
+    }
+
+    /// True once the next read-aloud step is due, not counting pauses.
+    pub fn read_aloud_due(&self, now: i64) -> bool {
+        self.paused_at.is_none()
+            && self
+                .read_aloud
+                .is_some_and(|at| now >= at + self.paused_secs)
+    }
+
+    /// Word at `index`; `None` only for a malformed (empty or
+    /// out-of-range) session.
+    pub fn current(&self) -> Option<&Word> {
+        self.words.get(self.index)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut Word> {
+        self.words.get_mut(self.index)
     }
 
-    pub fn current_mut(&mut self) -> &mut Word {
-        &mut self.words[self.index]
+    /// Whether the word at `position` is a marked word mixed into a Group
+    /// session from another group. Those only go after the starting word,
+    /// so the first word always belongs to the session's group.
+    pub fn is_mixed_in(&self, position: usize) -> bool {
+        self.session_type == Type::Group
+            && match (self.words.first(), self.words.get(position)) {
+                (Some(first), Some(word)) => word.group_id != first.group_id,
+                _ => false,
+            }
+    }
+
+    /// `position` counted in the group's own words, as saved progress
+    /// expects: mixed-in words before it don't count. A mixed-in word after
+    /// the group's last one wraps to 0, as advancing past the end does.
+    pub fn group_index(&self, position: usize) -> usize {
+        let own = |range: std::ops::Range<usize>| range.filter(|&i| !self.is_mixed_in(i)).count();
+        let index = own(0..position);
+        if index == own(0..self.words.len()) {
+            0
+        } else {
+            index
+        }
     }
 
     pub fn reset_ui_state(&mut self) {
@@ -62,6 +199,34 @@ impl Session {
         self.graded = None;
         self.input_buffer.clear();
         self.insert_mode = false;
+        self.answer_diff = None;
+        self.history = None;
+        self.leech = false;
+        self.ipa = None;
+        self.reading = None;
+    }
+
+    pub fn snapshot(&self, mode: Screen) -> SessionSnapshot {
+        SessionSnapshot {
+            session_type: self.session_type,
+            mode,
+            word_ids: self.words.iter().map(|w| w.id).collect(),
+            index: self.index,
+            show_definition: self.show_definition,
+            graded: self.graded,
+            closed_cleanly: false,
+        }
+    }
+
+    pub fn load_history(&mut self, conn: &Connection) -> Result<()> {
+        let Some(id) = self.current().map(|w| w.id) else {
+            return Ok(());
+        };
+        self.history = Some(queries::fetch_recent_outcomes(conn, id, HISTORY_LEN)?);
+        self.leech = queries::is_leech(conn, id)?;
+        self.ipa = queries::fetch_ipa(conn, id)?;
+        self.reading = queries::fetch_reading(conn, id)?;
+        Ok(())
     }
 
     pub fn advance(&mut self) -> bool {
@@ -77,31 +242,425 @@ impl Session {
     }
 }
 
-pub fn start_session(conn: &Connection, session_type: Type) -> Result<(Session, Screen)> {
+/// Starts a session of `session_type`. Group sessions get up to
+/// `mix_marked` marked words from other groups mixed in, placed by the
+/// time on `clock`; a Quick Review gets what fits in `quick_review`.
+pub fn start_session(
+    conn: &Connection,
+    clock: &dyn Clock,
+    session_type: Type,
+    mix_marked: usize,
+    quick_review: Duration,
+) -> Result<(Session, Screen)> {
     match session_type {
-        Type::Group => group_session(&conn),
-        Type::Marked => marks_session(&conn),
-        Type::Weak => weak_session(&conn),
-        Type::Custom => anyhow::bail!("Custom session requires query input"),
+        Type::Group => group_session(conn, clock, mix_marked),
+        Type::Marked => marks_session(conn),
+        Type::Weak => weak_session(conn),
+        Type::Leech => leech_session(conn),
+        Type::Quick => quick_session(conn, quick_review),
+        // Neither says which words: no group, no query.
+        Type::GroupTest | Type::Custom => Err(VocabError::EmptySelection {
+            kind: session_type.kind(),
+        }),
+    }
+}
+
+/// Fails unless there is a word at `index` to show.
+fn checked(words: Vec<Word>, index: usize, session_type: Type) -> Result<Session> {
+    if words.is_empty() {
+        return Err(VocabError::EmptySelection {
+            kind: session_type.kind(),
+        });
+    }
+    if index >= words.len() {
+        return Err(VocabError::ProgressOutOfRange {
+            index,
+            len: words.len(),
+        });
     }
+    Ok(Session::new(words, index, session_type))
 }
 
-pub fn group_session(conn: &Connection) -> Result<(Session, Screen)> {
-    let (screen, group_id, index) = queries::fetch_progress(conn)?;
+pub fn group_session(
+    conn: &Connection,
+    clock: &dyn Clock,
+    mix_marked: usize,
+) -> Result<(Session, Screen)> {
+    group_session_from(conn, clock, queries::fetch_progress(conn)?, mix_marked)
+}
+
+fn group_session_from(
+    conn: &Connection,
+    clock: &dyn Clock,
+    (screen, group_id, index): (Screen, i32, usize),
+    mix_marked: usize,
+) -> Result<(Session, Screen)> {
+    let words = queries::fetch_words_by_group(conn, group_id)?;
+    let mut session = checked(words, index, Type::Group)?;
+
+    if mix_marked > 0 {
+        let marked = queries::fetch_marked_outside_group(conn, group_id, mix_marked)?;
+        mix_in(&mut session.words, index, marked, clock.now() as u64);
+    }
+
+    Ok((session, screen))
+}
+
+/// A Test-screen session over every word of `group_id`, in an order picked
+/// by `seed`. Saved progress stays where it is.
+pub fn group_test_session(
+    conn: &Connection,
+    group_id: i32,
+    seed: u64,
+) -> Result<(Session, Screen)> {
+    let mut words = queries::fetch_words_by_group(conn, group_id)?;
+    shuffle(&mut words, seed);
+    Ok((checked(words, 0, Type::GroupTest)?, Screen::Test))
+}
+
+/// Starts a Group session at the beginning of `group_id`, moving the saved
+/// progress there when `persist`.
+pub fn group_session_at(
+    conn: &Connection,
+    clock: &dyn Clock,
+    group_id: i32,
+    mix_marked: usize,
+    persist: bool,
+) -> Result<(Session, Screen)> {
+    let start = (Screen::Practice, group_id, 0);
+    if !persist {
+        return group_session_from(conn, clock, start, mix_marked);
+    }
+    progress::save_progress(conn, start)?;
+    group_session(conn, clock, mix_marked)
+}
+
+/// Inserts `extras` into `words` after position `start`, each right after
+/// a different word picked by `seed`, so no two extras are adjacent. Extras
+/// that don't fit are dropped.
+fn mix_in(words: &mut Vec<Word>, start: usize, extras: Vec<Word>, seed: u64) {
+    let mut gaps: Vec<usize> = (start..words.len()).collect();
+    let count = extras.len().min(gaps.len());
+
+    // Partial Fisher-Yates: the first `count` gaps end up a random pick.
+    let mut state = seed;
+    for i in 0..count {
+        let j = i + (splitmix(&mut state) % (gaps.len() - i) as u64) as usize;
+        gaps.swap(i, j);
+    }
+    let mut chosen = gaps[..count].to_vec();
+    chosen.sort_unstable();
+
+    // From the back, so earlier gaps keep their positions.
+    for (gap, extra) in chosen.into_iter().zip(extras).rev() {
+        words.insert(gap + 1, extra);
+    }
+}
 
-    let words = queries::fetch_words_by_group(&conn, group_id)?;
+/// Fisher-Yates over the whole of `words`.
+fn shuffle(words: &mut [Word], seed: u64) {
+    let mut state = seed;
+    for i in (1..words.len()).rev() {
+        let j = (splitmix(&mut state) % (i as u64 + 1)) as usize;
+        words.swap(i, j);
+    }
+}
 
-    Ok((Session::new(words, index, Type::Group), screen))
+fn splitmix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
 pub fn marks_session(conn: &Connection) -> Result<(Session, Screen)> {
-    let words = queries::fetch_marked_words(&conn)?;
+    let words = queries::fetch_marked_words(conn)?;
 
-    Ok((Session::new(words, 0, Type::Marked), Screen::Practice))
+    Ok((checked(words, 0, Type::Marked)?, Screen::Practice))
 }
 
 pub fn weak_session(conn: &Connection) -> Result<(Session, Screen)> {
-    let words = queries::fetch_weak_words(&conn)?;
+    let words = queries::fetch_weak_words(conn, stats::WEAK_MIN_SEEN, stats::WEAK_LIMIT)?;
+
+    Ok((checked(words, 0, Type::Weak)?, Screen::Practice))
+}
+
+pub fn leech_session(conn: &Connection) -> Result<(Session, Screen)> {
+    let words = queries::fetch_leech_words(conn)?;
+
+    Ok((checked(words, 0, Type::Leech)?, Screen::Practice))
+}
+
+/// Weak words first, then the least recently seen, as many as the usual
+/// pace gets through in `limit`. Words never seen are left to Group
+/// sessions.
+pub fn quick_session(conn: &Connection, limit: Duration) -> Result<(Session, Screen)> {
+    let size = preview::quick_size(preview::seconds_per_word(conn)?, limit);
+    let mut words = queries::fetch_weak_words(conn, stats::WEAK_MIN_SEEN, size)?;
+    for word in queries::fetch_stale_words(conn, size)? {
+        if words.len() >= size {
+            break;
+        }
+        if !words.iter().any(|w| w.id == word.id) {
+            words.push(word);
+        }
+    }
+
+    Ok((checked(words, 0, Type::Quick)?, Screen::Practice))
+}
+
+/// Rebuilds a session from a snapshot. Words are re-fetched so edits made
+/// since show up; deleted ones are dropped, which can leave the saved
+/// index past the end.
+pub fn snapshot_session(
+    conn: &Connection,
+    snapshot: &SessionSnapshot,
+) -> Result<(Session, Screen)> {
+    let store = WordStore::new(conn);
+    let words = snapshot
+        .word_ids
+        .iter()
+        .filter_map(|id| store.fetch(*id).transpose())
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((
+        checked(words, snapshot.index, snapshot.session_type)?,
+        snapshot.mode,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::MockClock;
+    use crate::db::test_conn;
+
+    fn word(id: i32) -> Word {
+        Word {
+            id,
+            word: format!("word{id}"),
+            definition: String::new(),
+            group_id: 1,
+            marked: false,
+            last_seen: None,
+            times_seen: 0,
+            success_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_new_session_walks_words() {
+        let mut session = Session::new(vec![word(1), word(2)], 1, Type::Weak);
+        assert_eq!(session.session_type, Type::Weak);
+        assert_eq!(session.current().map(|w| w.id), Some(2));
+        assert!(session.graded.is_none() && !session.show_definition);
+
+        session.show_definition = true;
+        session.graded = Some(false);
+        session.input_buffer.insert('a');
+        assert!(session.advance());
+        assert_eq!(session.current().map(|w| w.id), Some(1));
+        assert!(session.graded.is_none() && !session.show_definition);
+        assert!(session.input_buffer.text().is_empty());
+
+        session.insert_mode = true;
+        session.reset_ui_state();
+        assert!(!session.insert_mode);
+        assert!(!session.advance());
+    }
+
+    fn in_group(id: i32, group_id: i32) -> Word {
+        Word {
+            group_id,
+            ..word(id)
+        }
+    }
+
+    #[test]
+    fn test_mix_in_spacing_and_limits() {
+        let ids = |words: &[Word]| words.iter().map(|w| w.id).collect::<Vec<_>>();
+        for seed in 0..200 {
+            let mut words: Vec<Word> = (1..=6).map(|id| in_group(id, 1)).collect();
+            let extras = (101..=105).map(|id| in_group(id, 2)).collect();
+            mix_in(&mut words, 2, extras, seed);
 
-    Ok((Session::new(words, 0, Type::Weak), Screen::Practice))
+            // Only four words from the start on, so only four gaps.
+            assert_eq!(words.len(), 10, "seed {seed}");
+            let session = Session::new(words, 2, Type::Group);
+            let mixed: Vec<usize> = (0..10).filter(|&i| session.is_mixed_in(i)).collect();
+            assert_eq!(mixed.len(), 4);
+            assert!(mixed[0] > 2, "seed {seed}: mixed in before the start");
+            assert!(
+                mixed.windows(2).all(|w| w[1] - w[0] > 1),
+                "seed {seed}: adjacent at {mixed:?}"
+            );
+            let own: Vec<Word> = (0..10)
+                .filter(|&i| !session.is_mixed_in(i))
+                .map(|i| session.words[i].clone())
+                .collect();
+            assert_eq!(ids(&own), vec![1, 2, 3, 4, 5, 6]);
+        }
+
+        let mut words = vec![in_group(1, 1), in_group(2, 1)];
+        mix_in(&mut words, 0, vec![in_group(9, 2)], 7);
+        assert_eq!(words.len(), 3);
+    }
+
+    #[test]
+    fn test_mixed_words_leave_group_index_alone() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,marked) VALUES
+                (1,'a','d',1,0), (2,'b','d',1,0), (3,'c','d',1,1), (4,'e','d',1,0),
+                (11,'x','d',2,1), (12,'y','d',2,1), (21,'z','d',3,0);",
+        )
+        .unwrap();
+
+        let clock = MockClock::at(1_700_000_000);
+        let (session, _) = group_session(&conn, &clock, 5).unwrap();
+        // The clock alone decides where they go.
+        let (again, _) = group_session(&conn, &clock, 5).unwrap();
+        assert_eq!(again.words, session.words);
+        let mixed: Vec<i32> = (0..session.words.len())
+            .filter(|&i| session.is_mixed_in(i))
+            .map(|i| session.words[i].id)
+            .collect();
+        // Marked words from other groups only; word 3 is already here.
+        assert_eq!(session.words.len(), 6);
+        assert_eq!(mixed.len(), 2);
+        assert!(mixed.iter().all(|id| [11, 12].contains(id)));
+
+        for position in 0..session.words.len() {
+            let index = session.group_index(position);
+            progress::save_progress(&conn, (Screen::Practice, 1, index)).unwrap();
+            let (_, group, saved) = queries::fetch_progress(&conn).unwrap();
+            assert_eq!(group, 1);
+            // A mixed-in word resumes at the group word after it.
+            let expected = session.words[position..]
+                .iter()
+                .find(|w| w.group_id == 1)
+                .map_or(0, |w| {
+                    [1, 2, 3, 4].iter().position(|id| *id == w.id).unwrap()
+                });
+            assert_eq!(saved, expected, "position {position}");
+        }
+    }
+
+    #[test]
+    fn test_empty_marked_list() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(word,definition,group_id) VALUES('a','b',1)",
+            [],
+        )
+        .unwrap();
+
+        let err = marks_session(&conn).unwrap_err();
+        assert!(matches!(
+            err,
+            VocabError::EmptySelection {
+                kind: "marked list"
+            }
+        ));
+    }
+
+    #[test]
+    fn test_group_test_session_is_shuffled() {
+        let conn = test_conn();
+        for id in 1..=8 {
+            conn.execute(
+                "INSERT INTO words(id,word,definition,group_id) VALUES(?1,?2,'d',1)",
+                rusqlite::params![id, format!("w{id}")],
+            )
+            .unwrap();
+        }
+        let order = |seed| {
+            let (session, screen) = group_test_session(&conn, 1, seed).unwrap();
+            assert_eq!(
+                (session.session_type, screen),
+                (Type::GroupTest, Screen::Test)
+            );
+            session.words.iter().map(|w| w.id).collect::<Vec<_>>()
+        };
+        let first = order(7);
+        assert_eq!(first, order(7));
+        assert!((0..20).any(|seed| order(seed) != first));
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, (1..=8).collect::<Vec<_>>());
+        assert!(group_test_session(&conn, 2, 0).is_err());
+    }
+
+    #[test]
+    fn test_pauses_move_the_deadline() {
+        let mut session = Session {
+            deadline: Some(100),
+            ..Default::default()
+        };
+        session.pause(40);
+        assert!(!session.out_of_time(500));
+        // A second pause key doesn't restart the pause.
+        session.pause(60);
+        session.unpause(70);
+        session.pause(80);
+        session.unpause(90);
+        assert_eq!(session.paused_secs, 40);
+        assert!(!session.out_of_time(139));
+        assert!(session.out_of_time(140));
+
+        assert!(!Session::default().out_of_time(i64::MAX));
+    }
+
+    #[test]
+    fn test_quick_session_fits_the_time() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,times_seen,success_count,last_seen) VALUES
+                 (1,'a','d',1,4,4,100), (2,'b','d',1,4,1,900), (3,'c','d',1,2,2,50),
+                 (4,'e','d',1,0,0,NULL), (5,'f','d',1,1,1,300), (6,'g','d',1,5,3,800);",
+        )
+        .unwrap();
+
+        // Weak words first, weakest leading, then the stalest of the rest.
+        let (session, screen) = quick_session(&conn, Duration::from_secs(32)).unwrap();
+        let ids: Vec<i32> = session.words.iter().map(|w| w.id).collect();
+        assert_eq!(ids, vec![2, 6, 3, 1]);
+        assert_eq!(
+            (session.session_type, screen),
+            (Type::Quick, Screen::Practice)
+        );
+
+        // Never fewer than one word, never one not yet seen.
+        let (session, _) = quick_session(&conn, Duration::from_secs(1)).unwrap();
+        assert_eq!(session.words.len(), 1);
+        let (session, _) = quick_session(&conn, Duration::from_secs(3600)).unwrap();
+        assert_eq!(session.words.len(), 5);
+
+        conn.execute("UPDATE words SET times_seen=0, success_count=0", [])
+            .unwrap();
+        assert!(quick_session(&conn, Duration::from_secs(60)).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_past_its_words() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES(1,'a','b',1), (2,'c','d',1);",
+        )
+        .unwrap();
+        let mut snapshot = Session::new(vec![word(1), word(2), word(3)], 2, Type::Marked)
+            .snapshot(Screen::Practice);
+
+        // Word 3 is gone, so index 2 points past the two that remain.
+        let err = snapshot_session(&conn, &snapshot).unwrap_err();
+        assert!(matches!(
+            err,
+            VocabError::ProgressOutOfRange { index: 2, len: 2 }
+        ));
+
+        snapshot.index = 1;
+        let (session, _) = snapshot_session(&conn, &snapshot).unwrap();
+        assert_eq!(session.current().map(|w| w.word.as_str()), Some("c"));
+    }
 }
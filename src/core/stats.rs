@@ -0,0 +1,430 @@
+use crate::core::utils;
+use crate::db::models::{GroupStats, SessionRecord, Word};
+use crate::ui::glyphs::Glyphs;
+use chrono::{DateTime, TimeZone};
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GroupColumn {
+    #[default]
+    Group,
+    Words,
+    Seen,
+    Accuracy,
+    LastPracticed,
+}
+
+impl GroupColumn {
+    pub const ALL: [GroupColumn; 5] = [
+        GroupColumn::Group,
+        GroupColumn::Words,
+        GroupColumn::Seen,
+        GroupColumn::Accuracy,
+        GroupColumn::LastPracticed,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        use GroupColumn::*;
+        match self {
+            Group => "Group",
+            Words => "Words",
+            Seen => "Seen",
+            Accuracy => "Accuracy",
+            LastPracticed => "Last Practiced",
+        }
+    }
+}
+
+impl GroupStats {
+    /// Fraction of the group's words seen at least once.
+    pub fn seen_ratio(&self) -> f64 {
+        if self.words == 0 {
+            0.0
+        } else {
+            self.seen as f64 / self.words as f64
+        }
+    }
+
+    /// Lifetime accuracy, or `None` before the group has been practiced.
+    pub fn accuracy(&self) -> Option<f64> {
+        accuracy(self.success_count, self.times_seen)
+    }
+}
+
+impl Word {
+    pub fn accuracy(&self) -> Option<f64> {
+        accuracy(self.success_count.into(), self.times_seen.into())
+    }
+}
+
+/// Attempts a word needs before it can count as weak.
+pub const WEAK_MIN_SEEN: i64 = 2;
+/// Most words in one Weak session.
+pub const WEAK_LIMIT: usize = 20;
+/// Attempts a word needs before it can count as mastered.
+pub const MASTERED_MIN_SEEN: i64 = 5;
+/// Accuracy, in percent, a word needs to count as mastered.
+pub const MASTERED_PERCENT: i64 = 90;
+/// Correct answers in a row that retire a word from rotation as mastered,
+/// given `MASTERED_PERCENT` accuracy overall.
+pub const MASTERED_STREAK: usize = 5;
+
+/// When a word becomes a leech: missed so often it eats sessions. Read from
+/// the `[leeches]` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LeechPolicy {
+    /// Total misses that flag a word.
+    pub threshold: u8,
+    /// Also take flagged words out of Group, Marked and Weak sessions; they
+    /// stay reachable from Review Leeches.
+    pub suspend: bool,
+}
+
+impl Default for LeechPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: 8,
+            suspend: false,
+        }
+    }
+}
+
+/// Correct answers in a row that clear a word's leech flag.
+pub const LEECH_RECOVERY_STREAK: usize = 3;
+
+/// How sure we are that a word is weak: the lower bound of the 95% Wilson
+/// score interval for its miss rate. One miss in one attempt scores about
+/// 0.21, below 8 misses in 20 (about 0.22), since a single attempt says
+/// little.
+pub fn weakness(success_count: i64, times_seen: i64) -> f64 {
+    const Z: f64 = 1.96;
+    if times_seen <= 0 {
+        return 0.0;
+    }
+    let n = times_seen as f64;
+    let p = (times_seen - success_count.clamp(0, times_seen)) as f64 / n;
+    let z2 = Z * Z;
+    let spread = Z * ((p * (1.0 - p) + z2 / (4.0 * n)) / n).sqrt();
+    (p + z2 / (2.0 * n) - spread) / (1.0 + z2 / n)
+}
+
+/// Share of attempts that succeeded, or `None` with no attempts. Every
+/// accuracy shown or exported goes through here; a success count outside
+/// `0..=times_seen` (which the schema rejects) is clamped rather than
+/// producing a ratio above 1.
+pub fn accuracy(success_count: i64, times_seen: i64) -> Option<f64> {
+    (times_seen > 0).then(|| success_count.clamp(0, times_seen) as f64 / times_seen as f64)
+}
+
+/// One group's progress for the CLI, e.g. "Group 2 · 12/30 seen · 77%".
+pub fn group_line(group: &GroupStats) -> String {
+    let name = match &group.name {
+        Some(name) => format!("Group {} ({name})", group.group_id),
+        None => format!("Group {}", group.group_id),
+    };
+    let accuracy = group
+        .accuracy()
+        .map(|a| format!("{:.0}%", a * 100.0))
+        .unwrap_or_else(|| "-".into());
+    let archived = if group.archived { " · archived" } else { "" };
+    format!(
+        "{name} · {}/{} seen · {accuracy}{archived}",
+        group.seen, group.words
+    )
+}
+
+/// One line of session history, e.g. "Yesterday 21:00 · Group · 30 words ·
+/// 77%".
+pub fn session_line<Tz: TimeZone>(
+    record: &SessionRecord,
+    now: &DateTime<Tz>,
+    glyphs: &Glyphs,
+) -> String {
+    let accuracy = accuracy(record.correct, record.words_reviewed)
+        .map(|a| format!("{:.0}%", a * 100.0))
+        .unwrap_or_else(|| "-".into());
+    let words = format!(
+        "{} {}",
+        record.words_reviewed,
+        if record.words_reviewed == 1 {
+            "word"
+        } else {
+            "words"
+        },
+    );
+    [
+        utils::day_and_time(record.started_at, now),
+        record.session_type.short_label().to_string(),
+        words,
+        accuracy,
+    ]
+    .join(glyphs.separator)
+}
+
+/// Weeks the stats screen's accuracy trend covers.
+pub const TREND_WEEKS: usize = 12;
+pub const WEEK_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Reviews in one week of the accuracy trend.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WeekAccuracy {
+    pub reviews: i64,
+    pub correct: i64,
+}
+
+impl WeekAccuracy {
+    pub fn accuracy(&self) -> Option<f64> {
+        accuracy(self.correct, self.reviews)
+    }
+}
+
+/// `reviews`, as time and outcome, counted into the `weeks` seven-day spans
+/// ending at `now`, oldest first. Older reviews are left out; ones stamped
+/// after `now` count toward the last week.
+pub fn weekly_accuracy(reviews: &[(i64, bool)], now: i64, weeks: usize) -> Vec<WeekAccuracy> {
+    let mut buckets = vec![WeekAccuracy::default(); weeks];
+    for &(at, correct) in reviews {
+        let back = (now - at).max(0) / WEEK_SECS;
+        if let Some(week) = usize::try_from(back)
+            .ok()
+            .filter(|&back| back < weeks)
+            .map(|back| weeks - 1 - back)
+        {
+            buckets[week].reviews += 1;
+            buckets[week].correct += correct as i64;
+        }
+    }
+    buckets
+}
+
+/// The last week's accuracy and its change since four weeks before, e.g.
+/// "This week 82% (41 of 50) · +6 pts vs 4 weeks ago".
+pub fn trend_legend(weeks: &[WeekAccuracy], glyphs: &Glyphs) -> String {
+    let Some(latest) = weeks.last() else {
+        return String::new();
+    };
+    let Some(now) = latest.accuracy() else {
+        return "No reviews this week".into();
+    };
+    let mut parts = vec![format!(
+        "This week {:.0}% ({} of {})",
+        now * 100.0,
+        latest.correct,
+        latest.reviews
+    )];
+    let before = weeks.len().checked_sub(5).and_then(|i| weeks[i].accuracy());
+    if let Some(before) = before {
+        let delta = (now * 100.0).round() - (before * 100.0).round();
+        parts.push(format!("{delta:+.0} pts vs 4 weeks ago"));
+    }
+    parts.join(glyphs.separator)
+}
+
+/// Recent outcomes as correct/wrong glyphs, newest on the right; "new"
+/// when the word has no history yet.
+pub fn outcome_glyphs(outcomes: &[bool], glyphs: &Glyphs) -> String {
+    if outcomes.is_empty() {
+        return "new".into();
+    }
+    outcomes
+        .iter()
+        .map(|&correct| {
+            if correct {
+                glyphs.correct
+            } else {
+                glyphs.wrong
+            }
+        })
+        .collect()
+}
+
+/// Orders groups by `column`. Ties always fall back to ascending group id so
+/// the table doesn't reshuffle equal rows when the direction flips.
+pub fn compare_groups(
+    a: &GroupStats,
+    b: &GroupStats,
+    column: GroupColumn,
+    ascending: bool,
+) -> Ordering {
+    let ord = match column {
+        GroupColumn::Group => a.group_id.cmp(&b.group_id),
+        GroupColumn::Words => a.words.cmp(&b.words),
+        GroupColumn::Seen => a.seen_ratio().total_cmp(&b.seen_ratio()),
+        GroupColumn::Accuracy => a
+            .accuracy()
+            .unwrap_or(-1.0)
+            .total_cmp(&b.accuracy().unwrap_or(-1.0)),
+        GroupColumn::LastPracticed => a.last_seen.cmp(&b.last_seen),
+    };
+
+    let ord = if ascending { ord } else { ord.reverse() };
+    ord.then(a.group_id.cmp(&b.group_id))
+}
+
+pub fn sort_groups(groups: &mut [GroupStats], column: GroupColumn, ascending: bool) {
+    groups.sort_by(|a, b| compare_groups(a, b, column, ascending));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::glyphs::{ASCII, UNICODE};
+
+    fn group(group_id: i32, words: i64, times_seen: i64, success_count: i64) -> GroupStats {
+        GroupStats {
+            group_id,
+            words,
+            seen: words,
+            times_seen,
+            success_count,
+            last_seen: None,
+            name: None,
+            archived: false,
+        }
+    }
+
+    fn ids(groups: &[GroupStats]) -> Vec<i32> {
+        groups.iter().map(|g| g.group_id).collect()
+    }
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_weekly_buckets_skip_empty_weeks() {
+        let now = 100 * DAY;
+        let reviews = [
+            // This week, including one from a clock ahead of `now`.
+            (now - DAY, true),
+            (now, false),
+            (now + 60, true),
+            // Two weeks back, right on the boundary of three.
+            (now - 14 * DAY, true),
+            (now - 21 * DAY + 1, false),
+            // The oldest week kept, then one just past it.
+            (now - 4 * 7 * DAY + 1, true),
+            (now - 4 * 7 * DAY, true),
+        ];
+        let weeks = weekly_accuracy(&reviews, now, 4);
+        let found: Vec<(i64, i64)> = weeks.iter().map(|w| (w.reviews, w.correct)).collect();
+        assert_eq!(found, vec![(1, 1), (2, 1), (0, 0), (3, 2)]);
+        assert_eq!(weeks[2].accuracy(), None);
+        assert_eq!(weeks[1].accuracy(), Some(0.5));
+
+        assert_eq!(
+            weekly_accuracy(&[], now, 3),
+            vec![WeekAccuracy::default(); 3]
+        );
+        assert!(weekly_accuracy(&reviews, now, 0).is_empty());
+    }
+
+    #[test]
+    fn test_trend_legend() {
+        let week = |reviews, correct| WeekAccuracy { reviews, correct };
+        let mut weeks = vec![week(0, 0); 7];
+        weeks[2] = week(4, 3);
+        weeks[6] = week(50, 41);
+        assert_eq!(
+            trend_legend(&weeks, &UNICODE),
+            "This week 82% (41 of 50) · +7 pts vs 4 weeks ago"
+        );
+
+        // Nothing four weeks back to compare with.
+        weeks[2] = week(0, 0);
+        assert_eq!(trend_legend(&weeks, &ASCII), "This week 82% (41 of 50)");
+        assert_eq!(
+            trend_legend(&weeks[4..], &UNICODE),
+            "This week 82% (41 of 50)"
+        );
+
+        weeks[6] = week(0, 0);
+        assert_eq!(trend_legend(&weeks, &UNICODE), "No reviews this week");
+        assert_eq!(trend_legend(&[], &UNICODE), "");
+    }
+
+    #[test]
+    fn test_session_line() {
+        use crate::core::session::Type;
+        use chrono::Utc;
+
+        let now = Utc.with_ymd_and_hms(2024, 3, 5, 17, 30, 0).unwrap();
+        let started = Utc.with_ymd_and_hms(2024, 3, 4, 21, 0, 0).unwrap();
+        let mut record = SessionRecord {
+            id: 1,
+            started_at: started.timestamp() as i32,
+            ended_at: Some(started.timestamp() as i32 + 900),
+            session_type: Type::Group,
+            words_reviewed: 30,
+            correct: 23,
+        };
+        assert_eq!(
+            session_line(&record, &now, &UNICODE),
+            "Yesterday 21:00 · Group · 30 words · 77%"
+        );
+        assert_eq!(
+            session_line(&record, &now, &ASCII),
+            "Yesterday 21:00 | Group | 30 words | 77%"
+        );
+
+        record.words_reviewed = 0;
+        record.correct = 0;
+        record.session_type = Type::Marked;
+        assert!(session_line(&record, &now, &UNICODE).ends_with("Marks · 0 words · -"));
+    }
+
+    #[test]
+    fn test_ties_fall_back_to_group_id() {
+        let mut groups = vec![group(3, 10, 4, 2), group(1, 10, 2, 1), group(2, 5, 2, 2)];
+
+        sort_groups(&mut groups, GroupColumn::Words, true);
+        assert_eq!(ids(&groups), vec![2, 1, 3]);
+
+        sort_groups(&mut groups, GroupColumn::Words, false);
+        assert_eq!(ids(&groups), vec![1, 3, 2]);
+
+        sort_groups(&mut groups, GroupColumn::Accuracy, false);
+        assert_eq!(ids(&groups), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_unpracticed_groups_sort_below_zero_accuracy() {
+        let mut groups = vec![group(1, 5, 0, 0), group(2, 5, 3, 0), group(3, 5, 2, 1)];
+
+        sort_groups(&mut groups, GroupColumn::Accuracy, true);
+        assert_eq!(ids(&groups), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_ratios() {
+        let mut g = group(1, 4, 0, 0);
+        g.seen = 1;
+        assert_eq!(g.seen_ratio(), 0.25);
+        assert_eq!(g.accuracy(), None);
+        assert_eq!(group(1, 4, 4, 3).accuracy(), Some(0.75));
+        assert_eq!(accuracy(7, 4), Some(1.0));
+        assert_eq!(accuracy(-2, 4), Some(0.0));
+        assert_eq!(accuracy(3, -1), None);
+    }
+
+    #[test]
+    fn test_weakness_weighs_evidence() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-4;
+        assert!(close(weakness(0, 1), 0.20654));
+        assert!(close(weakness(12, 20), 0.21881));
+        assert!(close(weakness(0, 10), 0.72247));
+        assert_eq!(weakness(5, 5), 0.0);
+        assert_eq!(weakness(0, 0), 0.0);
+
+        assert!(weakness(12, 20) > weakness(0, 1));
+        assert!(weakness(1, 10) > weakness(1, 2));
+    }
+
+    #[test]
+    fn test_outcome_glyphs() {
+        let outcomes = [true, false, false, true];
+        assert_eq!(outcome_glyphs(&[], &UNICODE), "new");
+        assert_eq!(outcome_glyphs(&outcomes, &UNICODE), "✓✗✗✓");
+        assert_eq!(outcome_glyphs(&outcomes, &ASCII), "+xx+");
+    }
+}
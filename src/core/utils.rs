@@ -1,18 +1,25 @@
-use chrono::{DateTime, Utc};
+use crate::core::clock::SKEW_TOLERANCE;
+use chrono::{DateTime, Local, TimeDelta, TimeZone, Utc};
 
-pub fn relative_time(ts: Option<i32>) -> String {
+/// Age of `ts` relative to `now`, e.g. "3h ago". Timestamps slightly in the
+/// future (a clock that was running fast) read as "just now".
+pub fn relative_time(ts: Option<i32>, now: i64) -> String {
     let ts = match ts {
         Some(v) => v,
         None => return "-".into(),
     };
 
-    let dt = DateTime::<Utc>::from_timestamp(ts.into(), 0);
-    let dt = match dt {
-        Some(v) => v,
-        None => return "-".into(),
+    let (Some(dt), Some(now)) = (
+        DateTime::<Utc>::from_timestamp(ts.into(), 0),
+        DateTime::<Utc>::from_timestamp(now, 0),
+    ) else {
+        return "-".into();
     };
 
-    let diff = Utc::now() - dt;
+    let diff = now - dt;
+    if diff < -TimeDelta::seconds(SKEW_TOLERANCE) {
+        return "in the future".into();
+    }
 
     let mins = diff.num_minutes();
     if mins < 1 {
@@ -30,3 +37,78 @@ pub fn relative_time(ts: Option<i32>) -> String {
     let days = diff.num_days();
     format!("{days}d ago")
 }
+
+/// Unix timestamp of the most recent local midnight before `now`.
+pub fn start_of_day<Tz: TimeZone>(now: DateTime<Tz>) -> i64 {
+    let midnight = now.date_naive().and_hms_opt(0, 0, 0).unwrap();
+    now.timezone()
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        // Midnight skipped by a DST change; fall back to the UTC reading.
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+/// `HH:MM` in local time, for timestamps known to be recent.
+pub fn clock_time(ts: i32) -> String {
+    match Local.timestamp_opt(ts.into(), 0).single() {
+        Some(dt) => dt.format("%H:%M").to_string(),
+        None => "-".into(),
+    }
+}
+
+/// Day and time of `ts` in `now`'s time zone: "Today 09:15",
+/// "Yesterday 21:00", then "Mar 5 21:00".
+pub fn day_and_time<Tz: TimeZone>(ts: i32, now: &DateTime<Tz>) -> String {
+    let Some(dt) = now.timezone().timestamp_opt(ts.into(), 0).single() else {
+        return "-".into();
+    };
+    let today = now.date_naive();
+    let day = match dt.date_naive() {
+        d if d == today => "Today".to_string(),
+        d if Some(d) == today.pred_opt() => "Yesterday".to_string(),
+        d => d.format("%b %-d").to_string(),
+    };
+    format!("{day} {}", dt.naive_local().format("%H:%M"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_time_with_skew() {
+        let now = 1_700_000_000;
+        let ago = |secs: i64| relative_time(Some((now - secs) as i32), now);
+
+        assert_eq!(ago(30), "just now");
+        assert_eq!(ago(5 * 60), "5m ago");
+        assert_eq!(ago(26 * 3600), "1d ago");
+        // A little ahead is a fast clock; far ahead is flagged.
+        assert_eq!(ago(-90), "just now");
+        assert_eq!(ago(-SKEW_TOLERANCE - 60), "in the future");
+        assert_eq!(relative_time(None, now), "-");
+    }
+
+    #[test]
+    fn test_start_of_day() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 5, 17, 30, 0).unwrap();
+        let midnight = Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap();
+        assert_eq!(start_of_day(now), midnight.timestamp());
+        assert_eq!(start_of_day(midnight), midnight.timestamp());
+    }
+
+    #[test]
+    fn test_day_and_time() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 5, 17, 30, 0).unwrap();
+        let at = |d, h, m| {
+            Utc.with_ymd_and_hms(2024, 3, d, h, m, 0)
+                .unwrap()
+                .timestamp() as i32
+        };
+
+        assert_eq!(day_and_time(at(5, 9, 15), &now), "Today 09:15");
+        assert_eq!(day_and_time(at(4, 21, 0), &now), "Yesterday 21:00");
+        assert_eq!(day_and_time(at(1, 8, 5), &now), "Mar 1 08:05");
+    }
+}
@@ -0,0 +1,294 @@
+use anyhow::Result;
+use rusqlite::{Connection, ErrorCode};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Why `init_db` refused to open a database. Carried in its error so the
+/// TUI and CLI can offer repair instead of failing on the first query.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum Damage {
+    #[error("missing columns {}", names(.0))]
+    MissingColumns(Vec<Column>),
+    #[error("written by a newer vocabulator (schema version {found}, this build knows {known})")]
+    NewerSchema { found: usize, known: usize },
+    #[error("corrupted ({})", .0.join("; "))]
+    Corrupt(Vec<String>),
+}
+
+fn names(columns: &[Column]) -> String {
+    columns
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A column as `PRAGMA table_info` describes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub table: String,
+    pub name: String,
+    pub decl: String,
+    pub not_null: bool,
+    pub default: Option<String>,
+    pub primary_key: bool,
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.table, self.name)
+    }
+}
+
+impl Column {
+    /// `ALTER TABLE` statement adding this column, if SQLite allows one:
+    /// not for keys, and not for NOT NULL columns without a default.
+    pub fn add_sql(&self) -> Option<String> {
+        if self.primary_key || (self.not_null && self.default.is_none()) {
+            return None;
+        }
+        let mut sql = format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            self.table, self.name, self.decl
+        );
+        if self.not_null {
+            sql.push_str(" NOT NULL");
+        }
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {default}"));
+        }
+        Some(sql)
+    }
+}
+
+/// Runs `PRAGMA quick_check`. A file that isn't a database at all fails
+/// the first read, which counts as corruption too.
+pub fn check_integrity(conn: &Connection) -> Result<Option<Damage>> {
+    let lines = conn.prepare("PRAGMA quick_check").and_then(|mut stmt| {
+        stmt.query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+    });
+    match lines {
+        Ok(lines) if lines == ["ok"] => Ok(None),
+        Ok(lines) => Ok(Some(Damage::Corrupt(lines))),
+        Err(rusqlite::Error::SqliteFailure(e, message))
+            if matches!(e.code, ErrorCode::NotADatabase | ErrorCode::DatabaseCorrupt) =>
+        {
+            Ok(Some(Damage::Corrupt(vec![
+                message.unwrap_or_else(|| e.to_string()),
+            ])))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Columns a new database has (`INIT_SCHEMA` plus the migrations) that the
+/// database's existing tables lack. Tables that don't exist yet aren't
+/// checked; `INIT_SCHEMA` creates them.
+pub fn missing_columns(conn: &Connection) -> Result<Vec<Column>> {
+    let reference = super::init_db(":memory:")?;
+
+    let mut missing = Vec::new();
+    for table in tables(&reference)? {
+        let present = columns(conn, &table)?;
+        if present.is_empty() {
+            continue;
+        }
+        missing.extend(
+            columns(&reference, &table)?
+                .into_iter()
+                .filter(|c| !present.iter().any(|p| p.name == c.name)),
+        );
+    }
+    Ok(missing)
+}
+
+fn tables(conn: &Connection) -> Result<Vec<String>> {
+    Ok(conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?)
+}
+
+fn columns(conn: &Connection, table: &str) -> Result<Vec<Column>> {
+    Ok(conn
+        .prepare("SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1)")?
+        .query_map([table], |row| {
+            Ok(Column {
+                table: table.to_string(),
+                name: row.get(0)?,
+                decl: row.get(1)?,
+                not_null: row.get(2)?,
+                default: row.get(3)?,
+                primary_key: row.get::<_, i64>(4)? > 0,
+            })
+        })?
+        .collect::<Result<_, _>>()?)
+}
+
+/// Copies next to the database named like it with a suffix, e.g.
+/// `vocab-20240309.db` for `vocab.db`, newest first.
+pub fn backups(db_path: &str) -> Vec<PathBuf> {
+    let path = Path::new(db_path);
+    let (Some(stem), Some(ext)) = (
+        path.file_stem().and_then(|s| s.to_str()),
+        path.extension().and_then(|s| s.to_str()),
+    ) else {
+        return Vec::new();
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let prefix = format!("{stem}-");
+    let suffix = format!(".{ext}");
+    let mut found: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| {
+            entry.file_name().to_str().is_some_and(|name| {
+                name.len() > prefix.len() + suffix.len()
+                    && name.starts_with(&prefix)
+                    && name.ends_with(&suffix)
+            })
+        })
+        .map(|entry| {
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, entry.path())
+        })
+        .collect();
+    found.sort_by(|a, b| b.cmp(a));
+    found.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Replaces the database with `backup`, keeping the damaged file as
+/// `<db>.damaged`. Returns where it was kept.
+pub fn restore(db_path: &str, backup: &Path) -> Result<PathBuf> {
+    let kept = PathBuf::from(format!("{db_path}.damaged"));
+    fs::rename(db_path, &kept)?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{db_path}{suffix}"));
+    }
+    fs::copy(backup, db_path)?;
+    Ok(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_db;
+    use std::io::{Seek, SeekFrom, Write};
+
+    fn damage(path: &str) -> Damage {
+        init_db(path)
+            .unwrap_err()
+            .downcast::<Damage>()
+            .expect("a Damage error")
+    }
+
+    #[test]
+    fn test_missing_column_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        init_db(path)
+            .unwrap()
+            .execute_batch("ALTER TABLE words DROP COLUMN marked")
+            .unwrap();
+
+        match damage(path) {
+            Damage::MissingColumns(columns) => {
+                assert_eq!(names(&columns), "words.marked");
+                assert_eq!(
+                    columns[0].add_sql().unwrap(),
+                    "ALTER TABLE words ADD COLUMN marked INTEGER NOT NULL DEFAULT 0"
+                );
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_garbage_file_is_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        fs::write(&path, "this is not a database, just some text ".repeat(200)).unwrap();
+
+        assert!(matches!(damage(path.to_str().unwrap()), Damage::Corrupt(_)));
+    }
+
+    #[test]
+    fn test_damaged_page_is_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        let conn = init_db(path).unwrap();
+        conn.execute_batch("PRAGMA page_size=4096; VACUUM;")
+            .unwrap();
+        for i in 0..500 {
+            conn.execute(
+                "INSERT INTO words(word,definition,group_id) VALUES(?1,'some definition',1)",
+                [format!("word{i}")],
+            )
+            .unwrap();
+        }
+        drop(conn);
+
+        // Scribble over the middle of the file, past the header page.
+        let mut file = fs::OpenOptions::new().write(true).open(path).unwrap();
+        file.seek(SeekFrom::Start(4096 * 3)).unwrap();
+        file.write_all(&[0xA5; 4096]).unwrap();
+        drop(file);
+
+        assert!(matches!(damage(path), Damage::Corrupt(lines) if lines != ["ok"]));
+    }
+
+    #[test]
+    fn test_newer_schema_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        init_db(path)
+            .unwrap()
+            .pragma_update(None, "user_version", 99)
+            .unwrap();
+
+        assert!(matches!(
+            damage(path),
+            Damage::NewerSchema { found: 99, .. }
+        ));
+    }
+
+    #[test]
+    fn test_backups_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        fs::write(path, "damaged").unwrap();
+        for name in ["vocab-1.db", "vocab-2.db", "vocab.db.lock", "other-1.db"] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        let newest = dir.path().join("vocab-2.db");
+        fs::File::options()
+            .write(true)
+            .open(&newest)
+            .unwrap()
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+
+        let found = backups(path);
+        assert_eq!(found, vec![newest.clone(), dir.path().join("vocab-1.db")]);
+
+        let kept = restore(path, &found[0]).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "vocab-2.db");
+        assert_eq!(fs::read_to_string(kept).unwrap(), "damaged");
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+/// Advisory lock next to the database, holding the owner's pid. Removed
+/// when dropped.
+#[derive(Debug)]
+pub struct DbLock {
+    path: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum LockStatus {
+    Acquired(DbLock),
+    /// Another live process owns the database.
+    Held {
+        pid: u32,
+    },
+}
+
+pub fn held_message(pid: u32) -> String {
+    format!("vocabulator is already running (pid {pid}) using this database")
+}
+
+pub fn lock_path(db_path: &str) -> PathBuf {
+    PathBuf::from(format!("{db_path}.lock"))
+}
+
+/// Takes the lock for `db_path`. A lock file left by a process that is no
+/// longer running is removed and taken over.
+pub fn acquire(db_path: &str) -> Result<LockStatus> {
+    let path = lock_path(db_path);
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                write!(file, "{}", std::process::id())?;
+                return Ok(LockStatus::Acquired(DbLock { path }));
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let owner = fs::read_to_string(&path)?.trim().parse::<u32>().ok();
+        match owner {
+            Some(pid) if is_running(pid) => return Ok(LockStatus::Held { pid }),
+            _ => {
+                tracing::info!(path = %path.display(), ?owner, "removing stale lock");
+                match fs::remove_file(&path) {
+                    Ok(()) => {}
+                    // Another instance cleaned it up first; try again.
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+    }
+}
+
+/// Whether process `pid` exists. `kill` with signal 0 only checks: EPERM
+/// means it exists but belongs to someone else.
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // SAFETY: signal 0 sends nothing; it only checks the pid.
+    let alive = unsafe { libc::kill(pid, 0) } == 0;
+    alive || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Whether process `pid` exists and hasn't exited. A process we may not
+/// query still exists.
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, ERROR_ACCESS_DENIED, GetLastError, STILL_ACTIVE,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    // SAFETY: the handle is checked before use and closed once read.
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        let mut code = 0;
+        let read = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        read && code == STILL_ACTIVE as u32
+    }
+}
+
+impl Drop for DbLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn db_in(dir: &TempDir) -> String {
+        dir.path().join("vocab.db").display().to_string()
+    }
+
+    #[test]
+    fn test_acquire_and_release() {
+        let dir = TempDir::new().unwrap();
+        let db = db_in(&dir);
+
+        let lock = acquire(&db).unwrap();
+        assert!(matches!(lock, LockStatus::Acquired(_)));
+        let written = fs::read_to_string(lock_path(&db)).unwrap();
+        assert_eq!(written, std::process::id().to_string());
+
+        drop(lock);
+        assert!(!lock_path(&db).exists());
+    }
+
+    #[test]
+    fn test_live_owner_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let db = db_in(&dir);
+        let _held = acquire(&db).unwrap();
+
+        let LockStatus::Held { pid } = acquire(&db).unwrap() else {
+            panic!("second acquire should see the lock");
+        };
+        assert_eq!(pid, std::process::id());
+        assert_eq!(
+            held_message(pid),
+            format!(
+                "vocabulator is already running (pid {}) using this database",
+                std::process::id()
+            )
+        );
+    }
+
+    #[test]
+    fn test_stale_lock_is_cleaned_up() {
+        let dir = TempDir::new().unwrap();
+        let db = db_in(&dir);
+        // A child that has exited and been waited for.
+        let mut child = std::process::Command::new(std::env::current_exe().unwrap())
+            .arg("--list")
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!is_running(pid));
+        fs::write(lock_path(&db), pid.to_string()).unwrap();
+
+        let lock = acquire(&db).unwrap();
+        assert!(matches!(lock, LockStatus::Acquired(_)));
+        let written = fs::read_to_string(lock_path(&db)).unwrap();
+        assert_eq!(written, std::process::id().to_string());
+    }
+
+    #[test]
+    fn test_garbage_lock_is_cleaned_up() {
+        let dir = TempDir::new().unwrap();
+        let db = db_in(&dir);
+        fs::write(lock_path(&db), "not a pid").unwrap();
+
+        assert!(matches!(acquire(&db).unwrap(), LockStatus::Acquired(_)));
+    }
+}
@@ -1,16 +1,180 @@
+pub mod health;
+pub mod lock;
 pub mod models;
 pub mod queries;
 pub mod schema;
+pub mod store;
 
-use rusqlite::{Connection, Result};
-use schema::INIT_SCHEMA;
+use anyhow::{Context, Result, bail};
+use health::Damage;
+use rusqlite::{Connection, OpenFlags};
+use schema::{INIT_SCHEMA, MIGRATIONS, SETTINGS_SCHEMA};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::time::Duration;
 
+/// Stored in the database header ("VOCA") so unrelated SQLite files are
+/// refused instead of being written to.
+const APPLICATION_ID: i32 = 0x564F_4341;
+
+/// How long a connection waits for another process's write to finish.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Opens `path`, creating and migrating the schema. A database that is
+/// corrupted, from a newer version, or missing columns fails with a
+/// `Damage` error.
 pub fn init_db(path: &str) -> Result<Connection> {
     let conn = Connection::open(path)?;
+    if let Some(damage) = health::check_integrity(&conn)? {
+        return Err(damage.into());
+    }
+    match application_id(&conn)? {
+        0 => conn.pragma_update(None, "application_id", APPLICATION_ID)?,
+        APPLICATION_ID => {}
+        _ => bail!("{path} is not a vocabulator database"),
+    }
+    // WAL lets `seed` write while an open app reads; the timeout has each
+    // side wait out the other's write instead of failing.
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+
+    let version = user_version(&conn)?;
+    if version > MIGRATIONS.len() {
+        return Err(Damage::NewerSchema {
+            found: version,
+            known: MIGRATIONS.len(),
+        }
+        .into());
+    }
+    // Only a database claiming the current schema is held to it; older ones
+    // get there through the migrations.
+    if version == MIGRATIONS.len() {
+        let missing = health::missing_columns(&conn)?;
+        if !missing.is_empty() {
+            return Err(Damage::MissingColumns(missing).into());
+        }
+    }
+
     conn.execute_batch(INIT_SCHEMA)?;
+    migrate(&conn)?;
+    conn.execute_batch(SETTINGS_SCHEMA)?;
+    Ok(conn)
+}
+
+/// Brings `user_version` up to the number of migrations, running the ones
+/// this database hasn't seen.
+fn migrate(conn: &Connection) -> Result<()> {
+    let version = user_version(conn)?;
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        // Can't change inside a transaction.
+        conn.pragma_update(None, "foreign_keys", false)?;
+        let migrated = with_tx(conn, |tx| {
+            tx.execute_batch(sql)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+            Ok(())
+        });
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrated.with_context(|| format!("Migrating the database to version {}", i + 1))?;
+        tracing::info!(version = i + 1, "migrated database");
+    }
+    Ok(())
+}
+
+/// Opens an existing database without write access, for use while another
+/// instance holds the lock.
+pub fn open_read_only(path: &str) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    checked_read_only(conn, path)
+}
+
+/// Opens `path` for a short read without writing anything next to it.
+/// Reading a WAL database normally creates its -wal and -shm files; with
+/// no -wal file nothing has it open for writing, so it is opened as
+/// immutable instead.
+pub fn open_for_reading(path: &str) -> Result<Connection> {
+    if Path::new(&format!("{path}-wal")).exists() {
+        return open_read_only(path);
+    }
+    let escaped = path
+        .replace('%', "%25")
+        .replace('?', "%3F")
+        .replace('#', "%23");
+    let conn = Connection::open_with_flags(
+        format!("file:{escaped}?immutable=1"),
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX
+            | OpenFlags::SQLITE_OPEN_URI,
+    )?;
+    checked_read_only(conn, path)
+}
+
+/// The checks `init_db` makes, for a connection that can't migrate or
+/// stamp the file. A database from before the application id was set is
+/// accepted; one on an older schema is refused with a hint, since reading
+/// it would fail on whatever it is missing. Nothing here is a `Damage`
+/// error: the recovery screen would try to write.
+fn checked_read_only(conn: Connection, path: &str) -> Result<Connection> {
+    if !matches!(application_id(&conn)?, 0 | APPLICATION_ID) {
+        bail!("{path} is not a vocabulator database");
+    }
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+
+    let version = user_version(&conn)?;
+    let known = MIGRATIONS.len();
+    if version > known {
+        let damage = Damage::NewerSchema {
+            found: version,
+            known,
+        };
+        bail!("Can't open {path}: {damage}");
+    }
+    if version < known {
+        bail!(
+            "{path} has an older schema (version {version} of {known}); \
+             open it once without --read-only to migrate it"
+        );
+    }
+    let missing = health::missing_columns(&conn)?;
+    if !missing.is_empty() {
+        let damage = Damage::MissingColumns(missing);
+        bail!("Can't open {path}: {damage}; run `vocabulator doctor --fix`");
+    }
+    conn.execute_batch(SETTINGS_SCHEMA)?;
     Ok(conn)
 }
 
+/// Whether `path` exists but can't be opened for writing: a copy with its
+/// write permission removed, or one on a read-only mount.
+pub fn is_read_only_file(path: &str) -> bool {
+    Path::new(path).exists() && OpenOptions::new().write(true).open(path).is_err()
+}
+
+/// Runs `f` in a transaction, committing only if it succeeds. Takes a
+/// shared reference so callers holding `&Connection` can use it.
+pub fn with_tx<T>(conn: &Connection, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    let tx = conn.unchecked_transaction()?;
+    let value = f(&tx)?;
+    tx.commit()?;
+    Ok(value)
+}
+
+/// In-memory database with the full schema, for tests.
+#[cfg(test)]
+pub fn test_conn() -> Connection {
+    init_db(":memory:").unwrap()
+}
+
+fn user_version(conn: &Connection) -> Result<usize> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+fn application_id(conn: &Connection) -> Result<i32> {
+    Ok(conn.pragma_query_value(None, "application_id", |row| row.get(0))?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,5 +195,153 @@ mod tests {
 
         assert!(tables.contains(&"words".to_string()));
         assert!(tables.contains(&"app_state".to_string()));
+        assert!(tables.contains(&"reviews".to_string()));
+    }
+
+    #[test]
+    fn test_migrates_legacy_words_table() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        let legacy = Connection::open(path).unwrap();
+        legacy
+            .execute_batch(
+                "CREATE TABLE words (
+                     id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     word TEXT NOT NULL UNIQUE,
+                     definition TEXT NOT NULL,
+                     group_id INTEGER NOT NULL,
+                     marked INTEGER NOT NULL DEFAULT 0,
+                     last_seen INTEGER,
+                     times_seen INTEGER NOT NULL DEFAULT 0,
+                     success_count INTEGER NOT NULL DEFAULT 0
+                 );
+                 CREATE TABLE reviews (
+                     id INTEGER PRIMARY KEY AUTOINCREMENT,
+                     word_id INTEGER NOT NULL REFERENCES words(id) ON DELETE CASCADE,
+                     reviewed_at INTEGER NOT NULL,
+                     correct INTEGER NOT NULL,
+                     mode INTEGER NOT NULL
+                 );
+                 INSERT INTO words VALUES
+                     (1,'a','b',1,0,NULL,2,5), (2,'c','d',1,0,NULL,-1,-3), (3,'e','f',1,0,NULL,4,3);
+                 INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES (1,10,1,0), (3,20,0,0);",
+            )
+            .unwrap();
+        drop(legacy);
+
+        let conn = init_db(path).unwrap();
+
+        let version: usize = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        let counts: Vec<(i64, i64)> = conn
+            .prepare("SELECT times_seen, success_count FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(counts, vec![(2, 2), (0, 0), (4, 3)]);
+        let reviews: i64 = conn
+            .query_row("SELECT COUNT(*) FROM reviews", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(reviews, 2);
+        assert!(
+            conn.execute("UPDATE words SET success_count=9 WHERE id=3", [])
+                .is_err()
+        );
+
+        // Reopening runs nothing again.
+        drop(conn);
+        init_db(path).unwrap();
+    }
+
+    #[test]
+    fn test_foreign_database_is_refused() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        Connection::open(path)
+            .unwrap()
+            .pragma_update(None, "application_id", 42)
+            .unwrap();
+
+        let err = init_db(path).unwrap_err();
+        assert!(err.to_string().contains("not a vocabulator database"));
+    }
+
+    #[test]
+    fn test_read_only_open() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        init_db(path).unwrap();
+
+        let conn = open_read_only(path).unwrap();
+        assert!(conn.execute("DELETE FROM words", []).is_err());
+        assert!(!is_read_only_file(path));
+        assert!(!is_read_only_file(&format!("{path}.missing")));
+    }
+
+    #[test]
+    fn test_read_only_open_accepts_unstamped_database() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        init_db(path)
+            .unwrap()
+            .pragma_update(None, "application_id", 0)
+            .unwrap();
+
+        assert!(open_read_only(path).is_ok());
+        assert!(open_for_reading(path).is_ok());
+    }
+
+    #[test]
+    fn test_settings_apply_to_read_only_connections() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        init_db(path).unwrap();
+
+        let conn = open_for_reading(path).unwrap();
+        queries::set_word_order(&conn, models::WordOrder::Frequency).unwrap();
+        assert_eq!(
+            queries::word_order(&conn).unwrap(),
+            models::WordOrder::Frequency
+        );
+        // Another connection keeps its own.
+        assert_eq!(
+            queries::word_order(&open_read_only(path).unwrap()).unwrap(),
+            models::WordOrder::Id
+        );
+    }
+
+    #[test]
+    fn test_read_only_open_refuses_older_schema() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        init_db(path)
+            .unwrap()
+            .pragma_update(None, "user_version", MIGRATIONS.len() - 1)
+            .unwrap();
+
+        for err in [
+            open_read_only(path).unwrap_err(),
+            open_for_reading(path).unwrap_err(),
+        ] {
+            assert!(err.to_string().contains("older schema"), "{err}");
+            assert!(err.downcast_ref::<Damage>().is_none());
+        }
+    }
+
+    #[test]
+    fn test_read_only_open_refuses_newer_schema() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        init_db(path)
+            .unwrap()
+            .pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+
+        let err = open_read_only(path).unwrap_err();
+        assert!(err.to_string().contains("newer vocabulator"), "{err}");
     }
 }
@@ -1,11 +1,221 @@
-#[derive(Debug, Clone)]
+use crate::core::session::Type;
+use crate::ui::app::Screen;
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+// Word and GroupStats serialize to the JSON shape pinned by the tests
+// below. Each field names its key, so renaming a Rust field leaves the
+// external format alone; changing a key breaks the tests.
+
+/// Version of that JSON shape, for documents that embed it under
+/// "schema_version". Bump it when a key is removed or changes type.
+#[allow(dead_code)]
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Word {
+    #[serde(rename = "id")]
     pub id: i32,
+    #[serde(rename = "word")]
     pub word: String,
+    #[serde(rename = "definition")]
     pub definition: String,
+    #[serde(rename = "group_id")]
     pub group_id: i32,
+    #[serde(rename = "marked", default)]
     pub marked: bool,
+    /// Unix seconds.
+    #[serde(rename = "last_seen", default)]
     pub last_seen: Option<i32>,
-    pub times_seen: u8,
-    pub success_count: u8,
+    #[serde(rename = "times_seen", default)]
+    pub times_seen: u32,
+    #[serde(rename = "success_count", default)]
+    pub success_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupStats {
+    #[serde(rename = "group_id")]
+    pub group_id: i32,
+    /// Label given by the list the group came from, if any.
+    #[serde(rename = "name", default)]
+    pub name: Option<String>,
+    #[serde(rename = "words")]
+    pub words: i64,
+    #[serde(rename = "seen")]
+    pub seen: i64,
+    #[serde(rename = "times_seen")]
+    pub times_seen: i64,
+    #[serde(rename = "success_count")]
+    pub success_count: i64,
+    /// Unix seconds.
+    #[serde(rename = "last_seen", default)]
+    pub last_seen: Option<i32>,
+    /// Kept out of the main rotation; see `groups::set_archived`.
+    #[serde(rename = "archived", default)]
+    pub archived: bool,
+}
+
+/// Order a Group session takes its group's words in. Set on each
+/// connection, so saved positions are counted the same way the session is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WordOrder {
+    /// As they were added.
+    #[default]
+    Id,
+    /// Most common first by `frequency_rank`; unranked words last.
+    Frequency,
+}
+
+impl WordOrder {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            WordOrder::Id => "id",
+            WordOrder::Frequency => "frequency_rank IS NULL, frequency_rank, id",
+        }
+    }
+}
+
+/// One row of the review history joined with its word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewEntry {
+    pub word_id: i32,
+    pub word: String,
+    pub definition: String,
+    pub marked: bool,
+    pub reviewed_at: i32,
+    pub correct: bool,
+    pub mode: Screen,
+}
+
+/// Last autosaved state of an open session, used to offer recovery after
+/// the app exits without closing it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSnapshot {
+    pub session_type: Type,
+    pub mode: Screen,
+    pub word_ids: Vec<i32>,
+    pub index: usize,
+    pub show_definition: bool,
+    pub graded: Option<bool>,
+    pub closed_cleanly: bool,
+}
+
+/// One practice session, from the `sessions` table. `ended_at` is `None`
+/// while it is still open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionRecord {
+    pub id: i64,
+    /// Unix seconds.
+    pub started_at: i32,
+    pub ended_at: Option<i32>,
+    pub session_type: Type,
+    pub words_reviewed: i64,
+    pub correct: i64,
+}
+
+/// A word missed during a report's period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissedWord {
+    pub word_id: i32,
+    pub word: String,
+    pub definition: String,
+    pub misses: i64,
+}
+
+/// A deleted word's row and review history, column by column, so undoing
+/// the delete puts back exactly what was there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletedWord {
+    pub word_id: i32,
+    pub word: String,
+    pub row: Vec<(String, Value)>,
+    /// Oldest first.
+    pub reviews: Vec<Vec<(String, Value)>>,
+}
+
+/// What `queries::import_marks` changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarksImport {
+    /// Words the list named, marked before or not.
+    pub matched: usize,
+    /// Of those, the ones that weren't marked yet.
+    pub newly_marked: usize,
+    /// Marks cleared because the list didn't name the word.
+    pub unmarked: usize,
+    /// Names matching no word, in list order.
+    pub unmatched: Vec<String>,
+}
+
+impl MarksImport {
+    /// One line per change, then the names that matched nothing.
+    pub fn report(&self, unmark_others: bool) -> Vec<String> {
+        let mut report = vec![format!(
+            "Marked {} words ({} already marked)",
+            self.matched,
+            self.matched - self.newly_marked
+        )];
+        if unmark_others {
+            report.push(format!("Unmarked {} words not in the list", self.unmarked));
+        }
+        if !self.unmatched.is_empty() {
+            report.push(format!("{} not found:", self.unmatched.len()));
+            report.extend(self.unmatched.iter().map(|name| format!("  {name}")));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_shape() {
+        let word = Word {
+            id: 7,
+            word: "abate".into(),
+            definition: "lessen".into(),
+            group_id: 2,
+            marked: true,
+            last_seen: Some(1_700_000_000),
+            times_seen: 3,
+            success_count: 2,
+        };
+        let json = serde_json::to_string(&word).unwrap();
+        assert_eq!(
+            json,
+            r#"{"id":7,"word":"abate","definition":"lessen","group_id":2,"marked":true,"last_seen":1700000000,"times_seen":3,"success_count":2}"#
+        );
+        assert_eq!(serde_json::from_str::<Word>(&json).unwrap(), word);
+
+        let stats = GroupStats {
+            group_id: 2,
+            name: None,
+            words: 30,
+            seen: 12,
+            times_seen: 20,
+            success_count: 15,
+            last_seen: None,
+            archived: false,
+        };
+        let json = serde_json::to_string(&stats).unwrap();
+        assert_eq!(
+            json,
+            r#"{"group_id":2,"name":null,"words":30,"seen":12,"times_seen":20,"success_count":15,"last_seen":null,"archived":false}"#
+        );
+        assert_eq!(serde_json::from_str::<GroupStats>(&json).unwrap(), stats);
+    }
+
+    #[test]
+    fn test_word_without_progress_fields() {
+        let word: Word =
+            serde_json::from_str(r#"{"id":1,"word":"a","definition":"b","group_id":1}"#).unwrap();
+        assert!(!word.marked);
+        assert_eq!(
+            (word.last_seen, word.times_seen, word.success_count),
+            (None, 0, 0)
+        );
+    }
 }
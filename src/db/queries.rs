@@ -1,7 +1,15 @@
-use super::models::Word;
+use super::models::{
+    DeletedWord, GroupStats, MarksImport, MissedWord, ReviewEntry, SessionRecord, SessionSnapshot,
+    Word, WordOrder,
+};
+use super::store::{WordFilter, WordStore};
+use crate::core::session::Type;
+use crate::core::stats;
+use crate::error::{Result, VocabError};
 use crate::ui::app::Screen;
-use anyhow::Result;
-use rusqlite::{Connection, params};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::{HashMap, HashSet};
 
 fn screen_to_int(screen: Screen) -> i32 {
     match screen {
@@ -18,7 +26,31 @@ fn int_to_screen(v: i32) -> Screen {
     }
 }
 
-fn map_word(row: &rusqlite::Row) -> rusqlite::Result<Word> {
+fn type_to_int(session_type: Type) -> i32 {
+    match session_type {
+        Type::Group => 0,
+        Type::Marked => 1,
+        Type::Weak => 2,
+        Type::Custom => 3,
+        Type::Leech => 4,
+        Type::Quick => 5,
+        Type::GroupTest => 6,
+    }
+}
+
+fn int_to_type(v: i32) -> Type {
+    match v {
+        1 => Type::Marked,
+        2 => Type::Weak,
+        3 => Type::Custom,
+        4 => Type::Leech,
+        5 => Type::Quick,
+        6 => Type::GroupTest,
+        _ => Type::Group,
+    }
+}
+
+pub fn map_word(row: &rusqlite::Row) -> rusqlite::Result<Word> {
     Ok(Word {
         id: row.get(0)?,
         word: row.get(1)?,
@@ -31,48 +63,209 @@ fn map_word(row: &rusqlite::Row) -> rusqlite::Result<Word> {
     })
 }
 
+/// Saved position; invalid parts are replaced (see `recover_progress`) and
+/// logged rather than failing the session start.
 pub fn fetch_progress(conn: &Connection) -> Result<(Screen, i32, usize)> {
-    let mode: i32 = conn
-        .query_row(
-            "SELECT value FROM app_state WHERE key=?1",
-            params!["mode"],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+    let (progress, problems) = recover_progress(conn)?;
+    for problem in problems {
+        tracing::warn!("{problem}");
+    }
+    Ok(progress)
+}
 
-    let group_id: i32 = conn
-        .query_row(
-            "SELECT value FROM app_state WHERE key=?1",
-            params!["group_id"],
-            |row| row.get(0),
-        )
-        .unwrap_or(1);
+/// Reads the saved position, falling back for each part that is missing or
+/// unusable: the mode to Practice, the group to the lowest one with words,
+/// and the index to 0. The index follows the saved word if it has moved
+/// within the group. Returns one message per fallback; a database with no
+/// saved position at all is a fresh start and has none.
+pub fn recover_progress(conn: &Connection) -> Result<((Screen, i32, usize), Vec<String>)> {
+    let mode = read_state(conn, "mode")?;
+    let group_id = read_state(conn, "group_id")?;
+    let index = read_state(conn, "index")?;
+    let word_id = read_state(conn, "word_id")?;
+    let fresh = mode.is_none() && group_id.is_none() && index.is_none();
 
-    let index: i32 = conn
-        .query_row(
-            "SELECT value FROM app_state WHERE key=?1",
-            params!["index"],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+    let mut problems = Vec::new();
+    let mut fall_back = |key: &str, value: Option<Value>, fallback: String| {
+        if !fresh {
+            problems.push(match value {
+                Some(value) => format!("Saved {key} {value:?} is not valid, using {fallback}"),
+                None => format!("Saved {key} is missing, using {fallback}"),
+            });
+        }
+    };
+
+    let screen = match mode {
+        Some(Value::Integer(0)) => Screen::Practice,
+        Some(Value::Integer(1)) => Screen::Test,
+        other => {
+            fall_back("mode", other, "Practice".into());
+            Screen::Practice
+        }
+    };
+
+    let group_id = match group_id {
+        Some(Value::Integer(g)) if g >= 1 && g <= i32::MAX as i64 => g as i32,
+        other => {
+            let first = conn
+                .query_row("SELECT MIN(group_id) FROM words", [], |row| {
+                    row.get::<_, Option<i32>>(0)
+                })?
+                .unwrap_or(1);
+            fall_back("group", other, format!("group {first}"));
+            first
+        }
+    };
+
+    let moved_to = match word_id {
+        Some(Value::Integer(id)) => position_in_group(conn, group_id, id)?,
+        _ => None,
+    };
+    let len = WordStore::new(conn).count(WordFilter::Group(group_id))? as i64;
+    let index = match index {
+        Some(Value::Integer(i)) if let Some(position) = moved_to => {
+            if i != position as i64 {
+                tracing::info!(from = i, to = position, "saved word moved within its group");
+            }
+            position
+        }
+        Some(Value::Integer(i)) if i == 0 || (i > 0 && i < len) => i as usize,
+        Some(Value::Integer(i)) if i > 0 => {
+            problems.push(format!(
+                "Saved index {i} is past the end of group {group_id} ({len} words), using 0"
+            ));
+            0
+        }
+        other => {
+            fall_back("index", other, "0".into());
+            0
+        }
+    };
+
+    Ok(((screen, group_id, index), problems))
+}
+
+/// Where word `id` sits in `group_id` in `fetch_words_by_group` order, or
+/// `None` if it isn't in that group any more.
+fn position_in_group(conn: &Connection, group_id: i32, id: i64) -> Result<Option<usize>> {
+    Ok(conn
+        .prepare_cached(&format!(
+            "SELECT position FROM
+                 (SELECT id, ROW_NUMBER() OVER (ORDER BY {}) - 1 AS position
+                  FROM words WHERE group_id=?2 AND {IN_ROTATION})
+             WHERE id=?1",
+            word_order(conn)?.sql()
+        ))?
+        .query_row(params![id, group_id], |row| row.get(0))
+        .optional()?)
+}
+
+/// Order Group sessions take words in, as set by `set_word_order`.
+pub fn word_order(conn: &Connection) -> Result<WordOrder> {
+    Ok(match read_setting(conn, "word_order")? {
+        Some(1) => WordOrder::Frequency,
+        _ => WordOrder::Id,
+    })
+}
 
-    Ok((int_to_screen(mode), group_id, index as usize))
+/// Sets the word order for this connection only; see `SETTINGS_SCHEMA`.
+pub fn set_word_order(conn: &Connection, order: WordOrder) -> Result<()> {
+    let value = match order {
+        WordOrder::Id => 0,
+        WordOrder::Frequency => 1,
+    };
+    upsert_setting(conn, "word_order", value)
+}
+
+/// Whether Group, Weak and mixed-in picks on this connection take mastered
+/// words too; see `IN_ROTATION`.
+pub fn set_include_mastered(conn: &Connection, include: bool) -> Result<()> {
+    upsert_setting(conn, "include_mastered", include as i32)
+}
+
+fn upsert_setting(conn: &Connection, key: &str, value: i32) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO temp.settings(key,value)
+         VALUES(?1,?2)
+         ON CONFLICT(key) DO UPDATE SET value=excluded.value",
+    )?
+    .execute(params![key, value])?;
+    Ok(())
+}
+
+fn read_setting(conn: &Connection, key: &str) -> Result<Option<i32>> {
+    Ok(conn
+        .prepare_cached("SELECT value FROM temp.settings WHERE key=?1")?
+        .query_row([key], |row| row.get(0))
+        .optional()?)
+}
+
+/// Counter bumped by whatever adds words from outside the app, such as
+/// `seed`; a running app polls it to pick the words up.
+pub fn data_version(conn: &Connection) -> Result<i64> {
+    Ok(match read_state(conn, "data_version")? {
+        Some(Value::Integer(version)) => version,
+        _ => 0,
+    })
+}
+
+pub fn bump_data_version(conn: &Connection) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO app_state(key,value) VALUES('data_version',1)
+         ON CONFLICT(key) DO UPDATE SET value=value+1",
+    )?
+    .execute([])?;
+    Ok(())
+}
+
+fn read_state(conn: &Connection, key: &str) -> Result<Option<Value>> {
+    Ok(conn
+        .prepare_cached("SELECT value FROM app_state WHERE key=?1")?
+        .query_row([key], |row| row.get(0))
+        .optional()?)
 }
 
 pub fn fetch_final_group(conn: &Connection) -> Result<Option<i32>> {
-    Ok(
-        conn.query_row("SELECT MAX(group_id) FROM words", [], |row| {
-            row.get::<_, Option<i32>>(0)
-        })?,
-    )
+    Ok(conn
+        .prepare_cached("SELECT MAX(group_id) FROM words")?
+        .query_row([], |row| row.get::<_, Option<i32>>(0))?)
+}
+
+/// Words Group, Weak and mixed-in picks take from: not suspended, and not
+/// mastered unless `set_include_mastered` says so. Quick, Marked and Leech
+/// sessions still show mastered words.
+pub(crate) const IN_ROTATION: &str = "suspended=0 AND (mastered_at IS NULL
+     OR EXISTS (SELECT 1 FROM temp.settings WHERE key='include_mastered' AND value=1))";
+
+/// Words in archived groups stay out of everything but their own group's
+/// session and the leech and marked lists.
+const NOT_ARCHIVED: &str = "group_id NOT IN (SELECT group_id FROM archived_groups)";
+
+/// The first group after `group_id` that has words and isn't archived.
+pub fn fetch_next_group(conn: &Connection, group_id: i32) -> Result<Option<i32>> {
+    Ok(conn
+        .prepare_cached(&format!(
+            "SELECT MIN(group_id) FROM words WHERE group_id>?1 AND {NOT_ARCHIVED}"
+        ))?
+        .query_row([group_id], |row| row.get::<_, Option<i32>>(0))?)
+}
+
+pub fn is_group_archived(conn: &Connection, group_id: i32) -> Result<bool> {
+    Ok(conn
+        .prepare_cached("SELECT 1 FROM archived_groups WHERE group_id=?1")?
+        .query_row([group_id], |_| Ok(()))
+        .optional()?
+        .is_some())
 }
 
 pub fn fetch_words_by_group(conn: &Connection, group_id: i32) -> Result<Vec<Word>> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, word, definition, group_id,
                 marked, last_seen, times_seen, success_count
-         FROM words WHERE group_id=?1",
-    )?;
+         FROM words WHERE group_id=?1 AND {IN_ROTATION}
+         ORDER BY {}",
+        word_order(conn)?.sql()
+    ))?;
 
     Ok(stmt
         .query_map(params![group_id], map_word)?
@@ -84,7 +277,7 @@ pub fn fetch_marked_words(conn: &Connection) -> Result<Vec<Word>> {
         "SELECT id, word, definition, group_id,
                 marked, last_seen, times_seen, success_count
          FROM words
-         WHERE marked=1
+         WHERE marked=1 AND suspended=0
          ORDER BY last_seen DESC
          LIMIT 20",
     )?;
@@ -94,15 +287,118 @@ pub fn fetch_marked_words(conn: &Connection) -> Result<Vec<Word>> {
         .collect::<Result<Vec<_>, _>>()?)
 }
 
-pub fn fetch_weak_words(conn: &Connection) -> Result<Vec<Word>> {
+/// Up to `limit` marked words outside `group_id`, least recently seen
+/// first, for mixing into that group's session.
+pub fn fetch_marked_outside_group(
+    conn: &Connection,
+    group_id: i32,
+    limit: usize,
+) -> Result<Vec<Word>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, word, definition, group_id,
+                marked, last_seen, times_seen, success_count
+         FROM words
+         WHERE marked=1 AND {IN_ROTATION} AND group_id<>?1 AND {NOT_ARCHIVED}
+         ORDER BY last_seen IS NOT NULL, last_seen, id
+         LIMIT ?2"
+    ))?;
+
+    Ok(stmt
+        .query_map(params![group_id, limit as i64], map_word)?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Up to `limit` words missed at least once in `min_seen` or more attempts,
+/// weakest first by `stats::weakness`.
+pub fn fetch_weak_words(conn: &Connection, min_seen: i64, limit: usize) -> Result<Vec<Word>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, word, definition, group_id,
+                marked, last_seen, times_seen, success_count
+         FROM words
+         WHERE times_seen>=?1
+         AND success_count < times_seen
+         AND {IN_ROTATION} AND {NOT_ARCHIVED}"
+    ))?;
+
+    let mut words = stmt
+        .query_map([min_seen], map_word)?
+        .collect::<Result<Vec<_>, _>>()?;
+    let weakness = |w: &Word| stats::weakness(w.success_count.into(), w.times_seen.into());
+    words.sort_by(|a, b| weakness(b).total_cmp(&weakness(a)).then(a.id.cmp(&b.id)));
+    words.truncate(limit);
+    Ok(words)
+}
+
+/// Up to `limit` words seen at least once, least recently seen first.
+pub fn fetch_stale_words(conn: &Connection, limit: usize) -> Result<Vec<Word>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, word, definition, group_id,
+                marked, last_seen, times_seen, success_count
+         FROM words
+         WHERE times_seen>0 AND suspended=0 AND {NOT_ARCHIVED}
+         ORDER BY last_seen IS NOT NULL, last_seen, id
+         LIMIT ?1"
+    ))?;
+
+    Ok(stmt
+        .query_map([limit as i64], map_word)?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Words `fetch_stale_words` could pick from.
+pub fn count_seen_words(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM words WHERE times_seen>0 AND suspended=0 AND {NOT_ARCHIVED}"
+        ),
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+pub fn count_words(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))?)
+}
+
+pub fn count_unseen_words(conn: &Connection) -> Result<i64> {
+    Ok(
+        conn.query_row("SELECT COUNT(*) FROM words WHERE times_seen=0", [], |row| {
+            row.get(0)
+        })?,
+    )
+}
+
+pub fn count_marked_words(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM words WHERE marked=1 AND suspended=0",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Size of the session `fetch_weak_words` would return.
+pub fn count_weak_words(conn: &Connection, min_seen: i64, limit: usize) -> Result<i64> {
+    let count: i64 = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM words
+             WHERE times_seen>=?1
+             AND success_count < times_seen
+             AND {IN_ROTATION} AND {NOT_ARCHIVED}"
+        ),
+        [min_seen],
+        |row| row.get(0),
+    )?;
+    Ok(count.min(limit as i64))
+}
+
+/// Every word flagged as a leech, suspended or not, most missed first.
+pub fn fetch_leech_words(conn: &Connection) -> Result<Vec<Word>> {
     let mut stmt = conn.prepare(
         "SELECT id, word, definition, group_id,
                 marked, last_seen, times_seen, success_count
          FROM words
-         WHERE times_seen>0
-         AND success_count != times_seen
-         ORDER BY 1.0*success_count/times_seen ASC
-         LIMIT 20",
+         WHERE leech=1
+         ORDER BY times_seen - success_count DESC, id",
     )?;
 
     Ok(stmt
@@ -110,36 +406,710 @@ pub fn fetch_weak_words(conn: &Connection) -> Result<Vec<Word>> {
         .collect::<Result<Vec<_>, _>>()?)
 }
 
+pub fn count_leech_words(conn: &Connection) -> Result<i64> {
+    Ok(
+        conn.query_row("SELECT COUNT(*) FROM words WHERE leech=1", [], |row| {
+            row.get(0)
+        })?,
+    )
+}
+
+/// Words left in `group_id` from `index` on, and how many of them are
+/// unseen: the rest of the session `fetch_words_by_group` would give.
+pub fn count_group_from(conn: &Connection, group_id: i32, index: usize) -> Result<(i64, i64)> {
+    Ok(conn
+        .prepare_cached(&format!(
+            "SELECT COUNT(*), COALESCE(SUM(times_seen=0), 0)
+             FROM (SELECT times_seen FROM words WHERE group_id=?1 AND {IN_ROTATION}
+                   ORDER BY {} LIMIT -1 OFFSET ?2)",
+            word_order(conn)?.sql()
+        ))?
+        .query_row(params![group_id, index as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?)
+}
+
+/// Size of `fetch_marked_outside_group` and how many of those are unseen.
+pub fn count_marked_outside_group(
+    conn: &Connection,
+    group_id: i32,
+    limit: usize,
+) -> Result<(i64, i64)> {
+    Ok(conn
+        .prepare_cached(&format!(
+            "SELECT COUNT(*), COALESCE(SUM(times_seen=0), 0)
+             FROM (SELECT times_seen FROM words
+                   WHERE marked=1 AND {IN_ROTATION} AND group_id<>?1 AND {NOT_ARCHIVED}
+                   ORDER BY last_seen IS NOT NULL, last_seen, id
+                   LIMIT ?2)"
+        ))?
+        .query_row(params![group_id, limit as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?)
+}
+
+/// Size of `fetch_marked_words` and how many of those are unseen.
+pub fn count_marked_session(conn: &Connection) -> Result<(i64, i64)> {
+    Ok(conn
+        .prepare_cached(
+            "SELECT COUNT(*), COALESCE(SUM(times_seen=0), 0)
+             FROM (SELECT times_seen FROM words WHERE marked=1 AND suspended=0
+                   ORDER BY last_seen DESC LIMIT 20)",
+        )?
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))?)
+}
+
+/// Seconds spent and distinct words reviewed, summed over sessions with
+/// more than one review. A session's time runs from its first review to
+/// its last.
+pub fn fetch_review_pace(conn: &Connection) -> Result<(i64, i64)> {
+    Ok(conn
+        .prepare_cached(
+            "SELECT COALESCE(SUM(span), 0), COALESCE(SUM(words), 0)
+             FROM (SELECT MAX(reviewed_at) - MIN(reviewed_at) AS span,
+                          COUNT(DISTINCT word_id) AS words
+                   FROM reviews WHERE session_id IS NOT NULL
+                   GROUP BY session_id HAVING COUNT(*) > 1)",
+        )?
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))?)
+}
+
+pub fn fetch_group_name(conn: &Connection, group_id: i32) -> Result<Option<String>> {
+    Ok(conn
+        .prepare_cached("SELECT name FROM group_names WHERE group_id=?1")?
+        .query_row([group_id], |row| row.get(0))
+        .optional()?)
+}
+
+pub fn fetch_ipa(conn: &Connection, word_id: i32) -> Result<Option<String>> {
+    Ok(conn
+        .prepare_cached("SELECT ipa FROM words WHERE id=?1")?
+        .query_row([word_id], |row| row.get(0))
+        .optional()?
+        .flatten())
+}
+
+pub fn fetch_reading(conn: &Connection, word_id: i32) -> Result<Option<String>> {
+    Ok(conn
+        .prepare_cached("SELECT reading FROM words WHERE id=?1")?
+        .query_row([word_id], |row| row.get(0))
+        .optional()?
+        .flatten())
+}
+
+pub fn is_leech(conn: &Connection, word_id: i32) -> Result<bool> {
+    Ok(conn
+        .prepare_cached("SELECT leech FROM words WHERE id=?1")?
+        .query_row([word_id], |row| row.get::<_, bool>(0))
+        .optional()?
+        .unwrap_or(false))
+}
+
+/// Flags the word as a leech, suspending it too if `suspend`. Returns
+/// false if it already was one.
+pub fn flag_leech(conn: &Connection, word_id: i32, suspend: bool) -> Result<bool> {
+    let changed = conn
+        .prepare_cached("UPDATE words SET leech=1, suspended=?2 WHERE id=?1 AND leech=0")?
+        .execute(params![word_id, suspend])?;
+    Ok(changed > 0)
+}
+
+/// Clears the leech flag and any suspension. Returns false if the word
+/// wasn't a leech.
+pub fn clear_leech(conn: &Connection, word_id: i32) -> Result<bool> {
+    let changed = conn
+        .prepare_cached("UPDATE words SET leech=0, suspended=0 WHERE id=?1 AND leech=1")?
+        .execute([word_id])?;
+    Ok(changed > 0)
+}
+
+/// Marks the word mastered at `at`, or back in rotation for `None`. Returns
+/// false if it already was.
+pub fn set_mastered(conn: &Connection, word_id: i32, at: Option<i64>) -> Result<bool> {
+    let changed = conn
+        .prepare_cached(
+            "UPDATE words SET mastered_at=?2
+             WHERE id=?1 AND (mastered_at IS NULL) <> (?2 IS NULL)",
+        )?
+        .execute(params![word_id, at])?;
+    Ok(changed > 0)
+}
+
+/// Every group's figures, leaving out archived groups unless
+/// `include_archived`.
+pub fn fetch_group_stats(conn: &Connection, include_archived: bool) -> Result<Vec<GroupStats>> {
+    let mut stmt = conn.prepare(
+        "SELECT w.group_id, COUNT(*), SUM(w.times_seen>0),
+                SUM(w.times_seen), SUM(w.success_count), MAX(w.last_seen), g.name,
+                a.group_id IS NOT NULL
+         FROM words w
+         LEFT JOIN group_names g ON g.group_id = w.group_id
+         LEFT JOIN archived_groups a ON a.group_id = w.group_id
+         WHERE ?1 OR a.group_id IS NULL
+         GROUP BY w.group_id
+         ORDER BY w.group_id",
+    )?;
+
+    Ok(stmt
+        .query_map([include_archived], |row| {
+            Ok(GroupStats {
+                group_id: row.get(0)?,
+                words: row.get(1)?,
+                seen: row.get(2)?,
+                times_seen: row.get(3)?,
+                success_count: row.get(4)?,
+                last_seen: row.get(5)?,
+                name: row.get(6)?,
+                archived: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
 pub fn update_word_stats(conn: &Connection, word: &Word) -> Result<()> {
-    conn.execute(
+    // The schema enforces this too; checking first gives a readable error.
+    if word.success_count > word.times_seen {
+        return Err(VocabError::InvalidStats {
+            word: word.word.clone(),
+            success: word.success_count,
+            seen: word.times_seen,
+        });
+    }
+    conn.prepare_cached(
         "UPDATE words
          SET marked=?1,
              last_seen=?2,
              times_seen=?3,
              success_count=?4
          WHERE id=?5",
+    )?
+    .execute(params![
+        word.marked,
+        word.last_seen,
+        word.times_seen,
+        word.success_count,
+        word.id
+    ])?;
+
+    Ok(())
+}
+
+pub fn insert_review(
+    conn: &Connection,
+    word_id: i32,
+    reviewed_at: i32,
+    correct: bool,
+    screen: Screen,
+) -> Result<()> {
+    // Stamped with the open session, if any; see `open_session_record`.
+    conn.prepare_cached(
+        "INSERT INTO reviews(word_id,reviewed_at,correct,mode,session_id)
+         VALUES(?1,?2,?3,?4,(SELECT value FROM app_state WHERE key='session_id'))",
+    )?
+    .execute(params![
+        word_id,
+        reviewed_at,
+        correct,
+        screen_to_int(screen)
+    ])?;
+    Ok(())
+}
+
+/// Outcomes of the last `limit` reviews of a word, oldest first.
+pub fn fetch_recent_outcomes(conn: &Connection, word_id: i32, limit: usize) -> Result<Vec<bool>> {
+    let mut stmt = conn.prepare(
+        "SELECT correct FROM reviews
+         WHERE word_id=?1
+         ORDER BY reviewed_at DESC, id DESC
+         LIMIT ?2",
+    )?;
+
+    let mut outcomes = stmt
+        .query_map(params![word_id, limit as i64], |row| row.get(0))?
+        .collect::<Result<Vec<bool>, _>>()?;
+    outcomes.reverse();
+    Ok(outcomes)
+}
+
+/// Reviews made at or after `from` and before `to`.
+pub fn count_reviews_between(conn: &Connection, from: i64, to: i64) -> Result<i64> {
+    Ok(conn
+        .prepare_cached(
+            "SELECT COUNT(*) FROM reviews WHERE reviewed_at >= ?1 AND reviewed_at < ?2",
+        )?
+        .query_row([from, to], |row| row.get(0))?)
+}
+
+/// Reviews and correct ones made at or after `from` and before `to`.
+pub fn count_outcomes_between(conn: &Connection, from: i64, to: i64) -> Result<(i64, i64)> {
+    Ok(conn
+        .prepare_cached(
+            "SELECT COUNT(*), COALESCE(SUM(correct), 0) FROM reviews
+             WHERE reviewed_at >= ?1 AND reviewed_at < ?2",
+        )?
+        .query_row([from, to], |row| Ok((row.get(0)?, row.get(1)?)))?)
+}
+
+/// Time and outcome of every review at or after `from`.
+pub fn fetch_outcomes_since(conn: &Connection, from: i64) -> Result<Vec<(i64, bool)>> {
+    let mut stmt =
+        conn.prepare_cached("SELECT reviewed_at, correct FROM reviews WHERE reviewed_at >= ?1")?;
+    Ok(stmt
+        .query_map([from], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Words first answered correctly at or after `from` and before `to`.
+pub fn count_first_correct_between(conn: &Connection, from: i64, to: i64) -> Result<i64> {
+    Ok(conn
+        .prepare_cached(
+            "SELECT COUNT(*) FROM (
+                 SELECT MIN(reviewed_at) AS first FROM reviews
+                 WHERE correct=1 GROUP BY word_id
+             ) WHERE first >= ?1 AND first < ?2",
+        )?
+        .query_row([from, to], |row| row.get(0))?)
+}
+
+/// Words with at least `min_seen` reviews before `before`, `percent` or
+/// more of them correct.
+pub fn count_mastered_before(
+    conn: &Connection,
+    before: i64,
+    min_seen: i64,
+    percent: i64,
+) -> Result<i64> {
+    Ok(conn
+        .prepare_cached(
+            "SELECT COUNT(*) FROM (
+                 SELECT word_id FROM reviews WHERE reviewed_at < ?1
+                 GROUP BY word_id
+                 HAVING COUNT(*) >= ?2 AND SUM(correct) * 100 >= COUNT(*) * ?3
+             )",
+        )?
+        .query_row([before, min_seen, percent], |row| row.get(0))?)
+}
+
+/// The `limit` words missed most at or after `from` and before `to`; ties
+/// go alphabetically.
+pub fn fetch_most_missed(
+    conn: &Connection,
+    from: i64,
+    to: i64,
+    limit: usize,
+) -> Result<Vec<MissedWord>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT w.id, w.word, w.definition, COUNT(*) AS misses
+         FROM reviews r
+         JOIN words w ON w.id = r.word_id
+         WHERE r.correct=0 AND r.reviewed_at >= ?1 AND r.reviewed_at < ?2
+         GROUP BY w.id
+         ORDER BY misses DESC, w.word, w.id
+         LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(params![from, to, limit as i64], |row| {
+        Ok(MissedWord {
+            word_id: row.get(0)?,
+            word: row.get(1)?,
+            definition: row.get(2)?,
+            misses: row.get(3)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Reviews made at or after `since`, newest first, one page at a time.
+pub fn fetch_reviews_since(
+    conn: &Connection,
+    since: i64,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<ReviewEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.word_id, w.word, w.definition, w.marked,
+                r.reviewed_at, r.correct, r.mode
+         FROM reviews r
+         JOIN words w ON w.id = r.word_id
+         WHERE r.reviewed_at >= ?1
+         ORDER BY r.reviewed_at DESC, r.id DESC
+         LIMIT ?2 OFFSET ?3",
+    )?;
+
+    Ok(stmt
+        .query_map(params![since, limit as i64, offset as i64], |row| {
+            Ok(ReviewEntry {
+                word_id: row.get(0)?,
+                word: row.get(1)?,
+                definition: row.get(2)?,
+                marked: row.get(3)?,
+                reviewed_at: row.get(4)?,
+                correct: row.get(5)?,
+                mode: int_to_screen(row.get(6)?),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Names a group unless it already has a name.
+pub fn name_group(conn: &Connection, group_id: i32, name: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO group_names(group_id, name) VALUES(?1, ?2)",
+        params![group_id, name],
+    )?;
+    Ok(())
+}
+
+pub fn set_marked(conn: &Connection, word_id: i32, marked: bool) -> Result<()> {
+    conn.prepare_cached("UPDATE words SET marked=?1 WHERE id=?2")?
+        .execute(params![marked, word_id])?;
+    Ok(())
+}
+
+/// Stores what was typed for `word_id`'s latest review.
+pub fn save_answer(conn: &Connection, word_id: i32, answer: &str) -> Result<()> {
+    conn.prepare_cached(
+        "UPDATE reviews SET answer=?2
+         WHERE id=(SELECT MAX(id) FROM reviews WHERE word_id=?1)",
+    )?
+    .execute(params![word_id, answer])?;
+    Ok(())
+}
+
+/// Words answered wrongly on the Test screen in session `session_id`, with
+/// what was typed, in the order they were answered.
+pub fn fetch_misspelled(conn: &Connection, session_id: i64) -> Result<Vec<(String, String)>> {
+    Ok(conn
+        .prepare(
+            "SELECT w.word, r.answer FROM reviews r JOIN words w ON w.id = r.word_id
+             WHERE r.session_id=?1 AND r.mode=1 AND r.correct=0 AND r.answer IS NOT NULL
+             ORDER BY r.id",
+        )?
+        .query_map([session_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Every marked word, suspended or not, in id order.
+pub fn fetch_marked_names(conn: &Connection) -> Result<Vec<String>> {
+    Ok(conn
+        .prepare("SELECT word FROM words WHERE marked=1 ORDER BY id")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Marks the words `names` lists, matched case-insensitively, so "Laud"
+/// marks both "laud" and "Laud". With `unmark_others`, words the list
+/// doesn't name lose their mark. Blank names are skipped and a name listed
+/// twice counts once.
+pub fn import_marks(conn: &Connection, names: &[&str], unmark_others: bool) -> Result<MarksImport> {
+    let mut ids: HashMap<String, Vec<i32>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, word FROM words")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (id, word) = row?;
+        ids.entry(word.to_lowercase()).or_default().push(id);
+    }
+
+    let mut import = MarksImport::default();
+    let mut listed = HashSet::new();
+    let mut names_seen = HashSet::new();
+    let mut mark = conn.prepare_cached("UPDATE words SET marked=1 WHERE id=?1 AND marked=0")?;
+    for name in names {
+        let name = name.trim();
+        let key = name.to_lowercase();
+        if key.is_empty() || !names_seen.insert(key.clone()) {
+            continue;
+        }
+        let Some(matched) = ids.get(&key) else {
+            import.unmatched.push(name.to_string());
+            continue;
+        };
+        for &id in matched {
+            listed.insert(id);
+            import.matched += 1;
+            import.newly_marked += mark.execute([id])?;
+        }
+    }
+
+    if unmark_others {
+        let marked: Vec<i32> = conn
+            .prepare("SELECT id FROM words WHERE marked=1")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        for id in marked.into_iter().filter(|id| !listed.contains(id)) {
+            set_marked(conn, id, false)?;
+            import.unmarked += 1;
+        }
+    }
+    Ok(import)
+}
+
+pub fn save_snapshot(conn: &Connection, snapshot: &SessionSnapshot) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO session_snapshot
+             (id, session_type, mode, word_ids, idx, show_definition, graded, closed_cleanly)
+         VALUES(1,?1,?2,?3,?4,?5,?6,?7)",
         params![
-            word.marked,
-            word.last_seen,
-            word.times_seen,
-            word.success_count,
-            word.id
+            type_to_int(snapshot.session_type),
+            screen_to_int(snapshot.mode),
+            join_ids(&snapshot.word_ids),
+            snapshot.index as i64,
+            snapshot.show_definition,
+            snapshot.graded,
+            snapshot.closed_cleanly,
         ],
     )?;
+    // The snapshot now includes every grade made so far.
+    upsert_state(conn, "last_graded", 0)
+}
+
+/// Remembers the word just graded so a resume from an older snapshot can
+/// skip past it instead of grading it twice.
+pub fn save_last_graded(conn: &Connection, word_id: i32) -> Result<()> {
+    upsert_state(conn, "last_graded", word_id)
+}
+
+/// Last word graded since the snapshot was saved, if any.
+pub fn fetch_last_graded(conn: &Connection) -> Result<Option<i32>> {
+    Ok(match read_state(conn, "last_graded")? {
+        Some(Value::Integer(id)) if id > 0 => Some(id as i32),
+        _ => None,
+    })
+}
+
+pub fn fetch_snapshot(conn: &Connection) -> Result<Option<SessionSnapshot>> {
+    let row = conn
+        .query_row(
+            "SELECT session_type, mode, word_ids, idx, show_definition, graded, closed_cleanly
+             FROM session_snapshot WHERE id=1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i32>(0)?,
+                    row.get::<_, i32>(1)?,
+                    parse_ids(&row.get::<_, String>(2)?).map_err(|e| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            2,
+                            rusqlite::types::Type::Text,
+                            Box::new(e),
+                        )
+                    })?,
+                    row.get::<_, i64>(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            },
+        )
+        .optional()?;
 
+    let Some((session_type, mode, word_ids, index, show_definition, graded, closed_cleanly)) = row
+    else {
+        return Ok(None);
+    };
+    Ok(Some(SessionSnapshot {
+        session_type: int_to_type(session_type),
+        mode: int_to_screen(mode),
+        word_ids,
+        index: index as usize,
+        show_definition,
+        graded,
+        closed_cleanly,
+    }))
+}
+
+/// Replaces the snapshot's word list, e.g. after words were merged.
+pub fn set_snapshot_word_ids(conn: &Connection, word_ids: &[i32]) -> Result<()> {
+    conn.execute(
+        "UPDATE session_snapshot SET word_ids=?1 WHERE id=1",
+        [join_ids(word_ids)],
+    )?;
     Ok(())
 }
 
-fn upsert_state(conn: &Connection, key: &str, value: i32) -> Result<()> {
+fn parse_ids(ids: &str) -> Result<Vec<i32>, std::num::ParseIntError> {
+    ids.split(',')
+        .filter(|id| !id.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+fn join_ids(ids: &[i32]) -> String {
+    ids.iter().map(i32::to_string).collect::<Vec<_>>().join(",")
+}
+
+/// Starts a `sessions` row and makes it the one new reviews are stamped
+/// with. A row left open by a session that never finished is closed first.
+pub fn open_session_record(conn: &Connection, session_type: Type, now: i64) -> Result<i64> {
+    close_abandoned_sessions(conn)?;
     conn.execute(
+        "INSERT INTO sessions(started_at,type) VALUES(?1,?2)",
+        params![now, type_to_int(session_type)],
+    )?;
+    let id = conn.last_insert_rowid();
+    upsert_state(conn, "session_id", id as i32)?;
+    Ok(id)
+}
+
+// Totals come from the reviews stamped with the session.
+const SESSION_TOTALS: &str =
+    "words_reviewed=(SELECT COUNT(*) FROM reviews WHERE session_id=sessions.id),
+     correct=(SELECT COALESCE(SUM(correct), 0) FROM reviews WHERE session_id=sessions.id)";
+
+/// Closes the open session at `now` with its totals. Does nothing when no
+/// session is open.
+pub fn finish_session_record(conn: &Connection, now: i64) -> Result<()> {
+    conn.execute(
+        &format!(
+            "UPDATE sessions SET ended_at=?1, {SESSION_TOTALS}
+             WHERE id=(SELECT value FROM app_state WHERE key='session_id') AND ended_at IS NULL"
+        ),
+        [now],
+    )?;
+    conn.execute("DELETE FROM app_state WHERE key='session_id'", [])?;
+    Ok(())
+}
+
+/// Closes sessions left open by a crash or a killed process, as ending at
+/// their last review (or their start, with none). Returns how many.
+pub fn close_abandoned_sessions(conn: &Connection) -> Result<usize> {
+    let closed = conn.execute(
+        &format!(
+            "UPDATE sessions SET
+                ended_at=COALESCE(
+                    (SELECT MAX(reviewed_at) FROM reviews WHERE session_id=sessions.id),
+                    started_at),
+                {SESSION_TOTALS}
+             WHERE ended_at IS NULL"
+        ),
+        [],
+    )?;
+    conn.execute("DELETE FROM app_state WHERE key='session_id'", [])?;
+    Ok(closed)
+}
+
+/// The last `limit` finished sessions, newest first.
+pub fn fetch_recent_sessions(conn: &Connection, limit: usize) -> Result<Vec<SessionRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, started_at, ended_at, type, words_reviewed, correct
+         FROM sessions
+         WHERE ended_at IS NOT NULL
+         ORDER BY started_at DESC, id DESC
+         LIMIT ?1",
+    )?;
+
+    Ok(stmt
+        .query_map([limit as i64], |row| {
+            Ok(SessionRecord {
+                id: row.get(0)?,
+                started_at: row.get(1)?,
+                ended_at: row.get(2)?,
+                session_type: int_to_type(row.get(3)?),
+                words_reviewed: row.get(4)?,
+                correct: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Records that the snapshotted session ended normally.
+pub fn close_snapshot(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE session_snapshot SET closed_cleanly=1 WHERE id=1",
+        [],
+    )?;
+    Ok(())
+}
+
+fn select_rows(conn: &Connection, sql: &str, id: i32) -> Result<Vec<Vec<(String, Value)>>> {
+    let mut stmt = conn.prepare(sql)?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let rows = stmt
+        .query_map([id], |row| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, name)| Ok((name.clone(), row.get(i)?)))
+                .collect()
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(rows)
+}
+
+fn insert_row(conn: &Connection, table: &str, row: &[(String, Value)]) -> Result<()> {
+    let names: Vec<&str> = row.iter().map(|(name, _)| name.as_str()).collect();
+    let slots: Vec<String> = (1..=row.len()).map(|i| format!("?{i}")).collect();
+    conn.execute(
+        &format!(
+            "INSERT INTO {table}({}) VALUES({})",
+            names.join(","),
+            slots.join(",")
+        ),
+        rusqlite::params_from_iter(row.iter().map(|(_, value)| value)),
+    )?;
+    Ok(())
+}
+
+/// Deletes a word and its reviews, returning them for `restore_word`.
+/// Saved progress on the word moves to the word now at its position, as
+/// after any other change to the group. Run it in a transaction.
+pub fn delete_word(conn: &Connection, word_id: i32) -> Result<Option<DeletedWord>> {
+    let Some(row) = select_rows(conn, "SELECT * FROM words WHERE id=?1", word_id)?.pop() else {
+        return Ok(None);
+    };
+    let reviews = select_rows(
+        conn,
+        "SELECT * FROM reviews WHERE word_id=?1 ORDER BY id",
+        word_id,
+    )?;
+    let word = conn.query_row("SELECT word FROM words WHERE id=?1", [word_id], |r| {
+        r.get(0)
+    })?;
+
+    let id = Some(Value::Integer(word_id.into()));
+    let on_word = read_state(conn, "word_id")? == id;
+    conn.execute("DELETE FROM reviews WHERE word_id=?1", [word_id])?;
+    conn.execute("DELETE FROM words WHERE id=?1", [word_id])?;
+    if read_state(conn, "last_graded")? == id {
+        upsert_state(conn, "last_graded", 0)?;
+    }
+    if on_word {
+        let (screen, group_id, index) = fetch_progress(conn)?;
+        let len = WordStore::new(conn).count(WordFilter::Group(group_id))? as usize;
+        save_progress(conn, (screen, group_id, index.min(len.saturating_sub(1))))?;
+    }
+
+    Ok(Some(DeletedWord {
+        word_id,
+        word,
+        row,
+        reviews,
+    }))
+}
+
+/// Puts back a word removed by `delete_word`, with its stats and reviews
+/// as they were. Run it in a transaction.
+pub fn restore_word(conn: &Connection, deleted: &DeletedWord) -> Result<()> {
+    insert_row(conn, "words", &deleted.row)?;
+    for review in &deleted.reviews {
+        insert_row(conn, "reviews", review)?;
+    }
+    Ok(())
+}
+
+fn upsert_state(conn: &Connection, key: &str, value: i32) -> Result<()> {
+    conn.prepare_cached(
         "INSERT INTO app_state(key,value)
          VALUES(?1,?2)
          ON CONFLICT(key) DO UPDATE SET value=excluded.value",
-        params![key, value],
-    )?;
+    )?
+    .execute(params![key, value])?;
     Ok(())
 }
 
+/// Saves the position along with the id of the word at it, so resuming
+/// still finds that word if the group's order changes in between.
 pub fn save_progress(conn: &Connection, progress: (Screen, i32, usize)) -> Result<()> {
     let (screen, group_id, index) = progress;
 
@@ -147,26 +1117,40 @@ pub fn save_progress(conn: &Connection, progress: (Screen, i32, usize)) -> Resul
     upsert_state(conn, "group_id", group_id)?;
     upsert_state(conn, "index", index as i32)?;
 
+    let word_id: Option<i32> = conn
+        .prepare_cached(&format!(
+            "SELECT id FROM words WHERE group_id=?1 AND {IN_ROTATION}
+             ORDER BY {} LIMIT 1 OFFSET ?2",
+            word_order(conn)?.sql()
+        ))?
+        .query_row(params![group_id, index as i64], |row| row.get(0))
+        .optional()?;
+    match word_id {
+        Some(id) => upsert_state(conn, "word_id", id)?,
+        None => {
+            conn.prepare_cached("DELETE FROM app_state WHERE key='word_id'")?
+                .execute([])?;
+        }
+    }
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::schema::INIT_SCHEMA;
-    use rusqlite::Connection;
-
-    fn setup() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-
-        conn.execute_batch(INIT_SCHEMA).unwrap();
-
-        conn
-    }
+    use crate::db::test_conn;
 
     #[test]
     fn test_save_and_fetch_progress() {
-        let conn = setup();
+        let conn = test_conn();
+        for i in 0..8 {
+            conn.execute(
+                "INSERT INTO words(word,definition,group_id) VALUES(?1,'d',3)",
+                [format!("w{i}")],
+            )
+            .unwrap();
+        }
 
         save_progress(&conn, (Screen::Test, 3, 7)).unwrap();
         let (screen, group, idx) = fetch_progress(&conn).unwrap();
@@ -176,11 +1160,213 @@ mod tests {
         assert_eq!(idx, 7);
     }
 
+    #[test]
+    fn test_recover_malformed_progress() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES('a','b',2), ('c','d',2), ('e','f',3);",
+        )
+        .unwrap();
+
+        // Stored (mode, group_id, index) values, expected progress, fallbacks.
+        let cases = [
+            ("", (Screen::Practice, 2, 0), 0),
+            (
+                "('mode',1), ('group_id',3), ('index',0)",
+                (Screen::Test, 3, 0),
+                0,
+            ),
+            (
+                "('mode','test'), ('group_id',3), ('index',0)",
+                (Screen::Practice, 3, 0),
+                1,
+            ),
+            (
+                "('mode',1), ('group_id',-4), ('index',1)",
+                (Screen::Test, 2, 1),
+                1,
+            ),
+            (
+                "('mode',0), ('group_id',2), ('index',9)",
+                (Screen::Practice, 2, 0),
+                1,
+            ),
+            ("('group_id',3)", (Screen::Practice, 3, 0), 2),
+            (
+                "('mode',7), ('group_id','x'), ('index',-1)",
+                (Screen::Practice, 2, 0),
+                3,
+            ),
+        ];
+
+        for (rows, expected, fallbacks) in cases {
+            conn.execute("DELETE FROM app_state", []).unwrap();
+            if !rows.is_empty() {
+                conn.execute_batch(&format!("INSERT INTO app_state(key,value) VALUES {rows}"))
+                    .unwrap();
+            }
+
+            let (progress, problems) = recover_progress(&conn).unwrap();
+            assert_eq!(progress, expected, "{rows}");
+            assert_eq!(problems.len(), fallbacks, "{rows}: {problems:?}");
+        }
+    }
+
+    #[test]
+    fn test_group_order_and_resume_by_word() {
+        let conn = test_conn();
+        // Inserted out of id order.
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                (30,'c','d',1), (10,'a','d',1), (20,'b','d',1), (15,'x','d',2);",
+        )
+        .unwrap();
+        let ids = |conn: &Connection| -> Vec<i32> {
+            fetch_words_by_group(conn, 1)
+                .unwrap()
+                .iter()
+                .map(|w| w.id)
+                .collect()
+        };
+        assert_eq!(ids(&conn), vec![10, 20, 30]);
+
+        save_progress(&conn, (Screen::Practice, 1, 1)).unwrap();
+        assert_eq!(
+            read_state(&conn, "word_id").unwrap(),
+            Some(Value::Integer(20))
+        );
+
+        // A word joins the group ahead of the saved one: resume follows it.
+        conn.execute("UPDATE words SET group_id=1 WHERE id=15", [])
+            .unwrap();
+        assert_eq!(ids(&conn), vec![10, 15, 20, 30]);
+        let (progress, problems) = recover_progress(&conn).unwrap();
+        assert_eq!(progress, (Screen::Practice, 1, 2));
+        assert!(problems.is_empty());
+
+        // The saved word is gone: the index is used as it is.
+        conn.execute("DELETE FROM words WHERE id=20", []).unwrap();
+        assert_eq!(fetch_progress(&conn).unwrap(), (Screen::Practice, 1, 1));
+    }
+
+    #[test]
+    fn test_frequency_order_puts_unranked_last() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,frequency_rank) VALUES
+                (1,'a','d',1,NULL), (2,'b','d',1,5000), (3,'c','d',1,NULL),
+                (4,'e','d',1,12), (5,'f','d',1,300);",
+        )
+        .unwrap();
+        let ids = |conn: &Connection| -> Vec<i32> {
+            fetch_words_by_group(conn, 1)
+                .unwrap()
+                .iter()
+                .map(|w| w.id)
+                .collect()
+        };
+        save_progress(&conn, (Screen::Practice, 1, 1)).unwrap();
+        assert_eq!(word_order(&conn).unwrap(), WordOrder::Id);
+
+        set_word_order(&conn, WordOrder::Frequency).unwrap();
+        assert_eq!(ids(&conn), vec![4, 5, 2, 1, 3]);
+        // The saved word, b, is third in the new order.
+        assert_eq!(fetch_progress(&conn).unwrap(), (Screen::Practice, 1, 2));
+        assert_eq!(count_group_from(&conn, 1, 2).unwrap(), (3, 3));
+
+        save_progress(&conn, (Screen::Test, 1, 3)).unwrap();
+        assert_eq!(
+            read_state(&conn, "word_id").unwrap(),
+            Some(Value::Integer(1))
+        );
+        set_word_order(&conn, WordOrder::Id).unwrap();
+        assert_eq!(ids(&conn), vec![1, 2, 3, 4, 5]);
+        assert_eq!(fetch_progress(&conn).unwrap(), (Screen::Test, 1, 0));
+    }
+
+    #[test]
+    fn test_session_record_lifecycle() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES(1,'a','b',1), (2,'c','d',1);",
+        )
+        .unwrap();
+        // Made outside any session: not stamped.
+        insert_review(&conn, 1, 50, true, Screen::Practice).unwrap();
+
+        let id = open_session_record(&conn, Type::Group, 100).unwrap();
+        insert_review(&conn, 1, 110, true, Screen::Practice).unwrap();
+        insert_review(&conn, 2, 120, false, Screen::Practice).unwrap();
+        insert_review(&conn, 2, 130, true, Screen::Test).unwrap();
+        assert!(fetch_recent_sessions(&conn, 10).unwrap().is_empty());
+
+        finish_session_record(&conn, 200).unwrap();
+        let sessions = fetch_recent_sessions(&conn, 10).unwrap();
+        assert_eq!(
+            sessions,
+            vec![SessionRecord {
+                id,
+                started_at: 100,
+                ended_at: Some(200),
+                session_type: Type::Group,
+                words_reviewed: 3,
+                correct: 2,
+            }]
+        );
+
+        // Reviews join back to their session.
+        let joined: Vec<(i32, i32)> = conn
+            .prepare(
+                "SELECT r.word_id, r.reviewed_at FROM reviews r
+                 JOIN sessions s ON s.id = r.session_id
+                 WHERE s.type = 0 ORDER BY r.id",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(joined, vec![(1, 110), (2, 120), (2, 130)]);
+
+        // Finishing again, with nothing open, changes nothing.
+        finish_session_record(&conn, 300).unwrap();
+        insert_review(&conn, 1, 310, true, Screen::Practice).unwrap();
+        assert_eq!(fetch_recent_sessions(&conn, 10).unwrap(), sessions);
+    }
+
+    #[test]
+    fn test_abandoned_session_closes_at_last_review() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(id,word,definition,group_id) VALUES(1,'a','b',1)",
+            [],
+        )
+        .unwrap();
+
+        open_session_record(&conn, Type::Marked, 100).unwrap();
+        insert_review(&conn, 1, 140, false, Screen::Practice).unwrap();
+        // Crash: the next start closes it.
+        open_session_record(&conn, Type::Weak, 500).unwrap();
+        // Crash with no reviews at all: closes at its start.
+        assert_eq!(close_abandoned_sessions(&conn).unwrap(), 1);
+
+        let ends: Vec<(Type, Option<i32>, i64)> = fetch_recent_sessions(&conn, 10)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.session_type, s.ended_at, s.words_reviewed))
+            .collect();
+        assert_eq!(
+            ends,
+            vec![(Type::Weak, Some(500), 0), (Type::Marked, Some(140), 1)]
+        );
+        assert_eq!(close_abandoned_sessions(&conn).unwrap(), 0);
+    }
+
     #[test]
     fn test_update_word_stats() {
-        let conn = setup();
+        let conn = test_conn();
 
-        conn.execute("INSERT INTO words VALUES(1,'a','b',1,0,0,0,0)", [])
+        conn.execute("INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count) VALUES(1,'a','b',1,0,0,0,0)", [])
             .unwrap();
 
         let w = Word {
@@ -203,9 +1389,39 @@ mod tests {
         assert_eq!(v, 5);
     }
 
+    #[test]
+    fn test_invalid_stats_are_rejected() {
+        let conn = test_conn();
+        conn.execute("INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count) VALUES(1,'a','b',1,0,0,2,1)", [])
+            .unwrap();
+        let mut w = fetch_words_by_group(&conn, 1).unwrap().remove(0);
+        w.success_count = 3;
+
+        let err = update_word_stats(&conn, &w).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Not saving a: 3 successes out of 2 attempts"
+        );
+
+        // Writes that skip the check still hit the constraint.
+        for sql in [
+            "UPDATE words SET success_count=3 WHERE id=1",
+            "UPDATE words SET success_count=-1 WHERE id=1",
+            "UPDATE words SET times_seen=-1, success_count=0 WHERE id=1",
+        ] {
+            assert!(conn.execute(sql, []).is_err(), "{sql}");
+        }
+        let stats: (i64, i64) = conn
+            .query_row("SELECT times_seen, success_count FROM words", [], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(stats, (2, 1));
+    }
+
     #[test]
     fn test_fetch_final_group() {
-        let conn = setup();
+        let conn = test_conn();
         let g = fetch_final_group(&conn).unwrap();
         assert_eq!(g, None);
 
@@ -224,4 +1440,332 @@ mod tests {
         let g = fetch_final_group(&conn).unwrap();
         assert_eq!(g, Some(3));
     }
+
+    #[test]
+    fn test_fetch_group_stats() {
+        let conn = test_conn();
+
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id,last_seen,times_seen,success_count)
+             VALUES('a','b',1,100,4,3), ('c','d',1,NULL,0,0), ('e','f',2,50,2,0),
+                   ('g','h',2,70,1,1)",
+        )
+        .unwrap();
+        name_group(&conn, 2, "Starter 2").unwrap();
+        name_group(&conn, 2, "ignored").unwrap();
+
+        let stats = fetch_group_stats(&conn, false).unwrap();
+
+        assert_eq!(
+            stats,
+            vec![
+                GroupStats {
+                    group_id: 1,
+                    words: 2,
+                    seen: 1,
+                    times_seen: 4,
+                    success_count: 3,
+                    last_seen: Some(100),
+                    name: None,
+                    archived: false,
+                },
+                GroupStats {
+                    group_id: 2,
+                    words: 2,
+                    seen: 2,
+                    times_seen: 3,
+                    success_count: 1,
+                    last_seen: Some(70),
+                    name: Some("Starter 2".into()),
+                    archived: false,
+                },
+            ]
+        );
+    }
+
+    fn marked(conn: &Connection) -> Vec<String> {
+        fetch_marked_names(conn).unwrap()
+    }
+
+    fn marks_fixture() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,marked) VALUES
+                 (1,'abate','lessen',1,1), (2,'laud','praise',1,0), (3,'Laud','praise',2,0),
+                 (4,'wane','decrease',2,0), (5,'ebb','recede',2,1)",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_import_marks_ignores_case_and_reports_unmatched() {
+        let conn = marks_fixture();
+        let import = import_marks(
+            &conn,
+            &["LAUD", "", "abate", "zephyr", "laud", " Quell "],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            import,
+            MarksImport {
+                matched: 3,
+                newly_marked: 2,
+                unmarked: 0,
+                unmatched: vec!["zephyr".into(), "Quell".into()],
+            }
+        );
+        assert_eq!(marked(&conn), ["abate", "laud", "Laud", "ebb"]);
+        assert_eq!(
+            import.report(false),
+            vec![
+                "Marked 3 words (1 already marked)",
+                "2 not found:",
+                "  zephyr",
+                "  Quell"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_marks_can_replace_all_marks() {
+        let conn = marks_fixture();
+        let import = import_marks(&conn, &["Wane", "abate"], true).unwrap();
+        assert_eq!(
+            (import.matched, import.newly_marked, import.unmarked),
+            (2, 1, 1)
+        );
+        assert_eq!(marked(&conn), ["abate", "wane"]);
+        assert_eq!(
+            import.report(true),
+            vec![
+                "Marked 2 words (1 already marked)",
+                "Unmarked 1 words not in the list"
+            ]
+        );
+
+        // An empty list clears them all.
+        assert_eq!(import_marks(&conn, &[], true).unwrap().unmarked, 2);
+        assert!(marked(&conn).is_empty());
+    }
+
+    #[test]
+    fn test_mastered_words_leave_the_rotation() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count,mastered_at)
+             VALUES(1,'a','b',1,1,10,10,9,10), (2,'c','d',1,1,20,3,1,NULL), (3,'e','f',2,0,NULL,0,0,NULL)",
+        )
+        .unwrap();
+        let ids = |words: Vec<Word>| words.iter().map(|w| w.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(fetch_words_by_group(&conn, 1).unwrap()), [2]);
+        assert_eq!(count_group_from(&conn, 1, 0).unwrap(), (1, 0));
+        assert_eq!(
+            WordStore::new(&conn).count(WordFilter::Group(1)).unwrap(),
+            1
+        );
+        assert_eq!(ids(fetch_weak_words(&conn, 1, 10).unwrap()), [2]);
+        assert_eq!(count_weak_words(&conn, 1, 10).unwrap(), 1);
+        assert_eq!(ids(fetch_marked_outside_group(&conn, 2, 10).unwrap()), [2]);
+        assert_eq!(count_marked_outside_group(&conn, 2, 10).unwrap(), (1, 0));
+        // Marked and Quick Review sessions keep them.
+        assert_eq!(ids(fetch_marked_words(&conn).unwrap()).len(), 2);
+        assert_eq!(ids(fetch_stale_words(&conn, 10).unwrap()), [1, 2]);
+        assert_eq!(
+            WordStore::new(&conn).count(WordFilter::Mastered).unwrap(),
+            1
+        );
+
+        set_include_mastered(&conn, true).unwrap();
+        assert_eq!(ids(fetch_words_by_group(&conn, 1).unwrap()), [1, 2]);
+        assert_eq!(count_weak_words(&conn, 1, 10).unwrap(), 2);
+        assert_eq!(count_marked_outside_group(&conn, 2, 10).unwrap(), (2, 0));
+        set_include_mastered(&conn, false).unwrap();
+        assert_eq!(count_group_from(&conn, 1, 0).unwrap(), (1, 0));
+
+        assert!(!set_mastered(&conn, 1, Some(30)).unwrap());
+        assert!(set_mastered(&conn, 1, None).unwrap());
+        assert!(!set_mastered(&conn, 1, None).unwrap());
+        assert_eq!(ids(fetch_words_by_group(&conn, 1).unwrap()), [1, 2]);
+    }
+
+    #[test]
+    fn test_archived_groups_leave_the_rotation() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count)
+             VALUES(1,'a','b',1,1,10,3,1), (2,'c','d',2,1,20,3,1), (3,'e','f',3,1,30,3,1),
+                   (4,'g','h',4,0,NULL,0,0);
+             INSERT INTO archived_groups(group_id) VALUES(2), (4)",
+        )
+        .unwrap();
+        let ids = |words: Vec<Word>| words.iter().map(|w| w.id).collect::<Vec<_>>();
+
+        let groups = fetch_group_stats(&conn, false).unwrap();
+        assert_eq!(
+            groups.iter().map(|g| g.group_id).collect::<Vec<_>>(),
+            [1, 3]
+        );
+        let groups = fetch_group_stats(&conn, true).unwrap();
+        let archived: Vec<bool> = groups.iter().map(|g| g.archived).collect();
+        assert_eq!(archived, [false, true, false, true]);
+
+        assert_eq!(ids(fetch_marked_outside_group(&conn, 3, 10).unwrap()), [1]);
+        assert_eq!(count_marked_outside_group(&conn, 3, 10).unwrap(), (1, 0));
+        assert_eq!(ids(fetch_weak_words(&conn, 1, 10).unwrap()), [1, 3]);
+        assert_eq!(count_weak_words(&conn, 1, 10).unwrap(), 2);
+        assert_eq!(ids(fetch_stale_words(&conn, 10).unwrap()), [1, 3]);
+        assert_eq!(count_seen_words(&conn).unwrap(), 2);
+
+        assert_eq!(fetch_next_group(&conn, 1).unwrap(), Some(3));
+        assert_eq!(fetch_next_group(&conn, 3).unwrap(), None);
+        assert!(is_group_archived(&conn, 2).unwrap());
+        assert!(!is_group_archived(&conn, 3).unwrap());
+
+        // Its own group's session still has every word.
+        assert_eq!(ids(fetch_words_by_group(&conn, 2).unwrap()), [2]);
+    }
+
+    #[test]
+    fn test_counts() {
+        let conn = test_conn();
+
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id,marked,times_seen,success_count)
+             VALUES('a','b',1,1,0,0), ('c','d',1,0,3,1), ('e','f',2,1,2,2)",
+        )
+        .unwrap();
+
+        assert_eq!(count_words(&conn).unwrap(), 3);
+        assert_eq!(count_unseen_words(&conn).unwrap(), 1);
+        assert_eq!(count_marked_words(&conn).unwrap(), 2);
+        assert_eq!(count_weak_words(&conn, 2, 20).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_weak_words_need_evidence() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id,times_seen,success_count) VALUES
+                 ('once',   'd',1, 1, 0),
+                 ('steady', 'd',1,20,12),
+                 ('twice',  'd',1, 2, 0),
+                 ('perfect','d',1, 6, 6),
+                 ('shaky',  'd',1, 4, 2)",
+        )
+        .unwrap();
+        let words = |min_seen, limit| -> Vec<String> {
+            fetch_weak_words(&conn, min_seen, limit)
+                .unwrap()
+                .into_iter()
+                .map(|w| w.word)
+                .collect()
+        };
+
+        assert_eq!(words(1, 20), vec!["twice", "steady", "once", "shaky"]);
+        assert_eq!(words(2, 20), vec!["twice", "steady", "shaky"]);
+        assert_eq!(words(2, 2), vec!["twice", "steady"]);
+        for (min_seen, limit) in [(1, 20), (2, 20), (2, 2)] {
+            assert_eq!(
+                count_weak_words(&conn, min_seen, limit).unwrap(),
+                words(min_seen, limit).len() as i64
+            );
+        }
+    }
+
+    #[test]
+    fn test_recent_outcomes_newest_last() {
+        let conn = test_conn();
+        conn.execute("INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count) VALUES(1,'a','b',1,0,0,0,0)", [])
+            .unwrap();
+
+        for (i, correct) in [true, false, false, true].into_iter().enumerate() {
+            insert_review(&conn, 1, 100 + i as i32, correct, Screen::Practice).unwrap();
+        }
+
+        assert_eq!(
+            fetch_recent_outcomes(&conn, 1, 3).unwrap(),
+            vec![false, false, true]
+        );
+        assert!(fetch_recent_outcomes(&conn, 2, 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reviews_since_day_boundary() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count) VALUES(1,'a','b',1,0,0,0,0);
+             INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count) VALUES(2,'c','d',1,0,0,0,0);",
+        )
+        .unwrap();
+        insert_review(&conn, 1, 999, true, Screen::Practice).unwrap();
+        insert_review(&conn, 1, 1000, false, Screen::Test).unwrap();
+        insert_review(&conn, 2, 1500, true, Screen::Practice).unwrap();
+
+        let today = fetch_reviews_since(&conn, 1000, 10, 0).unwrap();
+        let words: Vec<&str> = today.iter().map(|r| r.word.as_str()).collect();
+        assert_eq!(words, vec!["c", "a"]);
+        assert_eq!(today[1].mode, Screen::Test);
+        assert!(!today[1].correct);
+
+        let page = fetch_reviews_since(&conn, 1000, 1, 1).unwrap();
+        assert_eq!(page[0].word, "a");
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip_and_close() {
+        let conn = test_conn();
+        assert_eq!(fetch_snapshot(&conn).unwrap(), None);
+
+        let snapshot = SessionSnapshot {
+            session_type: Type::Weak,
+            mode: Screen::Practice,
+            word_ids: vec![3, 1, 2],
+            index: 2,
+            show_definition: false,
+            graded: None,
+            closed_cleanly: false,
+        };
+        save_snapshot(&conn, &snapshot).unwrap();
+        assert_eq!(fetch_snapshot(&conn).unwrap(), Some(snapshot.clone()));
+
+        close_snapshot(&conn).unwrap();
+        assert!(fetch_snapshot(&conn).unwrap().unwrap().closed_cleanly);
+
+        save_snapshot(&conn, &snapshot).unwrap();
+        assert!(!fetch_snapshot(&conn).unwrap().unwrap().closed_cleanly);
+    }
+
+    #[test]
+    fn test_delete_word_moves_progress_on() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'abate','lessen',2), (2,'belie','mask',2), (3,'wane','decrease',2);
+             INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES (2,100,1,0);",
+        )
+        .unwrap();
+        save_progress(&conn, (Screen::Practice, 2, 1)).unwrap();
+        save_last_graded(&conn, 2).unwrap();
+
+        let deleted = delete_word(&conn, 2).unwrap().unwrap();
+        assert_eq!(deleted.word, "belie");
+        assert_eq!(deleted.reviews.len(), 1);
+        assert_eq!(fetch_last_graded(&conn).unwrap(), None);
+        assert_eq!(
+            read_state(&conn, "word_id").unwrap(),
+            Some(Value::Integer(3))
+        );
+
+        // The last word in the group moves progress back one.
+        save_progress(&conn, (Screen::Practice, 2, 1)).unwrap();
+        delete_word(&conn, 3).unwrap();
+        assert_eq!(fetch_progress(&conn).unwrap(), (Screen::Practice, 2, 0));
+        assert!(delete_word(&conn, 3).unwrap().is_none());
+
+        restore_word(&conn, &deleted).unwrap();
+        assert_eq!(count_words(&conn).unwrap(), 2);
+    }
 }
@@ -9,11 +9,143 @@ CREATE TABLE IF NOT EXISTS words (
     marked INTEGER NOT NULL DEFAULT 0,
     last_seen INTEGER,
     times_seen INTEGER NOT NULL DEFAULT 0,
-    success_count INTEGER NOT NULL DEFAULT 0
+    success_count INTEGER NOT NULL DEFAULT 0,
+    leech INTEGER NOT NULL DEFAULT 0,
+    suspended INTEGER NOT NULL DEFAULT 0,
+    frequency_rank INTEGER,
+    ipa TEXT,
+    mastered_at INTEGER,
+    reading TEXT,
+    CHECK (success_count BETWEEN 0 AND times_seen)
+);
+
+CREATE TABLE IF NOT EXISTS group_names (
+    group_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS archived_groups (
+    group_id INTEGER PRIMARY KEY
 );
 
 CREATE TABLE IF NOT EXISTS app_state (
     key TEXT PRIMARY KEY,
     value INTEGER NOT NULL
 );
+
+CREATE TABLE IF NOT EXISTS reviews (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    word_id INTEGER NOT NULL REFERENCES words(id) ON DELETE CASCADE,
+    reviewed_at INTEGER NOT NULL,
+    correct INTEGER NOT NULL,
+    mode INTEGER NOT NULL
+    -- session_id is added by migration 3, answer by migration 7.
+);
+
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER,
+    type INTEGER NOT NULL,
+    words_reviewed INTEGER NOT NULL DEFAULT 0,
+    correct INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS reviews_word_time ON reviews(word_id, reviewed_at);
+
+CREATE TABLE IF NOT EXISTS session_snapshot (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    session_type INTEGER NOT NULL,
+    mode INTEGER NOT NULL,
+    word_ids TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    show_definition INTEGER NOT NULL,
+    graded INTEGER,
+    closed_cleanly INTEGER NOT NULL DEFAULT 0
+);
 "#;
+
+/// Settings that come from the config rather than the database, such as the
+/// word order. Kept in the temp schema, so each connection has its own and
+/// read-only ones can set them too.
+pub const SETTINGS_SCHEMA: &str = "
+CREATE TEMP TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value INTEGER NOT NULL
+);
+";
+
+/// Changes to databases created before `INIT_SCHEMA` had them. Migration `i`
+/// moves `user_version` from `i` to `i + 1`; each runs in a transaction with
+/// foreign keys off, so rebuilding a table leaves rows that reference it
+/// alone.
+pub const MIGRATIONS: &[&str] = &[
+    // 1: success_count BETWEEN 0 AND times_seen. Existing rows are capped,
+    // since the copy would fail otherwise.
+    r#"
+CREATE TABLE words_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    word TEXT NOT NULL UNIQUE,
+    definition TEXT NOT NULL,
+    group_id INTEGER NOT NULL,
+    marked INTEGER NOT NULL DEFAULT 0,
+    last_seen INTEGER,
+    times_seen INTEGER NOT NULL DEFAULT 0,
+    success_count INTEGER NOT NULL DEFAULT 0,
+    CHECK (success_count BETWEEN 0 AND times_seen)
+);
+INSERT INTO words_new
+    SELECT id, word, definition, group_id, marked, last_seen,
+           MAX(times_seen, 0), MIN(MAX(success_count, 0), MAX(times_seen, 0))
+    FROM words;
+DROP TABLE words;
+ALTER TABLE words_new RENAME TO words;
+"#,
+    // 2: leech flag and suspension.
+    r#"
+ALTER TABLE words ADD COLUMN leech INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE words ADD COLUMN suspended INTEGER NOT NULL DEFAULT 0;
+"#,
+    // 3: reviews remember the session they were made in.
+    r#"
+ALTER TABLE reviews ADD COLUMN session_id INTEGER REFERENCES sessions(id) ON DELETE SET NULL;
+CREATE INDEX reviews_session ON reviews(session_id);
+"#,
+    // 4: rank in an imported word frequency list; NULL when unranked.
+    r#"
+ALTER TABLE words ADD COLUMN frequency_rank INTEGER;
+CREATE INDEX words_frequency ON words(frequency_rank);
+"#,
+    // 5: IPA transcription, without the slashes; NULL when unknown.
+    r#"
+ALTER TABLE words ADD COLUMN ipa TEXT;
+"#,
+    // 6: groups kept out of the main rotation. A table of their own, since
+    // most groups have no row in group_names; `INIT_SCHEMA` has already
+    // created it on the way here.
+    r#"
+CREATE TABLE IF NOT EXISTS archived_groups (
+    group_id INTEGER PRIMARY KEY
+);
+"#,
+    // 7: what was typed for a review on the Test screen; NULL in practice.
+    r#"
+ALTER TABLE reviews ADD COLUMN answer TEXT;
+"#,
+    // 8: when a word was mastered; NULL while it is still being learned.
+    // Words that already meet the bar count as mastered at their last
+    // review.
+    r#"
+ALTER TABLE words ADD COLUMN mastered_at INTEGER;
+UPDATE words SET mastered_at = last_seen
+WHERE times_seen >= 5 AND success_count * 100 >= times_seen * 90
+AND (SELECT COUNT(*) FROM (SELECT correct FROM reviews r WHERE r.word_id = words.id
+                           ORDER BY reviewed_at DESC, id DESC LIMIT 5)
+     WHERE correct = 1) = 5;
+"#,
+    // 9: how the word is read, for scripts like kanji; NULL when it isn't
+    // written down.
+    r#"
+ALTER TABLE words ADD COLUMN reading TEXT;
+"#,
+];
@@ -0,0 +1,194 @@
+use super::models::Word;
+use super::queries::{IN_ROTATION, map_word};
+use crate::error::Result;
+use rusqlite::{Connection, OptionalExtension, params};
+
+/// Which words a page or count covers.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum WordFilter {
+    #[default]
+    All,
+    /// What a Group session over this group shows: suspended words, and
+    /// mastered ones unless they're included, are left out.
+    Group(i32),
+    /// Ranked in the top N of the imported frequency list.
+    TopFrequency(i32),
+    /// Mastered, whether or not they're in rotation.
+    Mastered,
+}
+
+impl WordFilter {
+    // Every clause takes ?1 so the statements can share one parameter list;
+    // All and Mastered ignore it.
+    fn clause(&self) -> (String, i32) {
+        match self {
+            WordFilter::All => ("?1 = ?1".into(), 0),
+            WordFilter::Group(id) => (format!("group_id = ?1 AND {IN_ROTATION}"), *id),
+            WordFilter::TopFrequency(n) => ("frequency_rank <= ?1".into(), *n),
+            WordFilter::Mastered => ("mastered_at IS NOT NULL AND ?1 = ?1".into(), 0),
+        }
+    }
+}
+
+/// Paged access to the word table, for callers that would otherwise load
+/// every row. Pages are keyset-paginated on `id`, so a late page costs the
+/// same as the first.
+#[derive(Debug, Clone, Copy)]
+pub struct WordStore<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> WordStore<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// Up to `limit` words matching `filter` with ids above `after`, in id
+    /// order. Pass the last id of a page to get the next; 0 starts over.
+    pub fn fetch_page(&self, filter: WordFilter, after: i32, limit: usize) -> Result<Vec<Word>> {
+        let (clause, value) = filter.clause();
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT id, word, definition, group_id,
+                    marked, last_seen, times_seen, success_count
+             FROM words
+             WHERE {clause} AND id > ?2
+             ORDER BY id
+             LIMIT ?3"
+        ))?;
+
+        Ok(stmt
+            .query_map(params![value, after, limit as i64], map_word)?
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn count(&self, filter: WordFilter) -> Result<i64> {
+        let (clause, value) = filter.clause();
+        Ok(self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM words WHERE {clause}"),
+            params![value],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn fetch(&self, id: i32) -> Result<Option<Word>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, word, definition, group_id,
+                    marked, last_seen, times_seen, success_count
+             FROM words WHERE id=?1",
+        )?;
+        Ok(stmt.query_row(params![id], map_word).optional()?)
+    }
+
+    /// Calls `f` with every word matching `filter`, one page at a time.
+    pub fn for_each(
+        &self,
+        filter: WordFilter,
+        page_size: usize,
+        mut f: impl FnMut(Word) -> Result<()>,
+    ) -> Result<()> {
+        let mut after = 0;
+        loop {
+            let page = self.fetch_page(filter, after, page_size)?;
+            let done = page.len() < page_size;
+            for word in page {
+                after = word.id;
+                f(word)?;
+            }
+            if done {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use std::time::{Duration, Instant};
+
+    fn store_conn() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'a','x',1), (2,'b','x',2), (3,'c','x',1), (5,'d','x',1), (8,'e','x',2);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn ids(words: &[Word]) -> Vec<i32> {
+        words.iter().map(|w| w.id).collect()
+    }
+
+    #[test]
+    fn test_pages_follow_last_id() {
+        let conn = store_conn();
+        let store = WordStore::new(&conn);
+
+        let first = store.fetch_page(WordFilter::All, 0, 2).unwrap();
+        assert_eq!(ids(&first), vec![1, 2]);
+        let second = store.fetch_page(WordFilter::All, 2, 2).unwrap();
+        assert_eq!(ids(&second), vec![3, 5]);
+
+        let group = store.fetch_page(WordFilter::Group(1), 3, 10).unwrap();
+        assert_eq!(ids(&group), vec![5]);
+    }
+
+    #[test]
+    fn test_counts_and_single_fetch() {
+        let conn = store_conn();
+        let store = WordStore::new(&conn);
+
+        assert_eq!(store.count(WordFilter::All).unwrap(), 5);
+        assert_eq!(store.count(WordFilter::Group(2)).unwrap(), 2);
+        assert_eq!(store.fetch(5).unwrap().unwrap().word, "d");
+        assert!(store.fetch(4).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_for_each_visits_every_word_once() {
+        let conn = store_conn();
+        let mut seen = Vec::new();
+        WordStore::new(&conn)
+            .for_each(WordFilter::All, 2, |w| {
+                seen.push(w.id);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen, vec![1, 2, 3, 5, 8]);
+    }
+
+    /// cargo test --release -- --ignored word_store_scales
+    #[test]
+    #[ignore]
+    fn test_word_store_scales() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200000)
+             INSERT INTO words(word, definition, group_id)
+             SELECT 'word' || i, 'definition of word ' || i, i / 20 + 1 FROM n;",
+        )
+        .unwrap();
+        let store = WordStore::new(&conn);
+
+        let started = Instant::now();
+        let page = store.fetch_page(WordFilter::All, 199_900, 50).unwrap();
+        let late = started.elapsed();
+        assert_eq!(page.len(), 50);
+        assert!(late < Duration::from_millis(50), "late page took {late:?}");
+
+        let mut largest_page = 0;
+        let mut after = 0;
+        loop {
+            let page = store.fetch_page(WordFilter::All, after, 500).unwrap();
+            largest_page = largest_page.max(page.len());
+            match page.last() {
+                Some(word) => after = word.id,
+                None => break,
+            }
+        }
+        assert_eq!(after, 200_000);
+        assert_eq!(largest_page, 500);
+    }
+}
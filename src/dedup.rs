@@ -0,0 +1,253 @@
+use crate::db::models::Word;
+use crate::db::store::{WordFilter, WordStore};
+use crate::db::{queries, with_tx};
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use std::collections::{BTreeMap, HashMap};
+use unicode_normalization::UnicodeNormalization;
+
+const PAGE_SIZE: usize = 1000;
+
+/// Merges words that only differ in case or Unicode composition ("Abate"
+/// and "abate"), or with `dry_run` just lists them. Returns one line per
+/// word kept.
+pub fn run(conn: &Connection, dry_run: bool) -> Result<Vec<String>> {
+    let groups = find_duplicates(conn)?;
+    if groups.is_empty() {
+        return Ok(vec!["No duplicates found".into()]);
+    }
+
+    let verb = if dry_run { "would merge" } else { "merged" };
+    let report = groups
+        .iter()
+        .map(|group| {
+            let others: Vec<String> = group[1..].iter().map(label).collect();
+            format!("{}: {verb} {}", label(&group[0]), others.join(", "))
+        })
+        .collect();
+
+    if !dry_run {
+        with_tx(conn, |tx| {
+            let mut moved = HashMap::new();
+            for group in &groups {
+                merge(tx, group)?;
+                moved.extend(group[1..].iter().map(|w| (w.id, group[0].id)));
+            }
+            if let Some(snapshot) = queries::fetch_snapshot(tx)? {
+                let ids: Vec<i32> = snapshot
+                    .word_ids
+                    .iter()
+                    .map(|id| *moved.get(id).unwrap_or(id))
+                    .collect();
+                queries::set_snapshot_word_ids(tx, &ids)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(report)
+}
+
+fn label(word: &Word) -> String {
+    format!("{} (#{})", word.word, word.id)
+}
+
+/// Sets of words equal under lowercase + NFC, each oldest first. The
+/// oldest row is the one kept.
+fn find_duplicates(conn: &Connection) -> Result<Vec<Vec<Word>>> {
+    let mut by_key: BTreeMap<String, Vec<Word>> = BTreeMap::new();
+    // Visits words in id order, so each set comes out oldest first.
+    WordStore::new(conn).for_each(WordFilter::All, PAGE_SIZE, |word| {
+        by_key.entry(key(&word.word)).or_default().push(word);
+        Ok(())
+    })?;
+
+    let mut groups: Vec<Vec<Word>> = by_key.into_values().filter(|g| g.len() > 1).collect();
+    groups.sort_by_key(|g| g[0].id);
+    Ok(groups)
+}
+
+fn key(word: &str) -> String {
+    word.to_lowercase().nfc().collect()
+}
+
+/// Folds the rest of `group` into its first word. Counts are summed, capped
+/// at what a row holds; marks are OR-ed; the latest `last_seen` wins; and
+/// definitions are joined unless one already contains the other. Reviews
+/// and the last-graded marker move to the kept row before the others go.
+fn merge(conn: &Connection, group: &[Word]) -> Result<()> {
+    let keep = &group[0];
+    let times_seen = group
+        .iter()
+        .map(|w| i64::from(w.times_seen))
+        .sum::<i64>()
+        .min(u32::MAX.into());
+    let success_count = group
+        .iter()
+        .map(|w| i64::from(w.success_count))
+        .sum::<i64>()
+        .min(times_seen);
+    let marked = group.iter().any(|w| w.marked);
+    let last_seen = group.iter().filter_map(|w| w.last_seen).max();
+
+    for word in &group[1..] {
+        conn.execute(
+            "UPDATE reviews SET word_id=?1 WHERE word_id=?2",
+            [keep.id, word.id],
+        )?;
+        conn.execute(
+            "UPDATE app_state SET value=?1 WHERE key='last_graded' AND value=?2",
+            [keep.id, word.id],
+        )?;
+        conn.execute("DELETE FROM words WHERE id=?1", [word.id])?;
+    }
+
+    conn.execute(
+        "UPDATE words
+         SET word=?1, definition=?2, marked=?3, last_seen=?4, times_seen=?5, success_count=?6
+         WHERE id=?7",
+        params![
+            keep.word.nfc().collect::<String>(),
+            merge_definitions(group),
+            marked,
+            last_seen,
+            times_seen,
+            success_count,
+            keep.id
+        ],
+    )?;
+    Ok(())
+}
+
+fn merge_definitions(group: &[Word]) -> String {
+    let mut definitions: Vec<&str> = group
+        .iter()
+        .map(|w| w.definition.trim())
+        .filter(|d| !d.is_empty())
+        .collect();
+    // Longest first, so a shorter one already covered is dropped.
+    definitions.sort_by_key(|d| std::cmp::Reverse(d.len()));
+
+    let mut kept: Vec<&str> = Vec::new();
+    for definition in definitions {
+        if !kept.iter().any(|k| k.contains(definition)) {
+            kept.push(definition);
+        }
+    }
+    kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::SystemClock;
+    use crate::core::progress::update_word_stats;
+    use crate::core::session::Type;
+    use crate::core::stats::LeechPolicy;
+    use crate::db::models::SessionSnapshot;
+    use crate::db::test_conn;
+    use crate::ui::app::Screen;
+
+    fn colliding() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,marked,last_seen,times_seen,success_count) VALUES
+                 (1,'abate','lessen',1,0,100,3,2),
+                 (2,'belie','mask',1,0,NULL,0,0),
+                 (3,'Abate','to lessen in intensity',2,1,300,2,0),
+                 (4,'cafe\u{301}','coffee shop',1,0,NULL,1,1),
+                 (5,'ABATE','subside',3,0,200,1,1),
+                 (6,'Café','bistro',1,0,NULL,0,0);
+             INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES
+                 (1,100,1,0), (3,300,0,0), (5,200,1,0);",
+        )
+        .unwrap();
+        queries::save_snapshot(
+            &conn,
+            &SessionSnapshot {
+                session_type: Type::Marked,
+                mode: Screen::Practice,
+                word_ids: vec![3, 2, 6],
+                index: 0,
+                show_definition: false,
+                graded: None,
+                closed_cleanly: false,
+            },
+        )
+        .unwrap();
+        queries::save_last_graded(&conn, 5).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_dry_run_changes_nothing() {
+        let conn = colliding();
+        let report = run(&conn, true).unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "abate (#1): would merge Abate (#3), ABATE (#5)",
+                "cafe\u{301} (#4): would merge Café (#6)",
+            ]
+        );
+        assert_eq!(queries::count_words(&conn).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_merge_sums_stats_and_moves_references() {
+        let conn = colliding();
+        run(&conn, false).unwrap();
+
+        let store = WordStore::new(&conn);
+        assert_eq!(queries::count_words(&conn).unwrap(), 3);
+        let abate = store.fetch(1).unwrap().unwrap();
+        assert_eq!(abate.word, "abate");
+        assert_eq!(abate.definition, "to lessen in intensity\nsubside");
+        assert_eq!((abate.times_seen, abate.success_count), (6, 3));
+        assert!(abate.marked);
+        assert_eq!(abate.last_seen, Some(300));
+        assert_eq!(abate.group_id, 1);
+        let cafe = store.fetch(4).unwrap().unwrap();
+        assert_eq!(cafe.word, "café");
+        assert_eq!(cafe.definition, "coffee shop\nbistro");
+
+        let reviews: Vec<i32> = conn
+            .prepare("SELECT word_id FROM reviews ORDER BY reviewed_at")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(reviews, vec![1, 1, 1]);
+        assert_eq!(queries::fetch_last_graded(&conn).unwrap(), Some(1));
+        let snapshot = queries::fetch_snapshot(&conn).unwrap().unwrap();
+        assert_eq!(snapshot.word_ids, vec![1, 2, 4]);
+
+        assert_eq!(run(&conn, false).unwrap(), vec!["No duplicates found"]);
+    }
+
+    #[test]
+    fn test_heavily_reviewed_merge_can_still_be_graded() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id,times_seen,success_count) VALUES
+                 ('a','x',1,200,190), ('A','x',1,100,100);",
+        )
+        .unwrap();
+        run(&conn, false).unwrap();
+        let mut word = WordStore::new(&conn).fetch(1).unwrap().unwrap();
+        assert_eq!((word.times_seen, word.success_count), (300, 290));
+        assert_eq!(word.definition, "x");
+
+        update_word_stats(
+            &conn,
+            &SystemClock,
+            LeechPolicy::default(),
+            &mut word,
+            true,
+            Screen::Test,
+        )
+        .unwrap();
+        let word = WordStore::new(&conn).fetch(1).unwrap().unwrap();
+        assert_eq!((word.times_seen, word.success_count), (301, 291));
+    }
+}
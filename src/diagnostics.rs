@@ -0,0 +1,69 @@
+use crate::db::queries;
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fmt;
+
+/// Facts worth pasting into a bug report.
+#[derive(Debug, Serialize)]
+pub struct Info {
+    pub version: &'static str,
+    pub commit: &'static str,
+    pub db_path: String,
+    pub schema_version: i64,
+    pub words: i64,
+}
+
+pub fn gather(conn: &Connection, db_path: &str) -> Result<Info> {
+    Ok(Info {
+        version: env!("CARGO_PKG_VERSION"),
+        commit: env!("VOCABULATOR_GIT_COMMIT"),
+        db_path: std::path::absolute(db_path)?.display().to_string(),
+        schema_version: conn.pragma_query_value(None, "user_version", |row| row.get(0))?,
+        words: queries::count_words(conn)?,
+    })
+}
+
+impl fmt::Display for Info {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version:        {} ({})", self.version, self.commit)?;
+        writeln!(f, "database:       {}", self.db_path)?;
+        writeln!(f, "schema version: {}", self.schema_version)?;
+        write!(f, "words:          {}", self.words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+
+    #[test]
+    fn test_json_structure() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(word,definition,group_id) VALUES('abate','lessen',1)",
+            [],
+        )
+        .unwrap();
+
+        let info = gather(&conn, "vocab.db").unwrap();
+        let json = serde_json::to_value(&info).unwrap();
+
+        let keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["commit", "db_path", "schema_version", "version", "words"]
+        );
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert!(!json["commit"].as_str().unwrap().is_empty());
+        assert!(json["db_path"].as_str().unwrap().ends_with("vocab.db"));
+        assert_eq!(json["words"], 1);
+        assert!(json["schema_version"].is_i64());
+    }
+}
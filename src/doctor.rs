@@ -0,0 +1,321 @@
+use crate::core::clock::{Clock, SKEW_TOLERANCE};
+use crate::db::health::{self, Damage};
+use crate::db::schema::SETTINGS_SCHEMA;
+use crate::db::{init_db, queries};
+use crate::lint;
+use crate::ui::app::Screen;
+use anyhow::Result;
+use rusqlite::Connection;
+
+/// Checks the database for problems the TUI can't get past on its own and
+/// repairs the ones that are safe to fix. Returns one line per finding.
+pub fn run(conn: &Connection, clock: &dyn Clock) -> Result<Vec<String>> {
+    let mut report = Vec::new();
+
+    let integrity: Vec<String> = conn
+        .prepare("PRAGMA integrity_check")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    if integrity != ["ok"] {
+        report.extend(
+            integrity
+                .into_iter()
+                .map(|line| format!("integrity: {line}")),
+        );
+    }
+
+    let ((_, group_id, _), problems) = queries::recover_progress(conn)?;
+    if !problems.is_empty() {
+        queries::save_progress(conn, (Screen::Practice, group_id, 0))?;
+        report.push(format!(
+            "{}; reset to the start of group {group_id}",
+            problems.join("; ")
+        ));
+    }
+
+    // The schema rejects these, but a database edited with checks turned
+    // off can still hold them.
+    let capped = conn.execute(
+        "UPDATE words
+         SET times_seen=MAX(times_seen, 0),
+             success_count=MIN(MAX(success_count, 0), MAX(times_seen, 0))
+         WHERE times_seen < 0 OR success_count < 0 OR success_count > times_seen",
+        [],
+    )?;
+    if capped > 0 {
+        report.push(format!(
+            "{capped} words had success counts outside 0..times seen; capped"
+        ));
+    }
+
+    // Written while the clock was far ahead. Left alone they sort as the
+    // most recent activity until real time catches up.
+    let now = clock.now();
+    let limit = now + SKEW_TOLERANCE;
+    let words = conn.execute(
+        "UPDATE words SET last_seen=?1 WHERE last_seen > ?2",
+        [now, limit],
+    )?;
+    let reviews = conn.execute(
+        "UPDATE reviews SET reviewed_at=?1 WHERE reviewed_at > ?2",
+        [now, limit],
+    )?;
+    if words + reviews > 0 {
+        report.push(format!(
+            "{words} words and {reviews} reviews had timestamps in the future \
+             (clock skew); moved back to now"
+        ));
+    }
+
+    // Left for `vocabulator lint --fix`, which asks first.
+    report.extend(
+        lint::check(conn)?
+            .into_iter()
+            .map(|line| format!("lint: {line}")),
+    );
+
+    if report.is_empty() {
+        report.push("No problems found".into());
+    }
+    Ok(report)
+}
+
+/// Fixes what keeps `init_db` from opening `path` where it can, then runs
+/// the usual checks. Backs `doctor --fix` and the TUI's recovery screen.
+pub fn repair(path: &str, clock: &dyn Clock) -> Result<Vec<String>> {
+    let damage = match init_db(path) {
+        Ok(conn) => return run(&conn, clock),
+        Err(e) => e.downcast::<Damage>()?,
+    };
+    let conn = Connection::open(path)?;
+    let mut report = vec![format!("Found: {damage}")];
+
+    match &damage {
+        Damage::NewerSchema { .. } => {
+            report.push("Can't downgrade it; upgrade vocabulator or restore a backup".into());
+            return Ok(report);
+        }
+        Damage::MissingColumns(columns) => {
+            for column in columns {
+                match column.add_sql() {
+                    Some(sql) => {
+                        conn.execute_batch(&sql)?;
+                        report.push(format!("Added {column} back with its default"));
+                    }
+                    None => report.push(format!("Can't add {column} back; restore a backup")),
+                }
+            }
+        }
+        Damage::Corrupt(_) => {
+            if let Err(e) = conn.execute_batch("REINDEX") {
+                report.push(format!("Rebuilding indexes failed ({e}); restore a backup"));
+                return Ok(report);
+            }
+            report.push("Rebuilt indexes".into());
+            // Rows breaking a CHECK constraint fail the quick check too, and
+            // the usual checks cap them.
+            conn.execute_batch(SETTINGS_SCHEMA)?;
+            match run(&conn, clock) {
+                Ok(lines) => report.extend(lines),
+                Err(e) => {
+                    report.push(format!("Checking rows failed ({e}); restore a backup"));
+                    return Ok(report);
+                }
+            }
+        }
+    }
+    drop(conn);
+
+    match init_db(path) {
+        Ok(_) if matches!(damage, Damage::Corrupt(_)) => {}
+        Ok(conn) => report.extend(run(&conn, clock)?),
+        Err(e) => report.push(format!("Still can't open it ({e}); restore a backup")),
+    }
+    Ok(report)
+}
+
+/// The choices the TUI's recovery screen offers, as text for the CLI.
+pub fn damage_help(db_path: &str, damage: &Damage) -> String {
+    let mut help = format!(
+        "Can't open {db_path}: {damage}\n\n\
+         Options:\n  \
+         vocabulator doctor --fix   try to repair it\n"
+    );
+    match health::backups(db_path).first() {
+        Some(latest) => help.push_str(&format!(
+            "  cp {} {db_path}   restore the latest backup\n",
+            latest.display()
+        )),
+        None => help.push_str("  (no backups found next to it to restore from)\n"),
+    }
+    help.push_str("  or quit and leave it as it is");
+    help
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::{MockClock, SystemClock};
+    use crate::db::{init_db, test_conn};
+
+    #[test]
+    fn test_resets_out_of_range_progress() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES('abate','lessen',2), ('wane','decrease',2)",
+        )
+        .unwrap();
+        queries::save_progress(&conn, (Screen::Test, 2, 7)).unwrap();
+
+        let report = run(&conn, &SystemClock).unwrap();
+        assert_eq!(report.len(), 1);
+        assert!(report[0].contains("reset"));
+
+        let (screen, group, index) = queries::fetch_progress(&conn).unwrap();
+        assert_eq!((screen, group, index), (Screen::Practice, 2, 0));
+        assert_eq!(run(&conn, &SystemClock).unwrap(), vec!["No problems found"]);
+    }
+
+    #[test]
+    fn test_fresh_database_is_fine() {
+        let conn = test_conn();
+        assert_eq!(run(&conn, &SystemClock).unwrap(), vec!["No problems found"]);
+    }
+
+    #[test]
+    fn test_caps_invalid_stats() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "PRAGMA ignore_check_constraints=ON;
+             INSERT INTO words(id,word,definition,group_id,times_seen,success_count) VALUES
+                 (1,'abate','lessen',1,2,5), (2,'wane','decrease',1,-1,-3), (3,'belie','mask',1,4,3);
+             PRAGMA ignore_check_constraints=OFF;",
+        )
+        .unwrap();
+
+        // integrity_check notices the violations too.
+        let report = run(&conn, &SystemClock).unwrap();
+        assert_eq!(
+            report.last().unwrap(),
+            "2 words had success counts outside 0..times seen; capped"
+        );
+        assert!(report[0].contains("CHECK constraint failed"), "{report:?}");
+        assert_eq!(run(&conn, &SystemClock).unwrap(), vec!["No problems found"]);
+        let counts: Vec<(i64, i64)> = conn
+            .prepare("SELECT times_seen, success_count FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(counts, vec![(2, 2), (0, 0), (4, 3)]);
+    }
+
+    #[test]
+    fn test_moves_future_timestamps_back() {
+        let conn = test_conn();
+        let clock = MockClock::at(1_700_000_000);
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,last_seen) VALUES
+                 (1,'abate','lessen',1,1700000120), (2,'wane','decrease',1,1800000000);
+             INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES
+                 (2,1800000000,1,0), (1,1699990000,1,0);",
+        )
+        .unwrap();
+
+        let report = run(&conn, &clock).unwrap();
+        assert_eq!(report.len(), 1);
+        assert!(report[0].starts_with("1 words and 1 reviews"), "{report:?}");
+        let last_seen: Vec<i64> = conn
+            .prepare("SELECT last_seen FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        // Two minutes ahead is within tolerance and stays.
+        assert_eq!(last_seen, vec![1_700_000_120, 1_700_000_000]);
+        assert_eq!(run(&conn, &clock).unwrap(), vec!["No problems found"]);
+    }
+
+    #[test]
+    fn test_reports_definition_lint() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES
+                 ('abate','lessen',1), ('wane','<b>decrease</b>',1)",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(&conn, &SystemClock).unwrap(),
+            vec!["lint: wane (#2): definition contains HTML tags"]
+        );
+    }
+
+    #[test]
+    fn test_repair_adds_missing_column() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        init_db(path)
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO words(word,definition,group_id,marked) VALUES('abate','lessen',1,1);
+                 ALTER TABLE words DROP COLUMN marked;",
+            )
+            .unwrap();
+        assert!(
+            damage_help(path, &init_db(path).unwrap_err().downcast().unwrap())
+                .contains("doctor --fix")
+        );
+
+        let report = repair(path, &SystemClock).unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "Found: missing columns words.marked",
+                "Added words.marked back with its default",
+                "No problems found",
+            ]
+        );
+        let marked: i64 = init_db(path)
+            .unwrap()
+            .query_row("SELECT marked FROM words", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(marked, 0);
+    }
+
+    #[test]
+    fn test_repair_gives_up_on_garbage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        std::fs::write(&path, "not a database ".repeat(100)).unwrap();
+
+        let report = repair(path.to_str().unwrap(), &SystemClock).unwrap();
+        assert!(report[0].starts_with("Found: corrupted"), "{report:?}");
+        assert!(report[1].ends_with("restore a backup"));
+    }
+
+    #[test]
+    fn test_repair_caps_rows_failing_the_quick_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        init_db(path)
+            .unwrap()
+            .execute_batch(
+                "PRAGMA ignore_check_constraints=ON;
+                 INSERT INTO words(word,definition,group_id,times_seen,success_count)
+                     VALUES('abate','lessen',1,1,5);",
+            )
+            .unwrap();
+
+        let report = repair(path, &SystemClock).unwrap();
+        assert_eq!(
+            report.last().unwrap(),
+            "1 words had success counts outside 0..times seen; capped"
+        );
+        init_db(path).unwrap();
+    }
+}
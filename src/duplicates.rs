@@ -0,0 +1,269 @@
+use crate::core::progress;
+use crate::db::models::Word;
+use crate::db::store::{WordFilter, WordStore};
+use crate::db::with_tx;
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 1000;
+
+/// What to do with one word of a duplicate set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    Keep,
+    Edit(String),
+    Delete,
+}
+
+/// `def` as compared for duplicates: lowercased, with whitespace runs
+/// collapsed to one space and the ends trimmed. Wording and punctuation
+/// still count.
+pub fn normalize(def: &str) -> String {
+    def.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Sets of different words whose definitions are the same under
+/// `normalize`, each in id order. Empty definitions are left to `lint`.
+pub fn find(conn: &Connection) -> Result<Vec<Vec<Word>>> {
+    let mut words = HashMap::new();
+    with_tx(conn, |tx| {
+        tx.execute_batch(
+            "CREATE TEMP TABLE IF NOT EXISTS definition_keys(
+                 word_id INTEGER PRIMARY KEY,
+                 word TEXT NOT NULL,
+                 key TEXT NOT NULL
+             );
+             DELETE FROM definition_keys;",
+        )?;
+        let mut insert = tx.prepare("INSERT INTO definition_keys VALUES(?1, ?2, ?3)")?;
+        WordStore::new(tx).for_each(WordFilter::All, PAGE_SIZE, |word| {
+            let key = normalize(&word.definition);
+            if !key.is_empty() {
+                insert.execute(params![word.id, word.word.to_lowercase(), key])?;
+                words.insert(word.id, word);
+            }
+            Ok(())
+        })?;
+        Ok(())
+    })?;
+
+    // Grouping happens in SQLite; the same word listed twice is `dedup`'s
+    // job, so a set needs two different words.
+    let rows: Vec<(String, i32)> = conn
+        .prepare(
+            "SELECT k.key, k.word_id FROM definition_keys k
+             JOIN (SELECT key, MIN(word_id) AS first FROM definition_keys
+                   GROUP BY key HAVING COUNT(DISTINCT word) > 1) d ON d.key=k.key
+             ORDER BY d.first, k.word_id",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    conn.execute("DELETE FROM definition_keys", [])?;
+
+    let mut sets: Vec<Vec<Word>> = Vec::new();
+    let mut last_key = None;
+    for (key, id) in rows {
+        let Some(word) = words.remove(&id) else {
+            continue;
+        };
+        if last_key.as_ref() == Some(&key) {
+            sets.last_mut().unwrap().push(word);
+        } else {
+            sets.push(vec![word]);
+            last_key = Some(key);
+        }
+    }
+    Ok(sets)
+}
+
+/// Words that share their definition with a different word, for the stats
+/// screen's warning.
+pub fn count(conn: &Connection) -> Result<usize> {
+    Ok(find(conn)?.iter().map(Vec::len).sum())
+}
+
+fn label(word: &Word) -> String {
+    format!("{} (#{})", word.word, word.id)
+}
+
+/// Lists each pair of words sharing a definition. Returns one line per
+/// pair.
+pub fn check(conn: &Connection) -> Result<Vec<String>> {
+    let mut report = Vec::new();
+    for set in find(conn)? {
+        for (i, a) in set.iter().enumerate() {
+            for b in &set[i + 1..] {
+                report.push(format!(
+                    "{} and {}: same definition \"{}\"",
+                    label(a),
+                    label(b),
+                    normalize(&a.definition)
+                ));
+            }
+        }
+    }
+    if report.is_empty() {
+        report.push("No duplicate definitions".into());
+    }
+    Ok(report)
+}
+
+/// Walks each duplicate set, asking `choose` what to do with a word and
+/// the others still sharing its definition. Stops asking about a set once
+/// no two of its words share the definition. Returns one line per word
+/// looked at.
+pub fn resolve(
+    conn: &Connection,
+    mut choose: impl FnMut(&Word, &[Word]) -> Result<Resolution>,
+) -> Result<Vec<String>> {
+    let mut report = Vec::new();
+    for set in find(conn)? {
+        let key = normalize(&set[0].definition);
+        let mut sharing = set.clone();
+        for word in &set {
+            if sharing.len() < 2 {
+                break;
+            }
+            let others: Vec<Word> = sharing
+                .iter()
+                .filter(|w| w.id != word.id)
+                .cloned()
+                .collect();
+            match choose(word, &others)? {
+                Resolution::Keep => report.push(format!("{}: kept", label(word))),
+                Resolution::Edit(definition) => {
+                    let definition = definition.trim();
+                    if definition.is_empty() {
+                        report.push(format!("{}: kept", label(word)));
+                        continue;
+                    }
+                    conn.execute(
+                        "UPDATE words SET definition=?1 WHERE id=?2",
+                        params![definition, word.id],
+                    )?;
+                    if normalize(definition) != key {
+                        sharing = others;
+                    }
+                    report.push(format!("{}: definition changed", label(word)));
+                }
+                Resolution::Delete => {
+                    progress::delete_word(conn, word.id)?;
+                    sharing = others;
+                    report.push(format!("{}: deleted", label(word)));
+                }
+            }
+        }
+    }
+    if report.is_empty() {
+        report.push("No duplicate definitions".into());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+
+    fn seeded() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'abate','to lessen',1), (2,'wane','decrease',1),
+                 (3,'ebb','  To   LESSEN',2), (4,'dwindle','Decrease',2),
+                 (5,'laud','praise',3), (6,'extol','to lessen',3),
+                 (7,'Laud','praise',3), (8,'subside','to lessen.',3),
+                 (9,'belie',' ',1), (10,'feign',' ',1)",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn ids(sets: &[Vec<Word>]) -> Vec<Vec<i32>> {
+        sets.iter()
+            .map(|set| set.iter().map(|w| w.id).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_ignores_case_and_whitespace_only() {
+        assert_eq!(normalize("  To\tLESSEN\n"), "to lessen");
+        assert_ne!(normalize("to lessen"), normalize("to lessen."));
+        assert_ne!(normalize("to lessen"), normalize("tolessen"));
+    }
+
+    #[test]
+    fn test_find_groups_different_words() {
+        let conn = seeded();
+        // laud and Laud are one word twice, and blanks are lint's.
+        assert_eq!(ids(&find(&conn).unwrap()), vec![vec![1, 3, 6], vec![2, 4]]);
+        assert_eq!(count(&conn).unwrap(), 5);
+        assert_eq!(
+            check(&conn).unwrap(),
+            vec![
+                "abate (#1) and ebb (#3): same definition \"to lessen\"",
+                "abate (#1) and extol (#6): same definition \"to lessen\"",
+                "ebb (#3) and extol (#6): same definition \"to lessen\"",
+                "wane (#2) and dwindle (#4): same definition \"decrease\"",
+            ]
+        );
+        // The temp table is reused.
+        assert_eq!(find(&conn).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_persists_choices() {
+        let conn = seeded();
+        let mut asked = Vec::new();
+        let report = resolve(&conn, |word, others| {
+            asked.push((word.id, others.iter().map(|w| w.id).collect::<Vec<_>>()));
+            Ok(match word.id {
+                1 => Resolution::Keep,
+                3 => Resolution::Delete,
+                6 => Resolution::Edit("to praise highly".into()),
+                2 => Resolution::Edit("  DECREASE ".into()),
+                _ => Resolution::Keep,
+            })
+        })
+        .unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "abate (#1): kept",
+                "ebb (#3): deleted",
+                "extol (#6): definition changed",
+                "wane (#2): definition changed",
+                "dwindle (#4): kept",
+            ]
+        );
+        // Rewording only the case keeps the pair, so dwindle is asked too.
+        assert_eq!(
+            asked,
+            vec![
+                (1, vec![3, 6]),
+                (3, vec![1, 6]),
+                (6, vec![1]),
+                (2, vec![4]),
+                (4, vec![2]),
+            ]
+        );
+
+        let exists: i64 = conn
+            .query_row("SELECT COUNT(*) FROM words WHERE id=3", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(exists, 0);
+        let definition: String = conn
+            .query_row("SELECT definition FROM words WHERE id=6", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(definition, "to praise highly");
+        assert_eq!(ids(&find(&conn).unwrap()), vec![vec![2, 4]]);
+    }
+}
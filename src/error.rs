@@ -0,0 +1,29 @@
+use thiserror::Error;
+
+/// Errors from the core and db layers, kept apart so the TUI can choose a
+/// message and recovery per case. The CLI just converts them to anyhow.
+#[derive(Debug, Error)]
+pub enum VocabError {
+    /// A session matched no words; `kind` names the list, e.g. "marked list".
+    #[error("The {kind} has no words yet")]
+    EmptySelection { kind: &'static str },
+    #[error(transparent)]
+    Db(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A seed file line that doesn't fit the grammar; `line` is 1-based.
+    #[error("Line {line}: {msg}")]
+    Parse { line: usize, msg: String },
+    /// Saved position lies past the end of its word list.
+    #[error("Saved progress points at word {} of a {len}-word list", index + 1)]
+    ProgressOutOfRange { index: usize, len: usize },
+    /// Word stats that would break `success_count <= times_seen`.
+    #[error("Not saving {word}: {success} successes out of {seen} attempts")]
+    InvalidStats {
+        word: String,
+        success: u32,
+        seen: u32,
+    },
+}
+
+pub type Result<T, E = VocabError> = std::result::Result<T, E>;
@@ -0,0 +1,94 @@
+use crate::db::store::{WordFilter, WordStore};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate};
+use rusqlite::Connection;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Words read per query while exporting.
+const PAGE_SIZE: usize = 1000;
+
+const HEADER: &str = "word,group,times_seen,success_count,accuracy,last_seen,marked";
+
+pub fn file_name(date: NaiveDate) -> String {
+    format!("vocab-stats-{}.csv", date.format("%Y%m%d"))
+}
+
+/// Writes per-word stats to `vocab-stats-YYYYMMDD.csv` in `dir` and returns
+/// the path. Shared by `vocabulator export` and the stats screen.
+pub fn export_stats(conn: &Connection, dir: &Path, date: NaiveDate) -> Result<PathBuf> {
+    let path = dir.join(file_name(date));
+    let mut out = BufWriter::new(File::create(&path)?);
+    write_stats_csv(conn, &mut out)?;
+    out.flush()?;
+    Ok(path)
+}
+
+pub fn write_stats_csv(conn: &Connection, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "{HEADER}")?;
+    WordStore::new(conn).for_each(WordFilter::All, PAGE_SIZE, |word| {
+        let accuracy = word
+            .accuracy()
+            .map(|a| format!("{a:.2}"))
+            .unwrap_or_default();
+        let last_seen = word
+            .last_seen
+            .and_then(|ts| DateTime::from_timestamp(ts as i64, 0))
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            .unwrap_or_default();
+
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            field(&word.word),
+            word.group_id,
+            word.times_seen,
+            word.success_count,
+            accuracy,
+            last_seen,
+            word.marked
+        )?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Quotes a field when it contains a separator, quote or line break.
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_stats_to_dir() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id,marked,last_seen,times_seen,success_count)
+             VALUES('abate','lessen',1,1,1700000000,4,3),
+                   ('laissez, faire','let be',2,0,NULL,0,0);",
+        )
+        .unwrap();
+        let dir = TempDir::new().unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+
+        let path = export_stats(&conn, dir.path(), date).unwrap();
+
+        assert_eq!(path, dir.path().join("vocab-stats-20240309.csv"));
+        let content = fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], HEADER);
+        assert_eq!(lines[1], "abate,1,4,3,0.75,2023-11-14T22:13:20Z,true");
+        assert_eq!(lines[2], "\"laissez, faire\",2,0,0,,,false");
+    }
+}
@@ -0,0 +1,136 @@
+use crate::db::store::{WordFilter, WordStore};
+use crate::db::with_tx;
+use anyhow::{Result, bail};
+use rusqlite::{Connection, params};
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 1000;
+
+/// Size of the "top" the import report counts words in.
+pub const TOP_WORDS: i32 = 1000;
+
+/// Ranks from a frequency list, keyed by lowercased word. A line is either
+/// a bare word, ranked by its line number, or `word,rank`; blank lines are
+/// skipped and a first line without a numeric rank is taken as a header.
+/// A word listed twice keeps its better rank.
+pub fn parse(content: &str) -> Result<HashMap<String, i64>> {
+    let mut ranks = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (word, rank) = match line.split_once(',') {
+            Some((word, rank)) => match rank.trim().parse::<i64>() {
+                Ok(rank) if rank > 0 => (word.trim(), rank),
+                _ if i == 0 => continue,
+                _ => bail!("Line {}: {:?} is not a rank", i + 1, rank.trim()),
+            },
+            None => (line, i as i64 + 1),
+        };
+        if word.is_empty() {
+            bail!("Line {}: no word before the rank", i + 1);
+        }
+        ranks
+            .entry(word.to_lowercase())
+            .and_modify(|r: &mut i64| *r = (*r).min(rank))
+            .or_insert(rank);
+    }
+    Ok(ranks)
+}
+
+/// Ranks the database's words from a frequency list, matching them
+/// case-insensitively and replacing any earlier ranking. Returns the report
+/// lines.
+pub fn import(conn: &Connection, content: &str) -> Result<Vec<String>> {
+    let ranks = parse(content)?;
+    if ranks.is_empty() {
+        bail!("The list has no words");
+    }
+
+    let mut total = 0;
+    let mut matched = Vec::new();
+    WordStore::new(conn).for_each(WordFilter::All, PAGE_SIZE, |word| {
+        total += 1;
+        if let Some(rank) = ranks.get(&word.word.to_lowercase()) {
+            matched.push((word.id, *rank));
+        }
+        Ok(())
+    })?;
+
+    with_tx(conn, |tx| {
+        tx.execute(
+            "UPDATE words SET frequency_rank=NULL WHERE frequency_rank IS NOT NULL",
+            [],
+        )?;
+        for (id, rank) in &matched {
+            tx.execute(
+                "UPDATE words SET frequency_rank=?1 WHERE id=?2",
+                params![rank, id],
+            )?;
+        }
+        Ok(())
+    })?;
+
+    let top = WordStore::new(conn).count(WordFilter::TopFrequency(TOP_WORDS))?;
+    Ok(vec![
+        format!("Read {} ranked words", ranks.len()),
+        format!("Matched {} of {total} words in the database", matched.len()),
+        format!("In the top {TOP_WORDS}: {top}"),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+
+    #[test]
+    fn test_parse_plain_and_csv_lists() {
+        let plain = parse("the\nOf\n\nand\n").unwrap();
+        assert_eq!(plain["the"], 1);
+        assert_eq!(plain["of"], 2);
+        assert_eq!(plain["and"], 4);
+
+        let csv = parse("word,rank\nabate, 5400\nAbate,120\nwane,7000").unwrap();
+        assert_eq!(csv.len(), 2);
+        assert_eq!(csv["abate"], 120);
+        assert_eq!(csv["wane"], 7000);
+
+        let err = parse("abate,1\nwane,often").unwrap_err();
+        assert!(err.to_string().starts_with("Line 2"), "{err}");
+        assert!(parse("abate,1\n,2").is_err());
+    }
+
+    #[test]
+    fn test_import_matches_case_insensitively() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,frequency_rank) VALUES
+                 (1,'Abate','lessen',1,NULL), (2,'wane','decrease',1,NULL),
+                 (3,'Über','over',1,NULL), (4,'laud','praise',1,9)",
+        )
+        .unwrap();
+
+        let report = import(&conn, "wane,3000\nabate,800\nüber,40\nthe,1").unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "Read 4 ranked words",
+                "Matched 3 of 4 words in the database",
+                "In the top 1000: 2",
+            ]
+        );
+        let ranks: Vec<Option<i64>> = conn
+            .prepare("SELECT frequency_rank FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        // laud isn't in the new list, so its old rank goes.
+        assert_eq!(ranks, vec![Some(800), Some(3000), Some(40), None]);
+
+        assert!(import(&conn, "\n\n").is_err());
+    }
+}
@@ -0,0 +1,491 @@
+use crate::core::progress;
+use crate::db::{queries, with_tx};
+use crate::ui::app::Screen;
+use anyhow::{Result, bail};
+use rusqlite::{Connection, OptionalExtension, params};
+
+/// Names group `id`, replacing any name it had. Returns one line per
+/// change.
+pub fn rename(conn: &Connection, id: i32, name: &str) -> Result<Vec<String>> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("A group name can't be empty");
+    }
+    with_tx(conn, |tx| {
+        let words = word_count(tx, id)?;
+        if words == 0 {
+            bail!("Group {id} has no words");
+        }
+        let old = queries::fetch_group_name(tx, id)?;
+        tx.execute(
+            "INSERT INTO group_names(group_id, name) VALUES(?1, ?2)
+             ON CONFLICT(group_id) DO UPDATE SET name=excluded.name",
+            params![id, name],
+        )?;
+        Ok(vec![match old {
+            Some(old) => format!("Renamed group {id} ({words} words) from \"{old}\" to \"{name}\""),
+            None => format!("Named group {id} ({words} words) \"{name}\""),
+        }])
+    })
+}
+
+/// Archives group `id`, or puts it back into the rotation when `archived`
+/// is false. Saved progress in a group being archived moves on to the next
+/// group that isn't. Returns one line per change.
+pub fn set_archived(conn: &Connection, id: i32, archived: bool) -> Result<Vec<String>> {
+    with_tx(conn, |tx| {
+        let words = word_count(tx, id)?;
+        if words == 0 {
+            bail!("Group {id} has no words");
+        }
+        if queries::is_group_archived(tx, id)? == archived {
+            let state = if archived { "already" } else { "not" };
+            bail!("Group {id} is {state} archived");
+        }
+        if !archived {
+            tx.execute("DELETE FROM archived_groups WHERE group_id=?1", [id])?;
+            return Ok(vec![format!("Restored group {id} ({words} words)")]);
+        }
+
+        tx.execute("INSERT INTO archived_groups(group_id) VALUES(?1)", [id])?;
+        let mut report = vec![format!("Archived group {id} ({words} words)")];
+        if saved_group(tx)? == Some(id)
+            && let Some(next) = progress::next_group(tx, id)?
+        {
+            queries::save_progress(tx, (Screen::Practice, next, 0))?;
+            report.push(format!("Moved saved progress to group {next}"));
+        }
+        Ok(report)
+    })
+}
+
+/// Moves every word in group `old` to group `new`, along with its name,
+/// archived state and any saved progress. A `new` that already has words
+/// is refused unless `merge`, which combines the two; the target keeps its
+/// own name then. Returns one line per change.
+pub fn renumber(conn: &Connection, old: i32, new: i32, merge: bool) -> Result<Vec<String>> {
+    if new < 1 {
+        bail!("Group numbers start at 1");
+    }
+    if old == new {
+        bail!("Group {old} is already number {new}");
+    }
+    with_tx(conn, |tx| {
+        if word_count(tx, old)? == 0 {
+            bail!("Group {old} has no words");
+        }
+        let existing = word_count(tx, new)?;
+        if existing > 0 && !merge {
+            bail!("Group {new} already has {existing} words; pass --merge to combine the groups");
+        }
+
+        let mut report = Vec::new();
+        let moved = tx.execute(
+            "UPDATE words SET group_id=?1 WHERE group_id=?2",
+            params![new, old],
+        )?;
+        report.push(format!("Moved {moved} words from group {old} to {new}"));
+
+        // A name left on a group with no words would otherwise label the
+        // moved ones.
+        if existing == 0 {
+            tx.execute("DELETE FROM group_names WHERE group_id=?1", [new])?;
+        }
+        match (
+            queries::fetch_group_name(tx, old)?,
+            queries::fetch_group_name(tx, new)?,
+        ) {
+            (Some(name), None) => {
+                tx.execute(
+                    "UPDATE group_names SET group_id=?1 WHERE group_id=?2",
+                    params![new, old],
+                )?;
+                report.push(format!("Moved the name \"{name}\" to group {new}"));
+            }
+            (Some(name), Some(kept)) => {
+                tx.execute("DELETE FROM group_names WHERE group_id=?1", [old])?;
+                report.push(format!(
+                    "Group {new} keeps its name \"{kept}\"; dropped \"{name}\""
+                ));
+            }
+            (None, _) => {}
+        }
+
+        // Merged words take on the target's state.
+        if existing == 0 {
+            tx.execute("DELETE FROM archived_groups WHERE group_id=?1", [new])?;
+            tx.execute(
+                "UPDATE archived_groups SET group_id=?1 WHERE group_id=?2",
+                params![new, old],
+            )?;
+        } else {
+            tx.execute("DELETE FROM archived_groups WHERE group_id=?1", [old])?;
+        }
+
+        let followed = tx.execute(
+            "UPDATE app_state SET value=?1 WHERE key='group_id' AND value=?2",
+            params![new, old],
+        )?;
+        if saved_group(tx)? == Some(new) {
+            // The saved word keeps its place; merged words shift the index.
+            let progress = queries::fetch_progress(tx)?;
+            queries::save_progress(tx, progress)?;
+            if followed > 0 {
+                report.push(format!(
+                    "Moved saved progress to group {new}, word {}",
+                    progress.2 + 1
+                ));
+            }
+        }
+        Ok(report)
+    })
+}
+
+/// Moves the words named in `words`, matched case-insensitively, to group
+/// `to`. Every copy of a name moves. Nothing moves if any name isn't found.
+/// Saved progress on a moved word goes to the next word that stayed in its
+/// group, or on to the next group if none did. Returns one line per change.
+pub fn move_words(conn: &Connection, words: &[&str], to: i32) -> Result<Vec<String>> {
+    if to < 1 {
+        bail!("Group numbers start at 1");
+    }
+    with_tx(conn, |tx| {
+        let mut ids = Vec::new();
+        let mut missing = Vec::new();
+        let mut find = tx.prepare("SELECT id FROM words WHERE word=?1 COLLATE NOCASE")?;
+        for &word in words {
+            let found = find
+                .query_map([word.trim()], |row| row.get::<_, i32>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            if found.is_empty() {
+                missing.push(word.trim());
+            }
+            ids.extend(found);
+        }
+        if !missing.is_empty() {
+            bail!("Not found: {}", missing.join(", "));
+        }
+        ids.sort_unstable();
+        ids.dedup();
+
+        // If the saved word moves, progress resumes at the first word after
+        // it that stays, or at the start when none does.
+        let saved = match saved_group(tx)? {
+            Some(_) => Some(queries::fetch_progress(tx)?),
+            None => None,
+        };
+        let mut resume = None;
+        if let Some((_, group, index)) = saved {
+            let order: Vec<i32> = queries::fetch_words_by_group(tx, group)?
+                .iter()
+                .map(|w| w.id)
+                .collect();
+            if order.get(index).is_some_and(|id| ids.contains(id)) {
+                resume = Some(order[index..].iter().find(|id| !ids.contains(id)).copied());
+            }
+        }
+
+        let mut moved = 0;
+        let mut update = tx.prepare("UPDATE words SET group_id=?1 WHERE id=?2 AND group_id<>?1")?;
+        for id in &ids {
+            moved += update.execute(params![to, id])?;
+        }
+        let mut report = vec![format!("Moved {moved} words to group {to}")];
+        if moved < ids.len() {
+            report.push(format!("{} already in group {to}", ids.len() - moved));
+        }
+
+        match (saved, resume) {
+            (Some((screen, group, _)), Some(resume)) => {
+                let index = match resume {
+                    Some(id) => queries::fetch_words_by_group(tx, group)?
+                        .iter()
+                        .position(|w| w.id == id)
+                        .unwrap_or(0),
+                    None => 0,
+                };
+                if word_count(tx, group)? > 0 {
+                    queries::save_progress(tx, (screen, group, index))?;
+                    report.push(format!(
+                        "Moved saved progress to group {group}, word {}",
+                        index + 1
+                    ));
+                } else if let Some(next) = progress::next_group(tx, group)? {
+                    queries::save_progress(tx, (Screen::Practice, next, 0))?;
+                    report.push(format!("Moved saved progress to group {next}"));
+                }
+            }
+            // The saved word stays, but moved words may shift its index.
+            (Some(_), None) => {
+                let progress = queries::fetch_progress(tx)?;
+                queries::save_progress(tx, progress)?;
+            }
+            (None, _) => {}
+        }
+        Ok(report)
+    })
+}
+
+fn saved_group(conn: &Connection) -> Result<Option<i32>> {
+    Ok(conn
+        .query_row(
+            "SELECT value FROM app_state WHERE key='group_id'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn word_count(conn: &Connection, group_id: i32) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM words WHERE group_id=?1",
+        [group_id],
+        |row| row.get(0),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+
+    fn seeded() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'abate','lessen',1), (2,'wane','decrease',1),
+                 (3,'belie','contradict',2), (4,'wan','pale',2), (5,'laud','praise',3);
+             INSERT INTO group_names(group_id,name) VALUES(1,'Verbs'), (3,'Praise')",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn groups(conn: &Connection) -> Vec<(i32, Option<String>, i64)> {
+        queries::fetch_group_stats(conn, true)
+            .unwrap()
+            .into_iter()
+            .map(|g| (g.group_id, g.name, g.words))
+            .collect()
+    }
+
+    #[test]
+    fn test_rename() {
+        let conn = seeded();
+        assert_eq!(
+            rename(&conn, 1, " Actions ").unwrap(),
+            vec!["Renamed group 1 (2 words) from \"Verbs\" to \"Actions\""]
+        );
+        assert_eq!(
+            rename(&conn, 2, "Adjectives").unwrap(),
+            vec!["Named group 2 (2 words) \"Adjectives\""]
+        );
+        assert_eq!(groups(&conn)[0].1.as_deref(), Some("Actions"));
+        assert_eq!(groups(&conn)[1].1.as_deref(), Some("Adjectives"));
+
+        assert!(rename(&conn, 9, "Nothing").is_err());
+        assert!(rename(&conn, 1, "  ").is_err());
+    }
+
+    #[test]
+    fn test_renumber_moves_words_name_and_progress() {
+        let conn = seeded();
+        queries::save_progress(&conn, (Screen::Test, 1, 1)).unwrap();
+
+        let report = renumber(&conn, 1, 7, false).unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "Moved 2 words from group 1 to 7",
+                "Moved the name \"Verbs\" to group 7",
+                "Moved saved progress to group 7, word 2",
+            ]
+        );
+        assert_eq!(
+            groups(&conn),
+            vec![
+                (2, None, 2),
+                (3, Some("Praise".into()), 1),
+                (7, Some("Verbs".into()), 2)
+            ]
+        );
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Test, 7, 1)
+        );
+    }
+
+    #[test]
+    fn test_renumber_onto_existing_group_is_refused() {
+        let conn = seeded();
+        queries::save_progress(&conn, (Screen::Practice, 1, 1)).unwrap();
+        let before = groups(&conn);
+
+        let err = renumber(&conn, 1, 2, false).unwrap_err();
+        assert!(err.to_string().contains("--merge"), "{err}");
+        assert_eq!(groups(&conn), before);
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Practice, 1, 1)
+        );
+
+        assert!(renumber(&conn, 4, 5, false).is_err());
+        assert!(renumber(&conn, 1, 1, true).is_err());
+        assert!(renumber(&conn, 1, 0, false).is_err());
+    }
+
+    #[test]
+    fn test_archiving_moves_progress_on() {
+        let conn = seeded();
+        queries::save_progress(&conn, (Screen::Test, 2, 1)).unwrap();
+        assert_eq!(
+            set_archived(&conn, 2, true).unwrap(),
+            vec![
+                "Archived group 2 (2 words)",
+                "Moved saved progress to group 3"
+            ]
+        );
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Practice, 3, 0)
+        );
+
+        // From the last group it wraps around to the first one left.
+        set_archived(&conn, 1, true).unwrap();
+        assert_eq!(
+            set_archived(&conn, 3, true).unwrap(),
+            vec!["Archived group 3 (1 words)"]
+        );
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Practice, 3, 0)
+        );
+        set_archived(&conn, 1, false).unwrap();
+        set_archived(&conn, 3, false).unwrap();
+        queries::save_progress(&conn, (Screen::Practice, 3, 0)).unwrap();
+        set_archived(&conn, 3, true).unwrap();
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Practice, 1, 0)
+        );
+
+        assert!(set_archived(&conn, 3, true).is_err());
+        assert!(set_archived(&conn, 2, true).is_err());
+        assert!(set_archived(&conn, 9, true).is_err());
+        assert_eq!(
+            set_archived(&conn, 2, false).unwrap(),
+            vec!["Restored group 2 (2 words)"]
+        );
+    }
+
+    #[test]
+    fn test_move_words_in_one_transaction() {
+        let conn = seeded();
+        assert_eq!(
+            move_words(&conn, &["Wane", " laud "], 2).unwrap(),
+            vec!["Moved 2 words to group 2"]
+        );
+        assert_eq!(
+            groups(&conn),
+            vec![(1, Some("Verbs".into()), 1), (2, None, 4)]
+        );
+
+        // One unknown name and nothing moves.
+        let err = move_words(&conn, &["abate", "nope", "nah"], 3).unwrap_err();
+        assert_eq!(err.to_string(), "Not found: nope, nah");
+        assert_eq!(groups(&conn)[0], (1, Some("Verbs".into()), 1));
+
+        assert_eq!(
+            move_words(&conn, &["belie", "abate"], 2).unwrap(),
+            vec!["Moved 1 words to group 2", "1 already in group 2"]
+        );
+        assert!(move_words(&conn, &["wan"], 0).is_err());
+    }
+
+    #[test]
+    fn test_move_words_repairs_progress() {
+        let conn = seeded();
+        // On abate: it leaves, so progress resumes at wane.
+        queries::save_progress(&conn, (Screen::Test, 1, 0)).unwrap();
+        assert_eq!(
+            move_words(&conn, &["abate"], 2).unwrap(),
+            vec![
+                "Moved 1 words to group 2",
+                "Moved saved progress to group 1, word 1"
+            ]
+        );
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Test, 1, 0)
+        );
+
+        // On wan, which stays while abate before it leaves.
+        queries::save_progress(&conn, (Screen::Practice, 2, 2)).unwrap();
+        move_words(&conn, &["abate"], 3).unwrap();
+        let index: i64 = conn
+            .query_row("SELECT value FROM app_state WHERE key='index'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(index, 1);
+
+        // Emptying the saved group moves progress on.
+        queries::save_progress(&conn, (Screen::Test, 1, 0)).unwrap();
+        assert_eq!(
+            move_words(&conn, &["wane"], 3).unwrap(),
+            vec![
+                "Moved 1 words to group 3",
+                "Moved saved progress to group 2"
+            ]
+        );
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Practice, 2, 0)
+        );
+    }
+
+    #[test]
+    fn test_renumber_keeps_archived_state() {
+        let conn = seeded();
+        set_archived(&conn, 1, true).unwrap();
+        renumber(&conn, 1, 7, false).unwrap();
+        assert!(queries::is_group_archived(&conn, 7).unwrap());
+        assert!(!queries::is_group_archived(&conn, 1).unwrap());
+
+        // Merged words take on the target's state.
+        renumber(&conn, 7, 2, true).unwrap();
+        assert!(!queries::is_group_archived(&conn, 2).unwrap());
+        assert!(!queries::is_group_archived(&conn, 7).unwrap());
+    }
+
+    #[test]
+    fn test_renumber_merge() {
+        let conn = seeded();
+        // On "laud", the only word of group 3.
+        queries::save_progress(&conn, (Screen::Practice, 3, 0)).unwrap();
+
+        let report = renumber(&conn, 1, 3, true).unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "Moved 2 words from group 1 to 3",
+                "Group 3 keeps its name \"Praise\"; dropped \"Verbs\"",
+            ]
+        );
+        assert_eq!(
+            groups(&conn),
+            vec![(2, None, 2), (3, Some("Praise".into()), 3)]
+        );
+        // abate and wane sort before laud now.
+        assert_eq!(
+            queries::fetch_progress(&conn).unwrap(),
+            (Screen::Practice, 3, 2)
+        );
+        let index: i64 = conn
+            .query_row("SELECT value FROM app_state WHERE key='index'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(index, 2);
+    }
+}
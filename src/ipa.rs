@@ -0,0 +1,128 @@
+use crate::db::store::{WordFilter, WordStore};
+use crate::db::with_tx;
+use anyhow::{Result, bail};
+use rusqlite::{Connection, params};
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 1000;
+
+/// Transcriptions from `word,ipa` lines, keyed by lowercased word, with
+/// any slashes around them dropped. Blank lines are skipped and a first
+/// line whose second field is `ipa` is taken as a header. A word listed
+/// twice keeps its first transcription.
+pub fn parse(content: &str) -> Result<HashMap<String, String>> {
+    let mut transcriptions = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((word, ipa)) = line.split_once(',') else {
+            bail!("Line {}: expected word,ipa", i + 1);
+        };
+        let (word, ipa) = (word.trim(), ipa.trim());
+        if i == 0 && ipa.eq_ignore_ascii_case("ipa") {
+            continue;
+        }
+        let ipa = ipa.strip_prefix('/').unwrap_or(ipa);
+        let ipa = ipa.strip_suffix('/').unwrap_or(ipa).trim();
+        if word.is_empty() {
+            bail!("Line {}: no word before the transcription", i + 1);
+        }
+        if ipa.is_empty() {
+            bail!("Line {}: no transcription for {word:?}", i + 1);
+        }
+        transcriptions
+            .entry(word.to_lowercase())
+            .or_insert_with(|| ipa.to_string());
+    }
+    Ok(transcriptions)
+}
+
+/// Sets the IPA of the database's words listed in `content`, matching them
+/// case-insensitively. Words the file doesn't list keep theirs. Returns the
+/// report lines.
+pub fn import(conn: &Connection, content: &str) -> Result<Vec<String>> {
+    let transcriptions = parse(content)?;
+    if transcriptions.is_empty() {
+        bail!("The file has no transcriptions");
+    }
+
+    let mut total = 0;
+    let mut matched = Vec::new();
+    WordStore::new(conn).for_each(WordFilter::All, PAGE_SIZE, |word| {
+        total += 1;
+        if let Some(ipa) = transcriptions.get(&word.word.to_lowercase()) {
+            matched.push((word.id, ipa.as_str()));
+        }
+        Ok(())
+    })?;
+
+    with_tx(conn, |tx| {
+        for (id, ipa) in &matched {
+            tx.execute("UPDATE words SET ipa=?1 WHERE id=?2", params![ipa, id])?;
+        }
+        Ok(())
+    })?;
+
+    Ok(vec![
+        format!("Read {} transcriptions", transcriptions.len()),
+        format!("Matched {} of {total} words in the database", matched.len()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+
+    #[test]
+    fn test_parse_csv_with_or_without_slashes() {
+        let ipa = parse("word,ipa\nlaconic,/ləˈkɒnɪk/\n\nWan, wɒn \nwan,wæn").unwrap();
+        assert_eq!(ipa.len(), 2);
+        assert_eq!(ipa["laconic"], "ləˈkɒnɪk");
+        assert_eq!(ipa["wan"], "wɒn");
+
+        let err = parse("laconic,ləˈkɒnɪk\nwan").unwrap_err();
+        assert!(err.to_string().starts_with("Line 2"), "{err}");
+        assert!(parse("wan,//").is_err());
+        assert!(parse(",wɒn").is_err());
+    }
+
+    #[test]
+    fn test_import_matches_case_insensitively() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,ipa) VALUES
+                 (1,'Laconic','terse',1,NULL), (2,'wan','pale',1,'wɒn'),
+                 (3,'laud','praise',1,NULL)",
+        )
+        .unwrap();
+
+        let report = import(&conn, "laconic,ləˈkɒnɪk\nlaud,lɔːd\nabate,əˈbeɪt").unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "Read 3 transcriptions",
+                "Matched 2 of 3 words in the database"
+            ]
+        );
+        let ipa: Vec<Option<String>> = conn
+            .prepare("SELECT ipa FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            ipa,
+            vec![
+                Some("ləˈkɒnɪk".into()),
+                Some("wɒn".into()),
+                Some("lɔːd".into())
+            ]
+        );
+
+        assert!(import(&conn, "\n\n").is_err());
+    }
+}
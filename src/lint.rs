@@ -0,0 +1,379 @@
+use crate::db::models::Word;
+use crate::db::store::{WordFilter, WordStore};
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use std::fmt;
+
+const PAGE_SIZE: usize = 1000;
+
+/// Definitions shorter than this many characters are flagged.
+pub const MIN_DEFINITION_CHARS: usize = 3;
+/// Definitions longer than this many characters are flagged; the bundled
+/// list stays well under it.
+pub const MAX_DEFINITION_CHARS: usize = 400;
+
+/// Tags that separate words, so stripping them leaves a space.
+const BREAKING_TAGS: [&str; 7] = ["br", "p", "div", "li", "tr", "td", "hr"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintWarning {
+    Empty,
+    TooShort,
+    /// Nothing but the word itself, e.g. "abate" for abate.
+    OnlyHeadword,
+    TooLong {
+        chars: usize,
+    },
+    UnbalancedParens,
+    HtmlTags,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintWarning::Empty => write!(f, "definition is empty"),
+            LintWarning::TooShort => write!(
+                f,
+                "definition is under {MIN_DEFINITION_CHARS} characters long"
+            ),
+            LintWarning::OnlyHeadword => write!(f, "definition only repeats the word"),
+            LintWarning::TooLong { chars } => write!(
+                f,
+                "definition is {chars} characters long (over {MAX_DEFINITION_CHARS})"
+            ),
+            LintWarning::UnbalancedParens => write!(f, "definition has unbalanced parentheses"),
+            LintWarning::HtmlTags => write!(f, "definition contains HTML tags"),
+        }
+    }
+}
+
+/// Everything that looks wrong with `def` as the definition of `word`.
+pub fn lint_definition(word: &str, def: &str) -> Vec<LintWarning> {
+    let def = def.trim();
+    if def.is_empty() {
+        return vec![LintWarning::Empty];
+    }
+
+    let mut warnings = Vec::new();
+    let chars = def.chars().count();
+    if chars < MIN_DEFINITION_CHARS {
+        warnings.push(LintWarning::TooShort);
+    } else if chars > MAX_DEFINITION_CHARS {
+        warnings.push(LintWarning::TooLong { chars });
+    }
+    if only_headword(word, def) {
+        warnings.push(LintWarning::OnlyHeadword);
+    }
+    if !balanced(def) {
+        warnings.push(LintWarning::UnbalancedParens);
+    }
+    if tags(def).next().is_some() {
+        warnings.push(LintWarning::HtmlTags);
+    }
+    warnings
+}
+
+fn only_headword(word: &str, def: &str) -> bool {
+    let word = word.to_lowercase();
+    let mut tokens = def
+        .split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '-')
+        .filter(|t| !t.is_empty())
+        .peekable();
+    tokens.peek().is_some() && tokens.all(|t| t.to_lowercase() == word)
+}
+
+fn balanced(def: &str) -> bool {
+    let mut depth = 0usize;
+    for c in def.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+/// Byte ranges of the tags in `text` and their lowercased names. A tag is
+/// `<`, an optional `/` or `!`, then a letter, up to the next `>`; a bare
+/// `<` as in "x < y" isn't one.
+pub fn tags(text: &str) -> impl Iterator<Item = (usize, usize, String)> + '_ {
+    let mut from = 0;
+    std::iter::from_fn(move || {
+        while let Some(offset) = text[from..].find('<') {
+            let start = from + offset;
+            from = start + 1;
+            let rest = &text[start + 1..];
+            let name_start = rest.trim_start_matches(['/', '!']);
+            if !name_start.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                continue;
+            }
+            let len = rest.find(['<', '>'])?;
+            if rest[len..].starts_with('<') {
+                continue;
+            }
+            let name: String = name_start
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .collect();
+            from = start + len + 2;
+            return Some((start, from, name.to_ascii_lowercase()));
+        }
+        None
+    })
+}
+
+/// `def` with its HTML tags removed and character entities decoded, so
+/// `<b>caf&eacute;</b> &amp; bar` reads "café & bar". Entities are decoded
+/// after the tags are gone, so `&lt;b&gt;` stays as the text `<b>`.
+pub fn strip_html(def: &str) -> String {
+    let mut text = String::with_capacity(def.len());
+    let mut last = 0;
+    for (start, end, name) in tags(def) {
+        text.push_str(&def[last..start]);
+        if BREAKING_TAGS.contains(&name.as_str()) {
+            text.push(' ');
+        }
+        last = end;
+    }
+    text.push_str(&def[last..]);
+
+    decode_entities(&text)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest
+            .find(';')
+            .filter(|&semi| semi <= 10)
+            .and_then(|semi| Some((entity(&rest[1..semi])?, semi)));
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(code) = name.strip_prefix('#') {
+        let code = match code.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => code.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "eacute" => 'é',
+        "egrave" => 'è',
+        "agrave" => 'à',
+        "ccedil" => 'ç',
+        "uuml" => 'ü',
+        "ouml" => 'ö',
+        _ => return None,
+    })
+}
+
+fn label(word: &Word) -> String {
+    format!("{} (#{})", word.word, word.id)
+}
+
+/// Lints every definition in the database. Returns one line per finding.
+pub fn check(conn: &Connection) -> Result<Vec<String>> {
+    let mut report = Vec::new();
+    WordStore::new(conn).for_each(WordFilter::All, PAGE_SIZE, |word| {
+        for warning in lint_definition(&word.word, &word.definition) {
+            report.push(format!("{}: {warning}", label(&word)));
+        }
+        Ok(())
+    })?;
+    Ok(report)
+}
+
+/// Offers to strip the HTML from each definition that has some, asking
+/// `confirm` with the word and its stripped definition. Returns one line
+/// per word looked at.
+pub fn fix(
+    conn: &Connection,
+    mut confirm: impl FnMut(&Word, &str) -> Result<bool>,
+) -> Result<Vec<String>> {
+    let mut tagged = Vec::new();
+    WordStore::new(conn).for_each(WordFilter::All, PAGE_SIZE, |word| {
+        if lint_definition(&word.word, &word.definition).contains(&LintWarning::HtmlTags) {
+            tagged.push(word);
+        }
+        Ok(())
+    })?;
+
+    let mut report = Vec::new();
+    for word in tagged {
+        let stripped = strip_html(&word.definition);
+        if stripped.is_empty() {
+            report.push(format!(
+                "{}: nothing left without the tags; kept",
+                label(&word)
+            ));
+        } else if confirm(&word, &stripped)? {
+            conn.execute(
+                "UPDATE words SET definition=?1 WHERE id=?2",
+                params![stripped, word.id],
+            )?;
+            report.push(format!("{}: stripped tags", label(&word)));
+        } else {
+            report.push(format!("{}: skipped", label(&word)));
+        }
+    }
+    if report.is_empty() {
+        report.push("No definitions with HTML tags".into());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+
+    #[test]
+    fn test_empty_definition() {
+        assert_eq!(lint_definition("abate", ""), vec![LintWarning::Empty]);
+        assert_eq!(lint_definition("abate", " \n "), vec![LintWarning::Empty]);
+    }
+
+    #[test]
+    fn test_too_short_definition() {
+        assert_eq!(lint_definition("abate", "x"), vec![LintWarning::TooShort]);
+        assert!(lint_definition("wan", "pale").is_empty());
+    }
+
+    #[test]
+    fn test_definition_only_repeating_the_word() {
+        assert_eq!(
+            lint_definition("Abate", "abate."),
+            vec![LintWarning::OnlyHeadword]
+        );
+        assert_eq!(
+            lint_definition("abate", "abate; abate"),
+            vec![LintWarning::OnlyHeadword]
+        );
+        assert!(lint_definition("abate", "to abate; lessen").is_empty());
+    }
+
+    #[test]
+    fn test_too_long_definition() {
+        let long = "an encyclopedia dump ".repeat(20);
+        assert_eq!(
+            lint_definition("abate", &long),
+            vec![LintWarning::TooLong {
+                chars: long.trim().chars().count()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_parentheses() {
+        assert_eq!(
+            lint_definition("austere", "(of a person strict"),
+            vec![LintWarning::UnbalancedParens]
+        );
+        assert_eq!(
+            lint_definition("austere", "strict) and (stern"),
+            vec![LintWarning::UnbalancedParens]
+        );
+        assert!(lint_definition("austere", "(of a person) strict (stern)").is_empty());
+    }
+
+    #[test]
+    fn test_html_tags() {
+        assert_eq!(
+            lint_definition("abate", "<i>lessen</i>"),
+            vec![LintWarning::HtmlTags]
+        );
+        assert_eq!(
+            lint_definition("abate", "lessen<br/>reduce"),
+            vec![LintWarning::HtmlTags]
+        );
+        assert!(lint_definition("fewer", "x < y and y > z").is_empty());
+    }
+
+    #[test]
+    fn test_strip_html_decodes_entities() {
+        assert_eq!(strip_html("<b>caf&eacute;</b> &amp; bar"), "café & bar");
+        assert_eq!(strip_html("lessen<br>reduce"), "lessen reduce");
+        assert_eq!(
+            strip_html("&lt;b&gt; is a tag&#33; &#x2014;"),
+            "<b> is a tag! —"
+        );
+        assert_eq!(strip_html("<p>strict</p>\n<p>plain</p>"), "strict\nplain");
+        assert_eq!(strip_html("x < y &unknown; z"), "x < y &unknown; z");
+    }
+
+    #[test]
+    fn test_check_and_fix() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'abate','<i>lessen</i>',1), (2,'wane','<b>decrease</b>',1),
+                 (3,'belie','belie',1), (4,'wan','pale',1)",
+        )
+        .unwrap();
+
+        assert_eq!(
+            check(&conn).unwrap(),
+            vec![
+                "abate (#1): definition contains HTML tags",
+                "wane (#2): definition contains HTML tags",
+                "belie (#3): definition only repeats the word",
+            ]
+        );
+
+        let mut offered = Vec::new();
+        let report = fix(&conn, |word, stripped| {
+            offered.push(stripped.to_string());
+            Ok(word.word == "abate")
+        });
+        assert_eq!(
+            report.unwrap(),
+            vec!["abate (#1): stripped tags", "wane (#2): skipped"]
+        );
+        assert_eq!(offered, vec!["lessen", "decrease"]);
+        let definition: String = conn
+            .query_row("SELECT definition FROM words WHERE id=1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(definition, "lessen");
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_FILTER: &str = "vocabulator=debug";
+
+/// `$STATE_DIR/vocabulator/vocabulator.log`, or the local data dir on
+/// platforms without a state dir.
+pub fn log_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("vocabulator").join("vocabulator.log"))
+}
+
+/// Starts logging to the log file when `verbose` is set or `RUST_LOG` is
+/// present. Output only ever goes to the file, never to the terminal the
+/// TUI is drawing on.
+pub fn init(verbose: bool) -> Result<()> {
+    let from_env = std::env::var_os("RUST_LOG").is_some();
+    if !verbose && !from_env {
+        return Ok(());
+    }
+
+    let path = log_path().context("No state directory to write the log file to")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Could not open log file {}", path.display()))?;
+
+    let filter = if from_env {
+        EnvFilter::from_default_env()
+    } else {
+        EnvFilter::new(DEFAULT_FILTER)
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_ansi(false)
+        .with_writer(Mutex::new(file))
+        .try_init()
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// Collects formatted log lines so tests can assert on emitted events.
+    #[derive(Clone, Default)]
+    pub struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Captured {
+        pub fn text(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+
+        /// Runs `f` with a subscriber that records into this buffer.
+        pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+            let subscriber = tracing_subscriber::fmt()
+                .with_max_level(tracing::Level::DEBUG)
+                .with_ansi(false)
+                .with_writer(self.clone())
+                .finish();
+            tracing::subscriber::with_default(subscriber, f)
+        }
+    }
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Captured;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+}
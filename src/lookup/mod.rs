@@ -0,0 +1,129 @@
+use crate::lint::strip_html;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Senses kept from a Wiktionary entry; the first ones are the common ones.
+pub const MAX_SENSES: usize = 3;
+
+const API: &str = "https://en.wiktionary.org/api/rest_v1/page/definition/";
+
+#[derive(Deserialize)]
+struct Usage {
+    definitions: Vec<Sense>,
+}
+
+#[derive(Deserialize)]
+struct Sense {
+    definition: String,
+}
+
+/// `word`'s English definition from Wiktionary: up to `MAX_SENSES` senses,
+/// one per line.
+pub fn define(word: &str) -> Result<String> {
+    let json = fetch(&format!("{API}{}", encode(word)), word)?;
+    let senses = senses(&json, MAX_SENSES)?;
+    if senses.is_empty() {
+        bail!("Wiktionary has no English definition of \"{word}\"");
+    }
+    Ok(senses.join("\n"))
+}
+
+#[cfg(feature = "lookup")]
+fn fetch(url: &str, word: &str) -> Result<String> {
+    let response = match ureq::get(url)
+        .timeout(std::time::Duration::from_secs(10))
+        .call()
+    {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => bail!("Wiktionary has no entry for \"{word}\""),
+        Err(e) => bail!("Couldn't reach Wiktionary: {e}"),
+    };
+    Ok(response.into_string()?)
+}
+
+#[cfg(not(feature = "lookup"))]
+fn fetch(_url: &str, _word: &str) -> Result<String> {
+    bail!("This build can't look words up; rebuild with --features lookup")
+}
+
+/// Title as the REST API expects it in the path: spaces as underscores,
+/// anything outside the unreserved set percent-encoded.
+fn encode(word: &str) -> String {
+    let mut out = String::new();
+    for byte in word.trim().replace(' ', "_").bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// The first `limit` English senses in a definition API response, as
+/// plain text. Senses that are empty without their markup are skipped.
+fn senses(json: &str, limit: usize) -> Result<Vec<String>> {
+    let mut languages: HashMap<String, Vec<Usage>> =
+        serde_json::from_str(json).context("Unexpected response from Wiktionary")?;
+    let english = languages.remove("en").unwrap_or_default();
+    Ok(english
+        .iter()
+        .flat_map(|usage| &usage.definitions)
+        .map(|sense| {
+            strip_html(&sense.definition)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|sense| !sense.is_empty())
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_senses_from_saved_response() {
+        let json = include_str!("fixtures/abate.json");
+        assert_eq!(
+            senses(json, MAX_SENSES).unwrap(),
+            [
+                "(transitive) To put an end to; to cause to cease.",
+                "(transitive) To reduce in amount, size, or value.",
+                "(intransitive) To decrease or diminish in intensity, numbers, or worth.",
+            ]
+        );
+        assert_eq!(senses(json, 10).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_senses_skip_empty_and_decode_entities() {
+        let json = include_str!("fixtures/cafe.json");
+        assert_eq!(
+            senses(json, MAX_SENSES).unwrap(),
+            [
+                "A small restaurant selling coffee & light meals.",
+                "(UK, informal) A greasy spoon—a cheap eatery.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_senses_without_english_entry() {
+        let json = include_str!("fixtures/non_english.json");
+        assert!(senses(json, MAX_SENSES).unwrap().is_empty());
+        assert!(senses("<html>", MAX_SENSES).is_err());
+    }
+
+    #[test]
+    fn test_encode_title() {
+        assert_eq!(encode("abate"), "abate");
+        assert_eq!(encode("ad hoc"), "ad_hoc");
+        assert_eq!(encode("café"), "caf%C3%A9");
+        assert_eq!(encode("and/or"), "and%2For");
+    }
+}
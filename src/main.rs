@@ -1,16 +1,68 @@
+mod clean;
+mod clipboard;
+mod config;
 mod core;
 mod db;
+mod dedup;
+mod diagnostics;
+mod doctor;
+mod duplicates;
+mod error;
+mod export;
+mod frequency;
+mod groups;
+mod ipa;
+mod lint;
+mod logging;
+mod lookup;
+mod notify;
+mod profile;
+mod remind;
+mod report;
 mod seed;
+mod speech;
 mod ui;
 
-use anyhow::Result;
+use crate::core::clock::SystemClock;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Local;
 use clap::{Parser, Subcommand};
+use db::health::Damage;
 use db::init_db;
-use seed::seed_from_file;
+use db::lock::{self, DbLock, LockStatus};
+use db::store::{WordFilter, WordStore};
+use rusqlite::Connection;
+use seed::{add_word, lint_seed, seed_bundled, seed_from_file};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use ui::screens::stats::RECENT_SESSIONS;
+use ui::theme::Theme;
 
 #[derive(Parser)]
 #[command(name = "vocabulator")]
 struct Cli {
+    /// Write debug logs to the log file (RUST_LOG also enables it)
+    #[arg(long, global = true)]
+    verbose: bool,
+
+    /// Use a named profile with its own database (overrides the config file)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Open the database without writing to it: nothing is graded, marked
+    /// or saved, and commands that change it refuse to run
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Draw the interface with ASCII characters only, without color
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Draw the interface without color (NO_COLOR also does this)
+    #[arg(long, global = true)]
+    no_color: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -20,23 +72,667 @@ enum Commands {
     /// Seed the database from a provided file path
     Seed {
         /// The path to the seed file (e.g., data/vocab.txt)
-        file: String,
+        #[arg(required_unless_present = "bundled", conflicts_with = "bundled")]
+        file: Option<String>,
+        /// Seed the starter list built into the binary instead
+        #[arg(long)]
+        bundled: bool,
+        /// Only check the definitions and report problems; seeds nothing
+        #[arg(long)]
+        lint: bool,
+    },
+    /// Write per-word stats to vocab-stats-YYYYMMDD.csv
+    Export {
+        /// Directory to write to (defaults to export_dir from the config, then
+        /// the working directory)
+        dir: Option<PathBuf>,
+    },
+    /// Check the database and repair saved progress that no longer fits
+    Doctor {
+        /// Also try to repair a database that can't be opened (missing
+        /// columns, corruption)
+        #[arg(long, conflicts_with = "duplicates")]
+        fix: bool,
+        /// List different words that share a definition, ignoring case and
+        /// whitespace
+        #[arg(long)]
+        duplicates: bool,
+        /// Ask whether to keep, edit or delete each word of a duplicate
+        #[arg(long, requires = "duplicates")]
+        resolve: bool,
+    },
+    /// Check definitions for problems (empty, repeated word, HTML, ...)
+    Lint {
+        /// Offer to strip HTML tags from each definition that has them
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Print per-group progress
+    Stats {
+        /// Print the most recent sessions instead
+        #[arg(long)]
+        sessions: bool,
+        /// List archived groups too
+        #[arg(long)]
+        archived: bool,
+        /// List the mastered words instead
+        #[arg(long, conflicts_with = "sessions")]
+        mastered: bool,
+    },
+    /// Strip HTML and markdown left in definitions by web imports
+    Clean {
+        /// Rewrite the definitions; without it, list what would change
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Print a one-line reminder and exit 1 when words are due or today's
+    /// goal isn't met; print nothing and exit 0 otherwise
+    Remind {
+        /// Also show the reminder as a desktop notification
+        #[arg(long)]
+        notify: bool,
+    },
+    /// Summarize recent progress: reviews per day, accuracy, words learned
+    /// and mastered, streak and the most missed words
+    Report {
+        /// How far back to go, e.g. 7d or 2w; days end at midnight
+        #[arg(long, default_value = "7d")]
+        since: String,
+        #[arg(long, value_enum, default_value_t = report::Format::Markdown)]
+        format: report::Format,
+        /// Write the report to this file instead of printing it
+        #[arg(long)]
+        output: Option<PathBuf>,
     },
+    /// Merge words that differ only in case or Unicode composition
+    Dedup {
+        /// List what would be merged without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Rank words from a frequency list: one word per line, ranked by line
+    /// number, or `word,rank` lines
+    ImportFrequency { file: PathBuf },
+    /// Set IPA transcriptions from `word,ipa` lines
+    ImportIpa { file: PathBuf },
+    /// Rename, renumber or archive groups
+    Group {
+        #[command(subcommand)]
+        command: GroupCommand,
+    },
+    /// Add a word to the list
+    Add {
+        word: String,
+        /// Its definition; senses go on separate lines
+        definition: Option<String>,
+        /// Group to add it to (defaults to the last group)
+        #[arg(long)]
+        group: Option<i32>,
+        /// Without a definition, fetch one from Wiktionary and ask before
+        /// using it (needs a build with the `lookup` feature)
+        #[arg(long)]
+        lookup: bool,
+    },
+    /// Move words to another group
+    Move {
+        #[arg(long)]
+        to_group: i32,
+        /// Words to move, matched ignoring case
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// Move marked words between databases as a list of words
+    Marks {
+        #[command(subcommand)]
+        command: MarksCommand,
+    },
+    /// Debug log helpers
+    Log {
+        #[command(subcommand)]
+        command: LogCommand,
+    },
+    /// Print version, build and database details for bug reports
+    Info {
+        /// Print as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage profiles, each with its own database
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommand {
+    /// List profiles, marking the active one
+    List,
+    /// Create an empty profile
+    Create { name: String },
+    /// Delete a profile and its database
+    Remove { name: String },
+}
+
+#[derive(Subcommand)]
+enum MarksCommand {
+    /// Write every marked word to a file, one per line
+    Export { file: PathBuf },
+    /// Mark the words a file lists, ignoring case
+    Import {
+        file: PathBuf,
+        /// Clear the mark on every word the file doesn't list
+        #[arg(long)]
+        unmark_others: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GroupCommand {
+    /// Give a group a name, replacing any it had
+    Rename { id: i32, name: String },
+    /// Move a group's words, name and saved progress to another number
+    Renumber {
+        old: i32,
+        new: i32,
+        /// Combine with the target group if it already has words
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Keep a group out of the main rotation without deleting it
+    Archive { id: i32 },
+    /// Put an archived group back into the rotation
+    Unarchive { id: i32 },
+}
+
+#[derive(Subcommand)]
+enum LogCommand {
+    /// Print where the log file is written
+    Path,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let conn = init_db("vocab.db")?;
+    logging::init(cli.verbose)?;
+    let config = config::load()?;
+    let profile = cli.profile.clone().or_else(|| config.profile.clone());
+    let db_path = profile::db_path(profile.as_deref())?;
+    match &cli.command {
+        // Neither opens the profile's database.
+        Some(Commands::Profile { .. } | Commands::Log { .. }) => {}
+        // Seeding is how a new profile gets its words.
+        Some(Commands::Seed { .. }) => profile::require(profile.as_deref(), true)?,
+        _ => profile::require(profile.as_deref(), false)?,
+    }
+    run(cli, &config, profile, &db_path)
+}
 
+fn run(cli: Cli, config: &config::Config, profile: Option<String>, db_path: &str) -> Result<()> {
     match cli.command {
-        Some(Commands::Seed { file }) => {
-            seed_from_file(&conn, &file)?;
+        Some(Commands::Seed {
+            file,
+            bundled,
+            lint: true,
+        }) => {
+            let report = lint_seed(file.as_deref().filter(|_| !bundled))?;
+            if report.is_empty() {
+                println!("No problems found");
+            }
+            for line in report {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Seed { file, bundled, .. }) => {
+            let _lock = lock_for_adding(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            match file {
+                Some(file) if !bundled => seed_from_file(&conn, &file)?,
+                _ => seed_bundled(&conn)?,
+            }
             println!("Database seeded successfully.");
         }
+        Some(Commands::Export { dir }) => {
+            let conn = open_for_reading(db_path, cli.read_only, config)?;
+            let dir = dir
+                .or(config.export_dir.clone())
+                .unwrap_or_else(|| ".".into());
+            let path = export::export_stats(&conn, &dir, Local::now().date_naive())?;
+            println!("Exported stats to {}", path.display());
+        }
+        Some(Commands::Doctor {
+            fix,
+            duplicates,
+            resolve,
+        }) => {
+            let report = if duplicates && !resolve {
+                duplicates::check(&open_for_reading(db_path, cli.read_only, config)?)?
+            } else {
+                let _lock = lock_for_writing(db_path, cli.read_only)?;
+                if resolve {
+                    duplicates::resolve(&open_db(db_path, config)?, ask_duplicate)?
+                } else if fix {
+                    doctor::repair(db_path, &SystemClock)?
+                } else {
+                    doctor::run(&open_db(db_path, config)?, &SystemClock)?
+                }
+            };
+            for line in report {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Lint { fix }) => {
+            let report = if fix {
+                let _lock = lock_for_writing(db_path, cli.read_only)?;
+                lint::fix(&open_db(db_path, config)?, confirm_strip)?
+            } else {
+                let report = lint::check(&open_for_reading(db_path, cli.read_only, config)?)?;
+                if report.is_empty() {
+                    vec!["No problems found".into()]
+                } else {
+                    report
+                }
+            };
+            for line in report {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Stats {
+            sessions,
+            archived,
+            mastered,
+        }) => {
+            let conn = open_for_reading(db_path, cli.read_only, config)?;
+            if mastered {
+                let mut any = false;
+                WordStore::new(&conn).for_each(WordFilter::Mastered, 1000, |word| {
+                    any = true;
+                    println!("{} (group {})", word.word, word.group_id);
+                    Ok(())
+                })?;
+                if !any {
+                    println!("No mastered words yet");
+                }
+            } else if sessions {
+                let now = Local::now();
+                let records = db::queries::fetch_recent_sessions(&conn, RECENT_SESSIONS)?;
+                if records.is_empty() {
+                    println!("No finished sessions yet");
+                }
+                for record in records {
+                    println!(
+                        "{}",
+                        core::stats::session_line(&record, &now, &ui::glyphs::UNICODE)
+                    );
+                }
+            } else {
+                for group in db::queries::fetch_group_stats(&conn, archived)? {
+                    println!("{}", core::stats::group_line(&group));
+                }
+            }
+        }
+        Some(Commands::Clean { apply }) => {
+            let _lock = lock_for_writing(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            for line in clean::run(&conn, apply)? {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Remind { notify }) => {
+            let conn = open_for_reading(db_path, cli.read_only, config)?;
+            let mut notifier = notify::SystemNotifier;
+            let notifier = notify.then_some(&mut notifier as &mut dyn notify::Notifier);
+            let mut out = io::stdout();
+            if remind::run(&conn, Local::now(), config.daily_goal, &mut out, notifier)?.is_some() {
+                process::exit(remind::REMINDER_EXIT_CODE);
+            }
+        }
+        Some(Commands::Report {
+            since,
+            format,
+            output,
+        }) => {
+            let days = report::parse_since(&since)?;
+            let conn = open_for_reading(db_path, cli.read_only, config)?;
+            let text = report::Report::load(&conn, Local::now(), days)?.render(format);
+            match output {
+                Some(path) => std::fs::write(&path, text)
+                    .with_context(|| format!("Couldn't write {}", path.display()))?,
+                None => print!("{text}"),
+            }
+        }
+        Some(Commands::Dedup { dry_run }) => {
+            let _lock = lock_for_writing(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            for line in dedup::run(&conn, dry_run)? {
+                println!("{line}");
+            }
+        }
+        Some(Commands::ImportFrequency { file }) => {
+            let _lock = lock_for_writing(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("Reading {}", file.display()))?;
+            for line in frequency::import(&conn, &content)? {
+                println!("{line}");
+            }
+        }
+        Some(Commands::ImportIpa { file }) => {
+            let _lock = lock_for_writing(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("Reading {}", file.display()))?;
+            for line in ipa::import(&conn, &content)? {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Group { command }) => {
+            let _lock = lock_for_writing(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            let report = match command {
+                GroupCommand::Rename { id, name } => groups::rename(&conn, id, &name)?,
+                GroupCommand::Renumber { old, new, merge } => {
+                    groups::renumber(&conn, old, new, merge)?
+                }
+                GroupCommand::Archive { id } => groups::set_archived(&conn, id, true)?,
+                GroupCommand::Unarchive { id } => groups::set_archived(&conn, id, false)?,
+            };
+            for line in report {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Add {
+            word,
+            definition,
+            group,
+            lookup,
+        }) => {
+            let definition = match definition {
+                Some(definition) => definition,
+                None if lookup => lookup_definition(&word)?,
+                None => bail!("Give a definition, or --lookup to fetch one from Wiktionary"),
+            };
+            let _lock = lock_for_adding(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            let group = match group {
+                Some(group) => group,
+                None => db::queries::fetch_final_group(&conn)?.unwrap_or(1),
+            };
+            if !add_word(&conn, &word, &definition, group)? {
+                bail!("\"{word}\" is already in the list");
+            }
+            println!("Added \"{word}\" to group {group}");
+        }
+        Some(Commands::Move { to_group, words }) => {
+            let _lock = lock_for_writing(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            let words: Vec<&str> = words.iter().map(String::as_str).collect();
+            for line in groups::move_words(&conn, &words, to_group)? {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Marks {
+            command: MarksCommand::Export { file },
+        }) => {
+            let conn = open_for_reading(db_path, cli.read_only, config)?;
+            let words = db::queries::fetch_marked_names(&conn)?;
+            let content: String = words.iter().map(|word| format!("{word}\n")).collect();
+            std::fs::write(&file, content)
+                .with_context(|| format!("Writing {}", file.display()))?;
+            println!(
+                "Exported {} marked words to {}",
+                words.len(),
+                file.display()
+            );
+        }
+        Some(Commands::Marks {
+            command:
+                MarksCommand::Import {
+                    file,
+                    unmark_others,
+                },
+        }) => {
+            let _lock = lock_for_writing(db_path, cli.read_only)?;
+            let conn = open_db(db_path, config)?;
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("Reading {}", file.display()))?;
+            let names: Vec<&str> = content.lines().collect();
+            let import = db::with_tx(&conn, |tx| {
+                Ok(db::queries::import_marks(tx, &names, unmark_others)?)
+            })?;
+            for line in import.report(unmark_others) {
+                println!("{line}");
+            }
+        }
+        Some(Commands::Log {
+            command: LogCommand::Path,
+        }) => match logging::log_path() {
+            Some(path) => println!("{}", path.display()),
+            None => println!("No state directory available on this platform"),
+        },
+        Some(Commands::Info { json }) => {
+            let conn = open_for_reading(db_path, cli.read_only, config)?;
+            let info = diagnostics::gather(&conn, db_path)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&info)?);
+            } else {
+                println!("{info}");
+            }
+        }
+        Some(Commands::Profile { command }) => {
+            let base = profile::profiles_dir()?;
+            match command {
+                ProfileCommand::List => {
+                    let active = profile.as_deref().unwrap_or(profile::DEFAULT);
+                    let names = profile::list(&base)?;
+                    for name in
+                        std::iter::once(profile::DEFAULT).chain(names.iter().map(String::as_str))
+                    {
+                        let marker = if name == active { "*" } else { " " };
+                        println!("{marker} {name}");
+                    }
+                }
+                ProfileCommand::Create { name } => {
+                    let path = profile::create(&base, &name)?;
+                    println!("Created profile '{name}' at {}", path.display());
+                }
+                ProfileCommand::Remove { name } => {
+                    profile::remove(&base, &name)?;
+                    println!("Removed profile '{name}'");
+                }
+            }
+        }
         None => {
-            ui::run::run()?;
+            let mut read_only = cli.read_only || db::is_read_only_file(db_path);
+            // Nothing gets written, so no lock is needed; a read-only mount
+            // couldn't take the lock file anyway.
+            let lock = if read_only {
+                None
+            } else {
+                match lock::acquire(db_path)? {
+                    LockStatus::Acquired(lock) => Some(lock),
+                    LockStatus::Held { pid } => {
+                        if !confirm_read_only(pid)? {
+                            return Ok(());
+                        }
+                        read_only = true;
+                        None
+                    }
+                }
+            };
+            let profile = profile.filter(|name| name != profile::DEFAULT);
+            let theme = Theme::from_env(cli.ascii || config.ascii, cli.no_color);
+            ui::run::run(db_path, config, theme, read_only, profile)?;
+            drop(lock);
         }
     }
 
     Ok(())
 }
+
+/// `init_db`, listing the ways out when the database is damaged, with the
+/// config's settings applied.
+fn open_db(db_path: &str, config: &config::Config) -> Result<Connection> {
+    let conn = init_db(db_path).map_err(|e| match e.downcast_ref::<Damage>() {
+        Some(damage) => anyhow!(doctor::damage_help(db_path, damage)),
+        None => e,
+    })?;
+    apply_settings(&conn, config)?;
+    Ok(conn)
+}
+
+/// Opens the database for a command that only reads it. Under
+/// `--read-only`, or when the file can't be written, nothing touches it:
+/// no migrations, no journal files.
+fn open_for_reading(db_path: &str, read_only: bool, config: &config::Config) -> Result<Connection> {
+    if read_only || db::is_read_only_file(db_path) {
+        let conn = db::open_for_reading(db_path)?;
+        apply_settings(&conn, config)?;
+        Ok(conn)
+    } else {
+        open_db(db_path, config)
+    }
+}
+
+fn apply_settings(conn: &Connection, config: &config::Config) -> Result<()> {
+    db::queries::set_word_order(conn, config.order)?;
+    db::queries::set_include_mastered(conn, config.include_mastered)?;
+    Ok(())
+}
+
+fn lock_for_writing(db_path: &str, read_only: bool) -> Result<DbLock> {
+    if read_only {
+        bail!("Not changing {db_path} with --read-only");
+    }
+    match lock::acquire(db_path)? {
+        LockStatus::Acquired(lock) => Ok(lock),
+        LockStatus::Held { pid } => bail!(lock::held_message(pid)),
+    }
+}
+
+/// Like `lock_for_writing`, but goes ahead without the lock while the app
+/// has the database open: adding words can't upset it, and it picks them
+/// up on its own.
+fn lock_for_adding(db_path: &str, read_only: bool) -> Result<Option<DbLock>> {
+    if read_only {
+        bail!("Not changing {db_path} with --read-only");
+    }
+    match lock::acquire(db_path)? {
+        LockStatus::Acquired(lock) => Ok(Some(lock)),
+        LockStatus::Held { pid } => {
+            eprintln!("vocabulator (pid {pid}) is using this database; adding alongside it");
+            Ok(None)
+        }
+    }
+}
+
+fn confirm_strip(word: &db::models::Word, stripped: &str) -> Result<bool> {
+    println!("{}:\n  {}\n  -> {}", word.word, word.definition, stripped);
+    print!("Strip the tags? [y/N] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Shows `word`'s Wiktionary definition and asks before using it. When the
+/// lookup fails or the definition is turned down, asks for one instead.
+fn lookup_definition(word: &str) -> Result<String> {
+    match lookup::define(word) {
+        Ok(fetched) => {
+            println!("{word}:\n  {}", fetched.replace('\n', "\n  "));
+            print!("Use this definition? [Y/n] ");
+            io::stdout().flush()?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer)?;
+            if matches!(answer.trim(), "" | "y" | "Y" | "yes") {
+                return Ok(fetched);
+            }
+        }
+        Err(e) => eprintln!("{e}"),
+    }
+    print!("Definition: ");
+    io::stdout().flush()?;
+    let mut definition = String::new();
+    io::stdin().read_line(&mut definition)?;
+    if definition.trim().is_empty() {
+        bail!("No definition given; nothing added");
+    }
+    Ok(definition)
+}
+
+fn ask_duplicate(
+    word: &db::models::Word,
+    others: &[db::models::Word],
+) -> Result<duplicates::Resolution> {
+    let others: Vec<String> = others.iter().map(|w| w.word.clone()).collect();
+    println!(
+        "{} (#{}): {}\n  also the definition of {}",
+        word.word,
+        word.id,
+        word.definition,
+        others.join(", ")
+    );
+    loop {
+        print!("[k]eep, [e]dit or [d]elete? [k] ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        match answer.trim() {
+            "" | "k" | "K" | "keep" => return Ok(duplicates::Resolution::Keep),
+            "d" | "D" | "delete" => return Ok(duplicates::Resolution::Delete),
+            "e" | "E" | "edit" => {
+                print!("New definition: ");
+                io::stdout().flush()?;
+                let mut definition = String::new();
+                io::stdin().read_line(&mut definition)?;
+                return Ok(duplicates::Resolution::Edit(definition));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn confirm_read_only(pid: u32) -> Result<bool> {
+    eprintln!("{}.", lock::held_message(pid));
+    eprint!("Open it read-only instead? [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_commands_leave_the_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let db_path = path.to_str().unwrap();
+        let conn = init_db(db_path).unwrap();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES('abate','lessen',1)",
+        )
+        .unwrap();
+        drop(conn);
+        let before = std::fs::read(&path).unwrap();
+
+        for args in [
+            &["stats"][..],
+            &["stats", "--sessions"],
+            &["info"],
+            &["lint"],
+        ] {
+            let cli = Cli::try_parse_from(["vocabulator", "--read-only"].iter().chain(args.iter()))
+                .unwrap();
+            run(cli, &config::Config::default(), None, db_path).unwrap();
+        }
+
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+        let wal = dir.path().join("vocab.db-wal");
+        assert!(!wal.exists());
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{Result, bail};
+use std::fmt;
+use std::process::Command;
+
+/// Desktop notifications, behind a trait so tests don't pop any up.
+pub trait Notifier: fmt::Debug {
+    fn notify(&mut self, title: &str, body: &str) -> Result<()>;
+}
+
+/// Shells out to the platform's notifier: `notify-send` on Linux and the
+/// BSDs, `osascript` on macOS and PowerShell on Windows.
+#[derive(Debug, Default)]
+pub struct SystemNotifier;
+
+impl Notifier for SystemNotifier {
+    fn notify(&mut self, title: &str, body: &str) -> Result<()> {
+        let mut command = command(title, body);
+        let program = command.get_program().to_string_lossy().into_owned();
+        let status = match command.status() {
+            Ok(status) => status,
+            Err(e) => bail!("Couldn't run {program}: {e}"),
+        };
+        if !status.success() {
+            bail!("{program} failed ({status})");
+        }
+        Ok(())
+    }
+}
+
+fn command(title: &str, body: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!(
+                "display notification {} with title {}",
+                quote(body),
+                quote(title)
+            ),
+        ]);
+        command
+    } else if cfg!(windows) {
+        let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let mut command = Command::new("powershell");
+        command.args([
+            "-NoProfile",
+            "-Command",
+            &format!(
+                "Add-Type -AssemblyName System.Windows.Forms; \
+                 $n = New-Object System.Windows.Forms.NotifyIcon; \
+                 $n.Icon = [System.Drawing.SystemIcons]::Information; \
+                 $n.Visible = $true; \
+                 $n.ShowBalloonTip(10000, {}, {}, 'Info'); \
+                 Start-Sleep -Seconds 10; $n.Dispose()",
+                quote(title),
+                quote(body)
+            ),
+        ]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=vocabulator", title, body]);
+        command
+    }
+}
+
+#[cfg(test)]
+pub use mock::MockNotifier;
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+
+    /// Records each notification as `(title, body)`.
+    #[derive(Debug, Default)]
+    pub struct MockNotifier {
+        pub sent: Vec<(String, String)>,
+        pub fail: bool,
+    }
+
+    impl Notifier for MockNotifier {
+        fn notify(&mut self, title: &str, body: &str) -> Result<()> {
+            if self.fail {
+                bail!("no notification daemon");
+            }
+            self.sent.push((title.to_string(), body.to_string()));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_quotes_the_message() {
+        let command = command("vocabulator", "say \"hi\" & it's 5\\5");
+        let args: Vec<String> = command
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        let last = args.last().unwrap();
+        if cfg!(target_os = "macos") {
+            assert!(last.contains(r#""say \"hi\" & it's 5\\5""#), "{last}");
+        } else if cfg!(windows) {
+            assert!(last.contains("'say \"hi\" & it''s 5\\5'"), "{last}");
+        } else {
+            assert_eq!(command.get_program(), "notify-send");
+            assert_eq!(last, "say \"hi\" & it's 5\\5");
+        }
+    }
+}
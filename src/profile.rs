@@ -0,0 +1,197 @@
+use crate::db::init_db;
+use crate::db::lock::{self, LockStatus};
+use anyhow::{Result, anyhow, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Database of the default profile, relative to the working directory as
+/// it always has been.
+pub const DEFAULT_DB_PATH: &str = "vocab.db";
+pub const DEFAULT: &str = "default";
+
+/// Directory holding one subdirectory per named profile.
+pub fn profiles_dir() -> Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join("vocabulator"))
+        .ok_or_else(|| anyhow!("No data directory available on this platform"))
+}
+
+/// Database path for `name`; `None` or "default" is the default profile.
+pub fn db_path(name: Option<&str>) -> Result<String> {
+    match name {
+        None | Some(DEFAULT) => Ok(DEFAULT_DB_PATH.into()),
+        Some(name) => Ok(db_path_in(&profiles_dir()?, name)?.display().to_string()),
+    }
+}
+
+/// Fails for a named profile that doesn't exist yet, so a typo in
+/// `--profile` doesn't quietly start an empty one. With `create` its
+/// directory is made instead, for commands that fill a new profile.
+pub fn require(name: Option<&str>, create: bool) -> Result<()> {
+    match name {
+        None | Some(DEFAULT) => Ok(()),
+        Some(name) => require_in(&profiles_dir()?, name, create),
+    }
+}
+
+fn require_in(base: &Path, name: &str, create: bool) -> Result<()> {
+    let path = db_path_in(base, name)?;
+    if create {
+        fs::create_dir_all(path.parent().unwrap())?;
+    } else if !path.exists() {
+        bail!("No such profile '{name}'; create it with `vocabulator profile create {name}`");
+    }
+    Ok(())
+}
+
+fn db_path_in(base: &Path, name: &str) -> Result<PathBuf> {
+    validate(name)?;
+    Ok(base.join(name).join("vocab.db"))
+}
+
+fn validate(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!("Invalid profile name '{name}': use letters, digits, '-' and '_'");
+    }
+    Ok(())
+}
+
+/// Named profiles under `base`, sorted. The default profile isn't listed.
+pub fn list(base: &Path) -> Result<Vec<String>> {
+    if !base.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(base)? {
+        let entry = entry?;
+        if entry.path().join("vocab.db").exists()
+            && let Some(name) = entry.file_name().to_str()
+        {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+pub fn create(base: &Path, name: &str) -> Result<PathBuf> {
+    if name == DEFAULT {
+        bail!("The default profile always exists");
+    }
+    let path = db_path_in(base, name)?;
+    if path.exists() {
+        bail!("Profile '{name}' already exists");
+    }
+    fs::create_dir_all(path.parent().unwrap())?;
+    init_db(&path.display().to_string())?;
+    Ok(path)
+}
+
+pub fn remove(base: &Path, name: &str) -> Result<()> {
+    if name == DEFAULT {
+        bail!("The default profile can't be removed");
+    }
+    let path = db_path_in(base, name)?;
+    if !path.exists() {
+        bail!("No profile named '{name}'");
+    }
+    let _lock = match lock::acquire(&path.display().to_string())? {
+        LockStatus::Acquired(lock) => lock,
+        LockStatus::Held { pid } => bail!(lock::held_message(pid)),
+    };
+    fs::remove_dir_all(path.parent().unwrap())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries;
+    use crate::seed::seed_from_file;
+    use std::io::Write;
+    use tempfile::{NamedTempFile, TempDir};
+
+    fn seed(path: &Path, content: &str) {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{content}").unwrap();
+        let conn = init_db(&path.display().to_string()).unwrap();
+        seed_from_file(&conn, file.path().to_str().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_profiles_are_isolated() {
+        let base = TempDir::new().unwrap();
+        let alice = create(base.path(), "alice").unwrap();
+        let bob = create(base.path(), "bob").unwrap();
+        assert_ne!(alice, bob);
+
+        seed(&alice, "Group 1\nabate lessen\nabound plentiful\n");
+        seed(&bob, "Group 1\nlaconic terse\n");
+
+        let words = |path: &Path| {
+            let conn = init_db(&path.display().to_string()).unwrap();
+            queries::fetch_words_by_group(&conn, 1)
+                .unwrap()
+                .into_iter()
+                .map(|w| w.word)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(words(&alice), vec!["abate", "abound"]);
+        assert_eq!(words(&bob), vec!["laconic"]);
+
+        assert_eq!(list(base.path()).unwrap(), vec!["alice", "bob"]);
+    }
+
+    #[test]
+    fn test_create_and_remove() {
+        let base = TempDir::new().unwrap();
+        assert!(list(base.path()).unwrap().is_empty());
+
+        create(base.path(), "work").unwrap();
+        assert!(create(base.path(), "work").is_err());
+
+        remove(base.path(), "work").unwrap();
+        assert!(list(base.path()).unwrap().is_empty());
+        assert!(remove(base.path(), "work").is_err());
+    }
+
+    #[test]
+    fn test_missing_profile_is_refused_unless_created() {
+        let base = TempDir::new().unwrap();
+        let err = require_in(base.path(), "wrok", false).unwrap_err();
+        assert!(
+            err.to_string().starts_with("No such profile 'wrok'"),
+            "{err}"
+        );
+        assert!(!base.path().join("wrok").exists());
+
+        require_in(base.path(), "work", true).unwrap();
+        assert!(base.path().join("work").is_dir());
+        init_db(
+            &db_path_in(base.path(), "work")
+                .unwrap()
+                .display()
+                .to_string(),
+        )
+        .unwrap();
+        require_in(base.path(), "work", false).unwrap();
+        require(None, false).unwrap();
+        require(Some(DEFAULT), false).unwrap();
+    }
+
+    #[test]
+    fn test_default_and_invalid_names() {
+        let base = TempDir::new().unwrap();
+        assert_eq!(db_path(None).unwrap(), DEFAULT_DB_PATH);
+        assert_eq!(db_path(Some("default")).unwrap(), DEFAULT_DB_PATH);
+        assert!(create(base.path(), DEFAULT).is_err());
+        assert!(remove(base.path(), DEFAULT).is_err());
+        for name in ["", "../escape", "a b", "x/y"] {
+            assert!(create(base.path(), name).is_err(), "{name:?} accepted");
+        }
+    }
+}
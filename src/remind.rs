@@ -0,0 +1,251 @@
+use crate::core::stats::WEAK_MIN_SEEN;
+use crate::core::utils;
+use crate::db::queries;
+use crate::notify::Notifier;
+use anyhow::Result;
+use chrono::{DateTime, Days, TimeZone};
+use rusqlite::Connection;
+use std::io::Write;
+
+/// Longest streak looked for, so a long history doesn't mean thousands of
+/// queries.
+const MAX_STREAK: u32 = 3650;
+
+/// What `vocabulator remind` reports on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Status {
+    /// Words the Weak Words session would bring up, without its cap.
+    pub due: i64,
+    pub reviewed_today: i64,
+    /// Reviews a day; 0 means no goal.
+    pub goal: u32,
+    /// Days in a row with at least one review, up to today or, before
+    /// today's first review, up to yesterday.
+    pub streak: u32,
+}
+
+impl Status {
+    pub fn goal_met(&self) -> bool {
+        self.reviewed_today >= i64::from(self.goal)
+    }
+
+    /// A streak that ends today unless something is reviewed.
+    pub fn streak_at_risk(&self) -> bool {
+        self.streak > 0 && self.reviewed_today == 0
+    }
+
+    /// The one-line reminder, or `None` when nothing is due, the goal is
+    /// met and the streak is safe.
+    pub fn message(&self) -> Option<String> {
+        if self.due == 0 && self.goal_met() && !self.streak_at_risk() {
+            return None;
+        }
+        let mut parts = Vec::new();
+        match self.due {
+            0 => {}
+            1 => parts.push("1 word due".to_string()),
+            n => parts.push(format!("{n} words due")),
+        }
+        if !self.goal_met() {
+            let left = i64::from(self.goal) - self.reviewed_today;
+            parts.push(format!("{left} reviews to today's goal"));
+        }
+        if self.streak_at_risk() {
+            parts.push("streak at risk".into());
+        }
+        Some(format!("vocabulator: {}", parts.join(", ")))
+    }
+}
+
+pub fn status<Tz: TimeZone>(conn: &Connection, now: DateTime<Tz>, goal: u32) -> Result<Status> {
+    let today = utils::start_of_day(now.clone());
+    Ok(Status {
+        due: queries::count_weak_words(conn, WEAK_MIN_SEEN, i64::MAX as usize)?,
+        reviewed_today: queries::count_reviews_between(conn, today, i64::MAX)?,
+        goal,
+        streak: streak(conn, now)?,
+    })
+}
+
+/// Days in a row with at least one review, up to today or, before today's
+/// first review, up to yesterday.
+pub fn streak<Tz: TimeZone>(conn: &Connection, now: DateTime<Tz>) -> Result<u32> {
+    let today = utils::start_of_day(now.clone());
+    let reviewed_today = queries::count_reviews_between(conn, today, i64::MAX)?;
+
+    let mut streak = (reviewed_today > 0) as u32;
+    let mut end = today;
+    for back in 1..=MAX_STREAK {
+        let Some(day) = now.clone().checked_sub_days(Days::new(back.into())) else {
+            break;
+        };
+        let start = utils::start_of_day(day);
+        if queries::count_reviews_between(conn, start, end)? == 0 {
+            break;
+        }
+        streak += 1;
+        end = start;
+    }
+    Ok(streak)
+}
+
+/// Exit code when there is a reminder. With nothing to do `remind` prints
+/// nothing and exits 0, so a cron job or timer can act on the difference.
+pub const REMINDER_EXIT_CODE: i32 = 1;
+
+/// Works out the reminder for now and writes it to `out`, then, with a
+/// `notifier`, sends it as a desktop notification too. A notification
+/// that can't be sent is only a warning: the reminder is already out.
+pub fn run<Tz: TimeZone>(
+    conn: &Connection,
+    now: DateTime<Tz>,
+    goal: u32,
+    out: &mut dyn Write,
+    notifier: Option<&mut dyn Notifier>,
+) -> Result<Option<String>> {
+    let message = status(conn, now, goal)?.message();
+    if let Some(message) = &message {
+        writeln!(out, "{message}")?;
+        if let Some(notifier) = notifier {
+            let body = message.trim_start_matches("vocabulator: ");
+            if let Err(e) = notifier.notify("vocabulator", body) {
+                tracing::warn!("sending the notification failed: {e}");
+                eprintln!("Couldn't send the notification: {e}");
+            }
+        }
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use crate::notify::MockNotifier;
+    use chrono::Utc;
+
+    fn status(due: i64, reviewed_today: i64, goal: u32, streak: u32) -> Status {
+        Status {
+            due,
+            reviewed_today,
+            goal,
+            streak,
+        }
+    }
+
+    #[test]
+    fn test_message_for_each_state() {
+        let cases = [
+            // Nothing due, goal met (or none), streak kept or not started.
+            (status(0, 25, 20, 4), None),
+            (status(0, 0, 0, 0), None),
+            (status(0, 0, 0, 4), Some("vocabulator: streak at risk")),
+            (status(23, 25, 20, 4), Some("vocabulator: 23 words due")),
+            (status(1, 0, 0, 0), Some("vocabulator: 1 word due")),
+            (
+                status(23, 0, 0, 4),
+                Some("vocabulator: 23 words due, streak at risk"),
+            ),
+            (
+                status(0, 5, 20, 4),
+                Some("vocabulator: 15 reviews to today's goal"),
+            ),
+            (
+                status(0, 0, 20, 0),
+                Some("vocabulator: 20 reviews to today's goal"),
+            ),
+            (
+                status(0, 0, 20, 4),
+                Some("vocabulator: 20 reviews to today's goal, streak at risk"),
+            ),
+            (
+                status(23, 5, 20, 0),
+                Some("vocabulator: 23 words due, 15 reviews to today's goal"),
+            ),
+            (
+                status(23, 0, 20, 4),
+                Some("vocabulator: 23 words due, 20 reviews to today's goal, streak at risk"),
+            ),
+        ];
+        for (status, expected) in cases {
+            assert_eq!(status.message().as_deref(), expected, "{status:?}");
+        }
+    }
+
+    #[test]
+    fn test_status_counts_streak_and_due() {
+        let conn = test_conn();
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let day = 86_400;
+        let today = now.timestamp() - 9 * 3600;
+        conn.execute_batch(&format!(
+            "INSERT INTO words(id,word,definition,group_id,times_seen,success_count) VALUES
+                 (1,'abate','lessen',1,3,1), (2,'wane','decrease',1,3,3);
+             INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES
+                 (1,{},1,0), (1,{},0,0), (2,{},1,0), (2,{},1,0);",
+            today - 4 * day + 60,
+            today - 2 * day + 60,
+            today - day + 60,
+            today - day + 120,
+        ))
+        .unwrap();
+
+        let before = super::status(&conn, now, 2).unwrap();
+        assert_eq!(before, status(1, 0, 2, 2));
+        assert!(before.streak_at_risk());
+
+        conn.execute(
+            "INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES(1,?1,1,0)",
+            [today + 60],
+        )
+        .unwrap();
+        assert_eq!(super::status(&conn, now, 2).unwrap(), status(1, 1, 2, 3));
+    }
+
+    #[test]
+    fn test_run_notifies_only_with_a_message() {
+        let conn = test_conn();
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let mut notifier = MockNotifier::default();
+        let mut out = Vec::new();
+
+        assert_eq!(
+            run(&conn, now, 0, &mut out, Some(&mut notifier)).unwrap(),
+            None
+        );
+        assert!(out.is_empty());
+        assert!(notifier.sent.is_empty());
+
+        let message = run(&conn, now, 10, &mut out, Some(&mut notifier)).unwrap();
+        assert_eq!(
+            message.as_deref(),
+            Some("vocabulator: 10 reviews to today's goal")
+        );
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "vocabulator: 10 reviews to today's goal\n"
+        );
+        assert_eq!(
+            notifier.sent,
+            vec![("vocabulator".into(), "10 reviews to today's goal".into())]
+        );
+    }
+
+    #[test]
+    fn test_run_prints_even_when_notifying_fails() {
+        let conn = test_conn();
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let mut notifier = MockNotifier {
+            fail: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+
+        let message = run(&conn, now, 10, &mut out, Some(&mut notifier)).unwrap();
+        assert!(message.is_some());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "vocabulator: 10 reviews to today's goal\n"
+        );
+    }
+}
@@ -0,0 +1,374 @@
+use crate::core::stats::{MASTERED_MIN_SEEN, MASTERED_PERCENT};
+use crate::core::utils;
+use crate::db::models::MissedWord;
+use crate::db::queries;
+use crate::remind;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Days, NaiveDate, TimeZone};
+use clap::ValueEnum;
+use rusqlite::Connection;
+use std::fmt::Write;
+
+/// Words listed under "Most missed".
+pub const MOST_MISSED: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Markdown,
+    Text,
+}
+
+/// Progress over the last few days, up to and including today.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Each day's date and review count, oldest first.
+    pub days: Vec<(NaiveDate, i64)>,
+    pub reviews: i64,
+    pub correct: i64,
+    /// Words answered correctly for the first time ever.
+    pub learned: i64,
+    /// Mastered words at the end of the period and at its start.
+    pub mastered: i64,
+    pub mastered_before: i64,
+    pub streak: u32,
+    pub missed: Vec<MissedWord>,
+}
+
+/// Days in a `--since` period: "7d", "2w" or a bare number of days.
+pub fn parse_since(since: &str) -> Result<u32> {
+    let since = since.trim();
+    let (number, days_each) = if let Some(n) = since.strip_suffix('d') {
+        (n, 1)
+    } else if let Some(n) = since.strip_suffix('w') {
+        (n, 7)
+    } else {
+        (since, 1)
+    };
+    match number.parse::<u32>() {
+        Ok(n) if n > 0 => n
+            .checked_mul(days_each)
+            .filter(|&days| days <= 3650)
+            .ok_or_else(|| anyhow::anyhow!("{since:?} is more than ten years")),
+        _ => bail!("{since:?} isn't a period like 7d or 2w"),
+    }
+}
+
+/// Midnight starting the day `back` days before `now`'s; -1 is tomorrow.
+fn day_start<Tz: TimeZone>(now: &DateTime<Tz>, back: i64) -> Option<(NaiveDate, i64)> {
+    let day = if back < 0 {
+        now.clone()
+            .checked_add_days(Days::new(back.unsigned_abs()))?
+    } else {
+        now.clone().checked_sub_days(Days::new(back as u64))?
+    };
+    Some((day.date_naive(), utils::start_of_day(day)))
+}
+
+impl Report {
+    /// The report for the `days` days ending with `now`'s.
+    pub fn load<Tz: TimeZone>(conn: &Connection, now: DateTime<Tz>, days: u32) -> Result<Report> {
+        let Some((_, end)) = day_start(&now, -1) else {
+            bail!("The clock is out of range");
+        };
+        let mut counts = Vec::new();
+        let mut to = end;
+        for back in 0..i64::from(days) {
+            let Some((date, from)) = day_start(&now, back) else {
+                break;
+            };
+            counts.push((date, queries::count_reviews_between(conn, from, to)?));
+            to = from;
+        }
+        counts.reverse();
+        let start = to;
+
+        let (reviews, correct) = queries::count_outcomes_between(conn, start, end)?;
+        Ok(Report {
+            days: counts,
+            reviews,
+            correct,
+            learned: queries::count_first_correct_between(conn, start, end)?,
+            mastered: queries::count_mastered_before(
+                conn,
+                end,
+                MASTERED_MIN_SEEN,
+                MASTERED_PERCENT,
+            )?,
+            mastered_before: queries::count_mastered_before(
+                conn,
+                start,
+                MASTERED_MIN_SEEN,
+                MASTERED_PERCENT,
+            )?,
+            streak: remind::streak(conn, now)?,
+            missed: queries::fetch_most_missed(conn, start, end, MOST_MISSED)?,
+        })
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Markdown => self.markdown(),
+            Format::Text => self.text(),
+        }
+    }
+
+    fn period(&self) -> String {
+        let (Some((first, _)), Some((last, _))) = (self.days.first(), self.days.last()) else {
+            return String::new();
+        };
+        let days = match self.days.len() {
+            1 => "1 day".to_string(),
+            n => format!("{n} days"),
+        };
+        format!("{first} to {last} ({days})")
+    }
+
+    /// The lines shared by both formats, as label and value.
+    fn summary(&self) -> [(&'static str, String); 5] {
+        let accuracy = match self.reviews {
+            0 => "-".to_string(),
+            n => format!(
+                "{:.0}% ({} of {n})",
+                self.correct as f64 * 100.0 / n as f64,
+                self.correct
+            ),
+        };
+        let streak = match self.streak {
+            1 => "1 day".to_string(),
+            n => format!("{n} days"),
+        };
+        [
+            ("Reviews", self.reviews.to_string()),
+            ("Accuracy", accuracy),
+            ("New words learned", self.learned.to_string()),
+            (
+                "Words mastered",
+                format!(
+                    "{} ({:+})",
+                    self.mastered,
+                    self.mastered - self.mastered_before
+                ),
+            ),
+            ("Current streak", streak),
+        ]
+    }
+
+    /// Same sections in the same order every time, so reports diff
+    /// cleanly week to week.
+    fn markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Vocabulator report\n\n{}\n", self.period());
+        out.push_str("## Summary\n\n");
+        for (label, value) in self.summary() {
+            let _ = writeln!(out, "- {label}: {value}");
+        }
+
+        out.push_str(
+            "\n## Reviews per day\n\n| Day | Date | Reviews |\n|-----|------|--------:|\n",
+        );
+        for (date, count) in &self.days {
+            let _ = writeln!(out, "| {} | {date} | {count} |", date.format("%a"));
+        }
+
+        out.push_str("\n## Most missed\n\n");
+        if self.missed.is_empty() {
+            out.push_str("No misses.\n");
+        }
+        for (i, word) in self.missed.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{}. **{}** ({}): {}",
+                i + 1,
+                word.word,
+                misses(word.misses),
+                one_line(&word.definition)
+            );
+        }
+        out
+    }
+
+    fn text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Vocabulator report, {}\n", self.period());
+        for (label, value) in self.summary() {
+            let _ = writeln!(out, "{:<19}{value}", format!("{label}:"));
+        }
+
+        out.push_str("\nReviews per day\n");
+        for (date, count) in &self.days {
+            let _ = writeln!(out, "  {} {date}  {count:>4}", date.format("%a"));
+        }
+
+        out.push_str("\nMost missed\n");
+        if self.missed.is_empty() {
+            out.push_str("  No misses\n");
+        }
+        for (i, word) in self.missed.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  {}. {} ({}): {}",
+                i + 1,
+                word.word,
+                misses(word.misses),
+                one_line(&word.definition)
+            );
+        }
+        out
+    }
+}
+
+fn misses(n: i64) -> String {
+    match n {
+        1 => "1 miss".into(),
+        n => format!("{n} misses"),
+    }
+}
+
+/// Senses joined with "; ", so a definition stays on its list line.
+fn one_line(definition: &str) -> String {
+    definition
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use chrono::Utc;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Compares `actual` with `src/snapshots/<name>`, or rewrites it when
+    /// `UPDATE_SNAPSHOTS` is set.
+    fn assert_golden(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(name);
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "missing golden file {}; run with UPDATE_SNAPSHOTS=1",
+                path.display()
+            )
+        });
+        assert!(
+            actual == expected,
+            "{name} differs from {}\n--- expected\n{expected}--- actual\n{actual}",
+            path.display()
+        );
+    }
+
+    /// A week of history ending Sunday 2024-03-10, plus older reviews that
+    /// count toward mastery and first correct answers but not the week.
+    fn fixture() -> (Connection, DateTime<Utc>) {
+        let conn = test_conn();
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 18, 0, 0).unwrap();
+        let day = |d: u32, hour: i64| {
+            Utc.with_ymd_and_hms(2024, 3, d, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+                + hour * 3600
+        };
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'abate','become less intense',1), (2,'wane','decrease',1),
+                 (3,'laconic','using few words',1), (4,'belie','fail to give a true notion of
+contradict',2),
+                 (5,'laud','praise',2), (6,'ebb','recede',2), (7,'wan','pale',2)",
+        )
+        .unwrap();
+        let reviews = [
+            // Before the week: abate is already mastered, wane nearly.
+            (1, day(1, 9), true),
+            (1, day(1, 10), true),
+            (1, day(2, 9), true),
+            (1, day(2, 10), true),
+            (1, day(2, 11), true),
+            (2, day(2, 9), true),
+            (2, day(2, 10), true),
+            (2, day(2, 11), true),
+            (2, day(2, 12), true),
+            (3, day(2, 9), false),
+            // The week.
+            (2, day(4, 9), true),
+            (3, day(4, 9), false),
+            (3, day(4, 10), true),
+            (4, day(5, 9), false),
+            (4, day(5, 10), false),
+            (4, day(7, 9), false),
+            (5, day(7, 9), true),
+            (6, day(7, 10), false),
+            (7, day(9, 9), false),
+            (7, day(9, 10), false),
+            (1, day(10, 8), true),
+            (5, day(10, 9), false),
+            (3, day(10, 10), false),
+        ];
+        for (word_id, at, correct) in reviews {
+            conn.execute(
+                "INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES(?1,?2,?3,0)",
+                rusqlite::params![word_id, at, correct],
+            )
+            .unwrap();
+        }
+        (conn, now)
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("7d").unwrap(), 7);
+        assert_eq!(parse_since("2w").unwrap(), 14);
+        assert_eq!(parse_since(" 30 ").unwrap(), 30);
+        assert!(parse_since("0d").is_err());
+        assert!(parse_since("week").is_err());
+        assert!(parse_since("9999w").is_err());
+    }
+
+    #[test]
+    fn test_week_figures() {
+        let (conn, now) = fixture();
+        let report = Report::load(&conn, now, 7).unwrap();
+        let counts: Vec<i64> = report.days.iter().map(|(_, n)| *n).collect();
+        assert_eq!(counts, vec![3, 2, 0, 3, 0, 2, 3]);
+        assert_eq!(report.days[0].0.to_string(), "2024-03-04");
+        assert_eq!((report.reviews, report.correct), (13, 4));
+        // laconic and laud; wane was right before the week.
+        assert_eq!(report.learned, 2);
+        assert_eq!((report.mastered, report.mastered_before), (2, 1));
+        assert_eq!(report.streak, 2);
+        let missed: Vec<(&str, i64)> = report
+            .missed
+            .iter()
+            .map(|w| (w.word.as_str(), w.misses))
+            .collect();
+        assert_eq!(
+            missed,
+            vec![
+                ("belie", 3),
+                ("laconic", 2),
+                ("wan", 2),
+                ("ebb", 1),
+                ("laud", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_golden_reports() {
+        let (conn, now) = fixture();
+        let report = Report::load(&conn, now, 7).unwrap();
+        assert_golden("report_week.md", &report.render(Format::Markdown));
+        assert_golden("report_week.txt", &report.render(Format::Text));
+
+        let empty = Report::load(&test_conn(), now, 1).unwrap();
+        assert_golden("report_empty.md", &empty.render(Format::Markdown));
+    }
+}
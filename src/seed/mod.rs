@@ -0,0 +1,357 @@
+mod parse;
+
+use crate::clean::clean_definition;
+use crate::db::queries;
+use crate::error::{Result, VocabError};
+use crate::lint::lint_definition;
+use parse::{MAX_DEFINITION_LEN, ParseError, parse};
+use rusqlite::{Connection, params};
+use std::fs;
+
+impl From<ParseError> for VocabError {
+    fn from(e: ParseError) -> Self {
+        VocabError::Parse {
+            line: e.line,
+            msg: e.msg,
+        }
+    }
+}
+
+/// Starter list shipped in the binary for users without a seed file.
+const BUNDLED: &str = include_str!("../../data/vocab.txt");
+
+pub fn seed_from_file(conn: &Connection, path: &str) -> Result<()> {
+    let content = fs::read_to_string(path)?;
+    seed_from_str(conn, &content)?;
+    Ok(())
+}
+
+/// Seeds the bundled starter list and names its groups "Starter N". Words
+/// already present keep their stats, so running it again changes nothing.
+pub fn seed_bundled(conn: &Connection) -> Result<()> {
+    for group_id in seed_from_str(conn, BUNDLED)? {
+        queries::name_group(conn, group_id, &format!("Starter {group_id}"))?;
+    }
+    Ok(())
+}
+
+/// Lints the definitions in the seed file at `path`, or the bundled list
+/// without one, leaving the database alone. Returns one line per finding.
+pub fn lint_seed(path: Option<&str>) -> Result<Vec<String>> {
+    let content = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => BUNDLED.to_string(),
+    };
+    lint_str(&content)
+}
+
+fn lint_str(content: &str) -> Result<Vec<String>> {
+    let (_, entries) = parse(content, MAX_DEFINITION_LEN)?;
+    let mut report = Vec::new();
+    for entry in entries {
+        for warning in lint_definition(&entry.word, &entry.senses.join("\n")) {
+            report.push(format!("Line {}: {}: {warning}", entry.line, entry.word));
+        }
+    }
+    Ok(report)
+}
+
+/// Adds `word` to `group_id` with its definition cleaned up as seeding
+/// would. False when the list already has it, ignoring case.
+pub fn add_word(conn: &Connection, word: &str, definition: &str, group_id: i32) -> Result<bool> {
+    let added = conn.execute(
+        "INSERT INTO words (word, group_id, definition)
+         SELECT ?1, ?2, ?3
+         WHERE NOT EXISTS (SELECT 1 FROM words WHERE word=?1 COLLATE NOCASE)",
+        params![word.trim(), group_id, clean_definition(definition)],
+    )?;
+    if added > 0 {
+        queries::bump_data_version(conn)?;
+    }
+    Ok(added > 0)
+}
+
+/// Inserts the words in `content` with their definitions cleaned up,
+/// returning the group ids it declared.
+fn seed_from_str(conn: &Connection, content: &str) -> Result<Vec<i32>> {
+    let (groups, entries) = parse(content, MAX_DEFINITION_LEN)?;
+    // One transaction, so an open app sees the whole file arrive at once.
+    let tx = conn.unchecked_transaction()?;
+    for entry in entries {
+        tx.execute(
+            "INSERT OR IGNORE INTO words (word, group_id, definition, ipa, reading)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                entry.word,
+                entry.group_id,
+                clean_definition(&entry.senses.join("\n")),
+                entry.ipa,
+                entry.reading
+            ],
+        )?;
+    }
+    queries::bump_data_version(&tx)?;
+    tx.commit()?;
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_basic_insert() {
+        let conn = test_conn();
+
+        let data = r#"
+Group 1
+abound be present in large quantities
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", data).unwrap();
+
+        seed_from_file(&conn, file.path().to_str().unwrap()).unwrap();
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_add_word_skips_words_already_listed() {
+        let conn = test_conn();
+        assert!(add_word(&conn, "abate", "<i>lessen</i>", 2).unwrap());
+        assert!(!add_word(&conn, "Abate", "reduce", 1).unwrap());
+
+        let row: (String, i32) = conn
+            .query_row("SELECT definition, group_id FROM words", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(row, ("lessen".into(), 2));
+        assert_eq!(queries::data_version(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_ipa_is_stored() {
+        let conn = test_conn();
+        seed_from_str(
+            &conn,
+            "Group 1\nlaconic /ləˈkɒnɪk/ using few words\nwan pale",
+        )
+        .unwrap();
+
+        let ipa: Vec<(String, Option<String>)> = conn
+            .prepare("SELECT definition, ipa FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            ipa,
+            vec![
+                ("using few words".into(), Some("ləˈkɒnɪk".into())),
+                ("pale".into(), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reading_is_stored() {
+        let conn = test_conn();
+        seed_from_str(&conn, "Group 1\n日本[にほん] Japan\nwan pale").unwrap();
+
+        let readings: Vec<(String, Option<String>)> = conn
+            .prepare("SELECT word, reading FROM words ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            readings,
+            vec![("日本".into(), Some("にほん".into())), ("wan".into(), None)]
+        );
+    }
+
+    #[test]
+    fn test_leading_trailing_spaces() {
+        let conn = test_conn();
+
+        let data = r#"
+Group 1
+
+   contrite    feeling regretful or guilty   
+
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", data).unwrap();
+
+        seed_from_file(&conn, file.path().to_str().unwrap()).unwrap();
+
+        let word: String = conn
+            .query_row("SELECT word FROM words", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(word, "contrite");
+    }
+
+    #[test]
+    fn test_multiple_definitions_numbered() {
+        let conn = test_conn();
+
+        let data = r#"
+Group 1
+austere 1. strict and stern
+2. lacking luxury
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", data).unwrap();
+
+        seed_from_file(&conn, file.path().to_str().unwrap()).unwrap();
+
+        let definition: String = conn
+            .query_row("SELECT definition FROM words", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(definition, "strict and stern\nlacking luxury");
+    }
+
+    #[test]
+    fn test_multiple_definitions_numbered_same_line() {
+        let conn = test_conn();
+
+        let data = r#"
+Group 1
+austere 1. strict and stern 2. lacking luxury
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", data).unwrap();
+
+        seed_from_file(&conn, file.path().to_str().unwrap()).unwrap();
+
+        let definition: String = conn
+            .query_row("SELECT definition FROM words", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(definition, "strict and stern\nlacking luxury");
+    }
+
+    #[test]
+    fn test_multiple_definitions_braced() {
+        let conn = test_conn();
+
+        let data = r#"
+Group 1
+amenable (of a person) receptive to change; open
+(of a thing) responsive to
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", data).unwrap();
+
+        seed_from_file(&conn, file.path().to_str().unwrap()).unwrap();
+
+        let definition: String = conn
+            .query_row("SELECT definition FROM words", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(
+            definition,
+            "(of a person) receptive to change; open\n(of a thing) responsive to"
+        );
+    }
+
+    #[test]
+    fn test_group_parsing() {
+        let conn = test_conn();
+
+        let data = r#"
+Group 42
+adulterate damage the quality of; corrupt
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", data).unwrap();
+
+        seed_from_file(&conn, file.path().to_str().unwrap()).unwrap();
+
+        let group_id: i32 = conn
+            .query_row("SELECT group_id FROM words", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(group_id, 42);
+    }
+
+    #[test]
+    fn test_bundled_seed_is_idempotent() {
+        let conn = test_conn();
+        let count = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        seed_bundled(&conn).unwrap();
+        let words = count(&conn);
+        assert!(words > 300, "only {words} bundled words");
+        conn.execute(
+            "UPDATE words SET times_seen=3, success_count=2, marked=1 WHERE word='abound'",
+            [],
+        )
+        .unwrap();
+
+        seed_bundled(&conn).unwrap();
+        assert_eq!(count(&conn), words);
+        let stats: (i64, i64, bool) = conn
+            .query_row(
+                "SELECT times_seen, success_count, marked FROM words WHERE word='abound'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(stats, (3, 2, true));
+
+        let groups = queries::fetch_group_stats(&conn, true).unwrap();
+        assert_eq!(groups[0].name.as_deref(), Some("Starter 1"));
+        assert!(groups.iter().all(|g| g.name.is_some()));
+    }
+
+    #[test]
+    fn test_definitions_are_cleaned() {
+        let conn = test_conn();
+        seed_from_str(
+            &conn,
+            "Group 1\nabate <i>lessen</i> &amp; **reduce**<br>subside",
+        )
+        .unwrap();
+
+        let definition: String = conn
+            .query_row("SELECT definition FROM words", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(definition, "lessen & reduce\nsubside");
+    }
+
+    #[test]
+    fn test_lint_reports_lines() {
+        let data = "Group 1\nabate lessen\nwane\nbelie <i>mask\n2. (disguise</i>\n";
+        assert_eq!(
+            lint_str(data).unwrap(),
+            vec![
+                "Line 3: wane: definition is empty",
+                "Line 4: belie: definition has unbalanced parentheses",
+                "Line 4: belie: definition contains HTML tags",
+            ]
+        );
+        assert!(lint_str(BUNDLED).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,512 @@
+use std::fmt;
+
+/// Longest definition `seed_from_file` accepts, in bytes.
+pub const MAX_DEFINITION_LEN: usize = 4096;
+
+/// A line the grammar below rejects; `line` is 1-based.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub word: String,
+    pub group_id: i32,
+    pub senses: Vec<String>,
+    /// IPA transcription, without its slashes.
+    pub ipa: Option<String>,
+    /// Reading, such as the kana for a kanji headword.
+    pub reading: Option<String>,
+    /// 1-based line the word starts on.
+    pub line: usize,
+}
+
+// Seed file grammar. Lines are trimmed and blank lines skipped; every other
+// line is one of:
+//
+//   Group <n> [text]       starts group <n>; anything after the number is
+//                          ignored. A line whose first token is `Group` must
+//                          be a group line.
+//   <n>. <senses>          more senses for the word above
+//   (<senses>              a sense opening with a parenthetical, e.g.
+//                          `(of a thing) responsive to`
+//   <word>[[<reading>]] [/<ipa>/] [<senses>]
+//                          a new word; the word is the first token and may
+//                          be followed by nothing at all. A first token
+//                          ending in a bracketed part, as in `日本[にほん]`,
+//                          is the word and its reading. A token right after
+//                          it that starts and ends with `/` is its IPA
+//                          transcription, unless written `\/<text>/`
+//
+// Within <senses>, a standalone `<n>.` token starts a new sense, so
+// `1. strict 2. plain` is two. Tokens that only begin with digits (`1990s`,
+// `3.5`) are plain text, and `\<n>.` writes a literal `<n>.`.
+
+/// Splits a seed file into its group ids and words, in file order. Sense
+/// lines before the first word of a group are dropped. Fails on a bad
+/// group line or a definition longer than `max_definition` bytes.
+pub fn parse(content: &str, max_definition: usize) -> Result<(Vec<i32>, Vec<Entry>)> {
+    let mut groups = Vec::new();
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut group_id: i32 = 0;
+    // Whether sense lines still belong to the last entry.
+    let mut open = false;
+    // Bytes in the last entry's definition once its senses are joined.
+    let mut definition_len = 0;
+
+    for (number, raw_line) in content.lines().enumerate() {
+        let number = number + 1;
+        let line = raw_line.trim();
+        let mut tokens = line.split_whitespace().peekable();
+        let Some(&first) = tokens.peek() else {
+            continue;
+        };
+
+        if first == "Group" {
+            group_id = tokens
+                .nth(1)
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| ParseError {
+                    line: number,
+                    msg: format!("Invalid group line: {}", excerpt(line)),
+                })?;
+            groups.push(group_id);
+            open = false;
+            continue;
+        }
+
+        if is_marker(first) || first.starts_with('(') {
+            if is_marker(first) {
+                tokens.next();
+            }
+            if open && let Some(entry) = entries.last_mut() {
+                let more = senses(tokens);
+                definition_len +=
+                    joined_len(&more) + (definition_len > 0 && !more.is_empty()) as usize;
+                check_len(entry, number, definition_len, max_definition)?;
+                entry.senses.extend(more);
+            }
+            continue;
+        }
+
+        tokens.next();
+        let (word, reading) = match headword(first) {
+            Some((word, reading)) => (word, Some(reading.to_string())),
+            None => (first, None),
+        };
+        let mut ipa = None;
+        // An escaped transcription is the definition's first token.
+        let mut literal = None;
+        if let Some(&next) = tokens.peek() {
+            if let Some(transcription) = transcription(next) {
+                ipa = Some(transcription.to_string());
+                tokens.next();
+            } else if let Some(text) = next
+                .strip_prefix('\\')
+                .filter(|t| transcription(t).is_some())
+            {
+                literal = Some(text);
+                tokens.next();
+            }
+        }
+        let entry = Entry {
+            word: word.to_string(),
+            group_id,
+            senses: senses(literal.into_iter().chain(tokens)),
+            ipa,
+            reading,
+            line: number,
+        };
+        definition_len = joined_len(&entry.senses);
+        check_len(&entry, number, definition_len, max_definition)?;
+        entries.push(entry);
+        open = true;
+    }
+
+    Ok((groups, entries))
+}
+
+/// The word and reading of a `<word>[<reading>]` token. Both need text, and
+/// other brackets in either make the whole token the word.
+fn headword(token: &str) -> Option<(&str, &str)> {
+    let (word, reading) = token.strip_suffix(']')?.split_once('[')?;
+    let plain = |s: &str| !s.is_empty() && !s.contains(['[', ']']);
+    (plain(word) && plain(reading)).then_some((word, reading))
+}
+
+/// The text of an `/<ipa>/` token; slashes inside, as in `and/or`, make it
+/// ordinary text.
+fn transcription(token: &str) -> Option<&str> {
+    token
+        .strip_prefix('/')?
+        .strip_suffix('/')
+        .filter(|t| !t.is_empty() && !t.contains('/'))
+}
+
+/// A sense number such as `2.`.
+fn is_marker(token: &str) -> bool {
+    token
+        .strip_suffix('.')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn senses<'a>(tokens: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut senses = vec![Vec::new()];
+    for token in tokens {
+        if is_marker(token) {
+            senses.push(Vec::new());
+            continue;
+        }
+        let token = token
+            .strip_prefix('\\')
+            .filter(|t| is_marker(t))
+            .unwrap_or(token);
+        senses.last_mut().unwrap().push(token);
+    }
+    senses
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| s.join(" "))
+        .collect()
+}
+
+fn joined_len(senses: &[String]) -> usize {
+    senses
+        .iter()
+        .map(|s| s.len() + 1)
+        .sum::<usize>()
+        .saturating_sub(1)
+}
+
+fn check_len(entry: &Entry, line: usize, len: usize, max: usize) -> Result<()> {
+    if len > max {
+        return Err(ParseError {
+            line,
+            msg: format!(
+                "Definition of {} is over {max} bytes long",
+                excerpt(&entry.word)
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Start of `text` for error messages, which may otherwise quote a whole
+/// runaway line.
+fn excerpt(text: &str) -> String {
+    match text.char_indices().nth(40) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn parse_default(content: &str) -> Result<(Vec<i32>, Vec<Entry>)> {
+        parse(content, MAX_DEFINITION_LEN)
+    }
+
+    fn entry(line: usize, word: &str, group_id: i32, senses: &[&str]) -> Entry {
+        Entry {
+            word: word.into(),
+            group_id,
+            senses: senses.iter().map(|s| s.to_string()).collect(),
+            ipa: None,
+            reading: None,
+            line,
+        }
+    }
+
+    #[test]
+    fn test_group_line_with_trailing_text() {
+        let (groups, entries) = parse_default("Group 3 (adjectives)\nwan pale").unwrap();
+        assert_eq!(groups, vec![3]);
+        assert_eq!(entries, vec![entry(2, "wan", 3, &["pale"])]);
+
+        let err = parse_default("Group 1\nwan pale\nGroup three").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.msg, "Invalid group line: Group three");
+        let (groups, entries) = parse_default("Groupthink conformity in a group").unwrap();
+        assert!(groups.is_empty());
+        assert_eq!(entries[0].word, "Groupthink");
+    }
+
+    #[test]
+    fn test_digits_are_text_unless_a_sense_number() {
+        let (_, entries) = parse_default(
+            "Group 1\n4x4 an off-road vehicle\nnineties 1990s. the decade 1990-1999\n2. 3.5 inch floppies\ny2k \\2000. bug",
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                entry(2, "4x4", 1, &["an off-road vehicle"]),
+                entry(
+                    3,
+                    "nineties",
+                    1,
+                    &["1990s. the decade 1990-1999", "3.5 inch floppies"]
+                ),
+                entry(5, "y2k", 1, &["2000. bug"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_word_without_definition() {
+        let (_, entries) = parse_default("Group 1\nlacuna\n(of a text) a gap\nfoo").unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                entry(2, "lacuna", 1, &["(of a text) a gap"]),
+                entry(4, "foo", 1, &[])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ipa_after_the_word() {
+        let (_, entries) = parse_default(
+            "Group 1\nlaconic /ləˈkɒnɪk/ using few words\nwan /wɒn/\n2. sickly\nper /pɜː/",
+        )
+        .unwrap();
+        let ipa: Vec<Option<&str>> = entries.iter().map(|e| e.ipa.as_deref()).collect();
+        assert_eq!(ipa, vec![Some("ləˈkɒnɪk"), Some("wɒn"), Some("pɜː")]);
+        assert_eq!(entries[0].senses, vec!["using few words"]);
+        assert_eq!(entries[1].senses, vec!["sickly"]);
+        assert!(entries[2].senses.is_empty());
+    }
+
+    #[test]
+    fn test_reading_in_brackets() {
+        let (_, entries) = parse_default(
+            "Group 1\n日本[にほん] Japan\n水[みず] /mizu/ water\n2. [archaic] a river\n山[やま]",
+        )
+        .unwrap();
+        let found: Vec<(&str, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.word.as_str(), e.reading.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("日本", Some("にほん")),
+                ("水", Some("みず")),
+                ("山", Some("やま"))
+            ]
+        );
+        assert_eq!(entries[1].ipa.as_deref(), Some("mizu"));
+        assert_eq!(entries[1].senses, vec!["water", "[archaic] a river"]);
+        assert!(entries[2].senses.is_empty());
+    }
+
+    #[test]
+    fn test_brackets_elsewhere_stay_text() {
+        let (_, entries) = parse_default(
+            "Group 1\narray[] a list\n[sic] as written\nfoo[a][b] odd\nbar[x]y odd\nbaz [qux] see [1]\nvec[i] element",
+        )
+        .unwrap();
+        let found: Vec<(&str, Option<&str>, String)> = entries
+            .iter()
+            .map(|e| (e.word.as_str(), e.reading.as_deref(), e.senses.join("; ")))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("array[]", None, "a list".into()),
+                ("[sic]", None, "as written".into()),
+                ("foo[a][b]", None, "odd".into()),
+                ("bar[x]y", None, "odd".into()),
+                ("baz", None, "[qux] see [1]".into()),
+                ("vec", Some("i"), "element".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slashes_in_definitions_stay_text() {
+        let (_, entries) = parse_default(
+            "Group 1\nkph km/h\nvel /s/ 1. speed\nslash / a stroke\neither either/or\npath /usr/bin/ dir\nsound \\/s/ a hiss\nvoid // nothing",
+        )
+        .unwrap();
+        let ipa: Vec<Option<&str>> = entries.iter().map(|e| e.ipa.as_deref()).collect();
+        assert_eq!(ipa, vec![None, Some("s"), None, None, None, None, None]);
+        let senses: Vec<String> = entries.iter().map(|e| e.senses.join("; ")).collect();
+        assert_eq!(
+            senses,
+            vec![
+                "km/h",
+                "speed",
+                "/ a stroke",
+                "either/or",
+                "/usr/bin/ dir",
+                "/s/ a hiss",
+                "// nothing"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_senses_before_a_word_are_dropped() {
+        let (_, entries) = parse_default("abate lessen\nGroup 2\n2. stray\nwane decrease").unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                entry(1, "abate", 0, &["lessen"]),
+                entry(4, "wane", 2, &["decrease"])
+            ]
+        );
+    }
+
+    mod roundtrip {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Word, its senses, and whether they are numbered on one line.
+        type Word = (String, Vec<String>, bool);
+        /// Group id, trailing text on the group line, and its words.
+        type Group = (i32, Option<String>, Vec<Word>);
+
+        fn token() -> impl Strategy<Value = String> {
+            "[a-z0-9(][a-z0-9.,;()'-]{0,9}"
+        }
+
+        fn sense() -> impl Strategy<Value = String> {
+            prop::collection::vec(token(), 1..6).prop_map(|t| t.join(" "))
+        }
+
+        fn word() -> impl Strategy<Value = Word> {
+            (
+                "[a-z0-9][a-z0-9'-]{0,11}",
+                prop::collection::vec(sense(), 0..4),
+                any::<bool>(),
+            )
+        }
+
+        fn group() -> impl Strategy<Value = Group> {
+            (
+                1..10_000i32,
+                prop::option::of("[a-z() -]{1,12}"),
+                prop::collection::vec(word(), 0..8),
+            )
+        }
+
+        fn escape(sense: &str) -> String {
+            sense
+                .split(' ')
+                .map(|t| {
+                    if is_marker(t) {
+                        format!("\\{t}")
+                    } else {
+                        t.into()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+
+        /// Writes the groups in the seed format, numbering multiple senses
+        /// either on one line or one per line.
+        fn render(groups: &[Group]) -> String {
+            let mut out = String::new();
+            for (id, trailing, words) in groups {
+                out.push_str(&format!(
+                    "Group {id} {}\n",
+                    trailing.as_deref().unwrap_or("")
+                ));
+                for (word, senses, inline) in words {
+                    out.push_str(word);
+                    match senses.as_slice() {
+                        [] => {}
+                        [only] => out.push_str(&format!(" {}", escape(only))),
+                        many => {
+                            for (i, sense) in many.iter().enumerate() {
+                                let sep = if i == 0 || *inline { " " } else { "\n" };
+                                out.push_str(&format!("{sep}{}. {}", i + 1, escape(sense)));
+                            }
+                        }
+                    }
+                    out.push('\n');
+                }
+            }
+            out
+        }
+
+        proptest! {
+            #[test]
+            fn parse_recovers_rendered_structure(groups in prop::collection::vec(group(), 1..5)) {
+                let (ids, entries) = parse(&render(&groups), MAX_DEFINITION_LEN).unwrap();
+
+                let expected: Vec<(String, i32, Vec<String>)> = groups
+                    .iter()
+                    .flat_map(|(id, _, words)| {
+                        words
+                            .iter()
+                            .map(move |(word, senses, _)| (word.clone(), *id, senses.clone()))
+                    })
+                    .collect();
+                let found: Vec<(String, i32, Vec<String>)> = entries
+                    .into_iter()
+                    .map(|e| (e.word, e.group_id, e.senses))
+                    .collect();
+                prop_assert_eq!(ids, groups.iter().map(|g| g.0).collect::<Vec<_>>());
+                prop_assert_eq!(found, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_definition_length_limit() {
+        let long = format!("w {}", "x ".repeat(60));
+        assert!(parse(&long, 120).is_ok());
+        let err = parse(&long, 100).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 1: Definition of w is over 100 bytes long"
+        );
+
+        // Sense lines count toward the same definition.
+        let split = format!("w 1. {}\n2. {}", "x".repeat(60), "y".repeat(60));
+        assert!(parse(&split, 121).is_ok());
+        assert_eq!(parse(&split, 120).unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn test_fuzz_regressions() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions/seed_parse");
+        for file in fs::read_dir(dir).unwrap() {
+            let path = file.unwrap().path();
+            let content = String::from_utf8_lossy(&fs::read(&path).unwrap()).into_owned();
+            // Only panics fail; errors are a fine answer to garbage.
+            let _ = parse(&content, MAX_DEFINITION_LEN);
+        }
+    }
+
+    mod arbitrary {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn parse_never_panics(content in any::<String>(), max in 0..64usize) {
+                let _ = parse(&content, max);
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::{Result, anyhow};
+use std::fmt;
+use std::process::{Child, Command, Stdio};
+
+pub trait Speaker: fmt::Debug {
+    /// Starts speaking `text` and returns without waiting for it to finish.
+    fn say(&mut self, text: &str) -> Result<()>;
+}
+
+/// Text-to-speech through an external program such as `espeak` or `say`,
+/// given the text as its last argument.
+#[derive(Debug)]
+pub struct CommandSpeaker {
+    program: String,
+    args: Vec<String>,
+    /// Last utterance, stopped if still going when the next one starts.
+    child: Option<Child>,
+}
+
+impl CommandSpeaker {
+    /// Splits `command` on whitespace into the program and its arguments.
+    pub fn new(command: &str) -> Result<Self> {
+        let mut parts = command.split_whitespace().map(str::to_string);
+        let program = parts
+            .next()
+            .ok_or_else(|| anyhow!("tts_command is empty"))?;
+        Ok(Self {
+            program,
+            args: parts.collect(),
+            child: None,
+        })
+    }
+}
+
+impl Speaker for CommandSpeaker {
+    fn say(&mut self, text: &str) -> Result<()> {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        let child = Command::new(&self.program)
+            .args(&self.args)
+            .arg(text)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("couldn't run {}: {e}", self.program))?;
+        self.child = Some(child);
+        Ok(())
+    }
+}
+
+impl Drop for CommandSpeaker {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+pub use mock::MockSpeaker;
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use anyhow::bail;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records spoken text; clones share the same log so a test can keep
+    /// one after handing the other to the app.
+    #[derive(Debug, Default, Clone)]
+    pub struct MockSpeaker {
+        pub spoken: Rc<RefCell<Vec<String>>>,
+        pub fail: bool,
+    }
+
+    impl Speaker for MockSpeaker {
+        fn say(&mut self, text: &str) -> Result<()> {
+            if self.fail {
+                bail!("speech unavailable");
+            }
+            self.spoken.borrow_mut().push(text.to_string());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_is_split_into_arguments() {
+        let speaker = CommandSpeaker::new("espeak -s 140").unwrap();
+        assert_eq!(speaker.program, "espeak");
+        assert_eq!(speaker.args, ["-s", "140"]);
+        assert!(CommandSpeaker::new("  ").is_err());
+    }
+}
@@ -1,100 +1,1019 @@
-use crate::core::session::{Session, Type};
+use crate::clipboard::{Clipboard, SystemClipboard};
+use crate::core::clock::{Clock, SystemClock};
+use crate::core::preview::{self, SessionPreview};
+use crate::core::session::{self, Session, Type};
+use crate::core::stats::{LeechPolicy, WEAK_LIMIT, WEAK_MIN_SEEN};
+use crate::core::{actions, progress};
+use crate::db::models::SessionSnapshot;
+use crate::db::{self, init_db, queries};
+use crate::error::VocabError;
+use crate::speech::Speaker;
+use crate::ui::event::AppEvent;
+use crate::ui::keymap::{Action, KeyMap};
+use crate::ui::list_nav::{self, ListNav, NavMove};
+use crate::ui::screens::review_log::ReviewLogState;
+use crate::ui::screens::splash::SplashState;
+use crate::ui::screens::stats::StatsState;
+use crate::ui::screens::{menu, practice, review_log, splash, stats, test};
+use crate::ui::theme::Theme;
+use crate::ui::widgets::{Buttons, LineEdit, Modal, ModalResult, Toast};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use rusqlite::Connection;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How often a modified session is written back while it stays open.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the app looks for words added by another process.
+const DATA_CHECK_INTERVAL: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Screen {
+    Splash,
     Menu,
     Practice,
     Test,
+    Stats,
+    ReviewLog,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum MenuAction {
     Session(Type),
+    Stats,
     Exit,
 }
 
 impl MenuAction {
-    pub fn label(&self) -> &'static str {
+    pub fn label(&self, app: &App) -> String {
+        let counts = &app.counts;
+        match self {
+            MenuAction::Session(t @ Type::Group) => {
+                format!("{} ({} new)", t.label(), counts.unseen)
+            }
+            MenuAction::Session(t @ Type::Marked) => format!("{} ({})", t.label(), counts.marked),
+            MenuAction::Session(t @ Type::Weak) => format!("{} ({})", t.label(), counts.weak),
+            MenuAction::Session(t @ Type::Leech) => {
+                format!("{} ({})", t.label(), counts.leeches)
+            }
+            MenuAction::Session(t @ Type::Quick) => {
+                format!("{} ({} min)", t.label(), app.quick_review.as_secs() / 60)
+            }
+            MenuAction::Session(t) => t.label().to_string(),
+            MenuAction::Stats => "Statistics".into(),
+            MenuAction::Exit => "Exit".into(),
+        }
+    }
+
+    /// Why the entry can't be started right now, if it can't.
+    pub fn disabled_reason(&self, app: &App) -> Option<String> {
+        let counts = &app.counts;
         match self {
-            MenuAction::Session(t) => t.label(),
-            MenuAction::Exit => "Exit",
+            MenuAction::Session(Type::Marked) if counts.marked == 0 => Some(format!(
+                "No marked words yet. Press {} while practicing to mark one",
+                app.keymap.label(Action::Mark, app.theme.glyphs)
+            )),
+            MenuAction::Session(Type::Weak) if counts.weak == 0 => Some(format!(
+                "No weak words yet. Words you miss show up here once seen {} times",
+                WEAK_MIN_SEEN
+            )),
+            MenuAction::Session(Type::Leech) if counts.leeches == 0 => Some(format!(
+                "No leeches. Words missed {} times show up here",
+                app.leeches.threshold
+            )),
+            MenuAction::Session(Type::Quick) if counts.words == counts.unseen => {
+                Some("Nothing to review yet. Words show up here once you've practiced them".into())
+            }
+            _ => None,
+        }
+    }
+}
+
+const EMPTY_DATABASE: &str = "The database has no words yet. See the guide above to add some";
+
+/// What to tell the user when a session couldn't be started.
+fn start_problem(err: &VocabError, database_empty: bool) -> String {
+    use rusqlite::ErrorCode::*;
+    match err {
+        VocabError::EmptySelection { .. } if database_empty => EMPTY_DATABASE.into(),
+        VocabError::ProgressOutOfRange { .. } => {
+            format!("{err}. Run `vocabulator doctor` to reset it")
         }
+        VocabError::Db(rusqlite::Error::SqliteFailure(e, _)) if e.code == DiskFull => {
+            "The disk is full. Free some space and try again".into()
+        }
+        VocabError::Db(rusqlite::Error::SqliteFailure(e, _))
+            if matches!(e.code, DatabaseCorrupt | NotADatabase) =>
+        {
+            "The database is corrupted. Run `vocabulator doctor` to check it".into()
+        }
+        _ => err.to_string(),
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MenuCounts {
+    pub words: i64,
+    pub unseen: i64,
+    pub marked: i64,
+    pub weak: i64,
+    pub leeches: i64,
+}
+
+impl MenuCounts {
+    pub fn load(conn: &Connection) -> Result<Self> {
+        Ok(Self {
+            words: queries::count_words(conn)?,
+            unseen: queries::count_unseen_words(conn)?,
+            marked: queries::count_marked_words(conn)?,
+            weak: queries::count_weak_words(conn, WEAK_MIN_SEEN, WEAK_LIMIT)?,
+            leeches: queries::count_leech_words(conn)?,
+        })
     }
 }
 
 #[derive(Debug)]
 pub struct App {
     pub conn: Connection,
+    pub db_path: String,
+    /// Named profile in use; `None` for the default one.
+    pub profile: Option<String>,
+    /// Directory the stats screen exports CSV files to.
+    pub export_dir: PathBuf,
+    pub leeches: LeechPolicy,
+    /// Marked words from other groups mixed into each Group session.
+    pub mix_marked: usize,
+    /// Keep readings off the practice screen until the definition shows.
+    pub hide_reading: bool,
+    /// How long a Quick Review runs before it stops.
+    pub quick_review: Duration,
+    /// Text-to-speech for read-aloud; `None` unless `tts_command` is set.
+    pub speaker: Option<Box<dyn Speaker>>,
+    /// How long read-aloud waits between a word and its definition.
+    pub read_aloud_pause: Duration,
+    pub keymap: KeyMap,
+    /// Colors and glyphs, from `--ascii`, `--no-color` and `NO_COLOR`.
+    pub theme: Theme,
     pub current_screen: Screen,
     pub menu_items: Vec<MenuAction>,
     pub selected: usize,
+    pub list_nav: ListNav,
+    /// Text typed after `/` to narrow the menu; `None` when not filtering.
+    pub menu_filter: Option<LineEdit>,
+    pub counts: MenuCounts,
+    /// Preview of the highlighted session entry, loaded when the highlight
+    /// moves rather than on every frame.
+    pub preview: Option<SessionPreview>,
+    /// Entry `preview` was loaded for.
+    preview_for: Option<usize>,
     pub should_quit: bool,
+    /// Nothing is written to the database: sessions run in memory and
+    /// other writes are refused with a toast.
+    pub read_only: bool,
+    /// Something changed since the last draw.
+    pub dirty: bool,
+    /// Practice screen shows only the word and definition.
+    pub focus_mode: bool,
     pub session: Option<Session>,
+    last_autosave: Instant,
+    /// `queries::data_version` as of the last check.
+    data_version: i64,
+    last_data_check: Instant,
     pub error: Option<String>,
+    pub status: Option<String>,
+    pub toast: Option<Toast>,
+    /// Dialog drawn over the screen; takes keys before the screen does.
+    pub modal: Option<Modal>,
+    /// Unclosed session offered for recovery by the open modal.
+    pub pending_resume: Option<SessionSnapshot>,
+    pub clipboard: Box<dyn Clipboard>,
+    pub clock: Box<dyn Clock>,
+    pub splash: SplashState,
+    pub stats: StatsState,
+    pub review_log: ReviewLogState,
 }
 
 impl App {
-    pub fn new(conn: Connection) -> Self {
-        Self {
+    pub fn new(conn: Connection, keymap: KeyMap) -> Self {
+        let counts = MenuCounts::load(&conn).unwrap_or_default();
+        let data_version = queries::data_version(&conn).unwrap_or_default();
+        let mut app = Self {
             conn,
+            db_path: String::new(),
+            profile: None,
+            export_dir: PathBuf::from("."),
+            leeches: LeechPolicy::default(),
+            mix_marked: 0,
+            hide_reading: false,
+            quick_review: Duration::from_secs(60 * u64::from(session::QUICK_REVIEW_MINUTES)),
+            speaker: None,
+            read_aloud_pause: Duration::from_secs(u64::from(session::READ_ALOUD_PAUSE_SECS)),
+            keymap,
+            theme: Theme::default(),
             current_screen: Screen::Menu,
             menu_items: vec![
                 MenuAction::Session(Type::Group),
                 MenuAction::Session(Type::Marked),
                 MenuAction::Session(Type::Weak),
+                MenuAction::Session(Type::Leech),
+                MenuAction::Session(Type::Quick),
+                MenuAction::Stats,
                 MenuAction::Exit,
             ],
             selected: 0,
+            list_nav: ListNav::default(),
+            menu_filter: None,
+            counts,
+            preview: None,
+            preview_for: None,
             should_quit: false,
+            read_only: false,
+            dirty: true,
+            focus_mode: false,
             session: None,
+            last_autosave: Instant::now(),
+            data_version,
+            last_data_check: Instant::now(),
             error: None,
+            status: None,
+            toast: None,
+            modal: None,
+            pending_resume: None,
+            clipboard: Box::new(SystemClipboard::default()),
+            clock: Box::new(SystemClock),
+            splash: SplashState::default(),
+            stats: StatsState::default(),
+            review_log: ReviewLogState::default(),
+        };
+        app.update_preview();
+        app
+    }
+
+    /// Opens the database at `path` and starts on the splash screen when it
+    /// holds no words yet. `read_only` opens it without write access, e.g.
+    /// when another instance owns it; nothing is offered for recovery then.
+    pub fn open(path: &str, keymap: KeyMap, read_only: bool) -> Result<Self> {
+        let conn = if read_only {
+            db::open_read_only(path)?
+        } else {
+            init_db(path)?
+        };
+        let mut app = Self::new(conn, keymap);
+        app.db_path = std::path::absolute(path)?.display().to_string();
+        app.read_only = read_only;
+        if read_only {
+            app.status = Some("Read-only: grades, marks and progress aren't saved".into());
+        } else if app.counts.words == 0 {
+            app.current_screen = Screen::Splash;
+        } else {
+            app.offer_recovery();
+        }
+        Ok(app)
+    }
+
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.menu_items[index].disabled_reason(self).is_none()
+    }
+
+    /// Moves the selection, skipping entries that are currently disabled.
+    pub fn navigate(&mut self, mv: NavMove) {
+        let len = self.menu_items.len();
+        let forward = matches!(mv, NavMove::Down(_) | NavMove::Top);
+        let mut target = list_nav::apply(self.selected, len, mv);
+
+        for _ in 0..len {
+            if self.is_enabled(target) {
+                break;
+            }
+            target = if forward {
+                (target + 1) % len
+            } else {
+                (target + len - 1) % len
+            };
+        }
+
+        self.selected = target;
+    }
+
+    pub fn refresh_counts(&mut self) {
+        match MenuCounts::load(&self.conn) {
+            Ok(counts) => self.counts = counts,
+            Err(e) => {
+                tracing::error!("loading menu counts failed: {e}");
+                self.error = Some(e.to_string());
+            }
+        }
+        self.refresh_preview();
+    }
+
+    /// Loads the preview for the highlighted entry unless it is already
+    /// loaded.
+    pub fn update_preview(&mut self) {
+        if self.preview_for == Some(self.selected) {
+            return;
+        }
+        self.preview_for = Some(self.selected);
+        self.preview = match self.menu_items[self.selected] {
+            MenuAction::Session(session_type) if self.is_enabled(self.selected) => {
+                preview::load(&self.conn, session_type, self.mix_marked, self.quick_review)
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("loading session preview failed: {e}");
+                        None
+                    })
+            }
+            _ => None,
+        };
+    }
+
+    /// Reloads the preview, e.g. after the words behind it changed.
+    pub fn refresh_preview(&mut self) {
+        self.preview_for = None;
+        self.update_preview();
+    }
+
+    /// Switches to a freshly started session, or back to the menu with an
+    /// error when it can't be shown.
+    pub fn begin_session(&mut self, started: Result<(Session, Screen), VocabError>) {
+        let (mut session, screen) = match started {
+            Ok(started) => started,
+            Err(e) => {
+                self.return_to_menu();
+                tracing::warn!("session could not start: {e:?}");
+                self.error = Some(start_problem(&e, self.counts.words == 0));
+                return;
+            }
+        };
+
+        tracing::info!(
+            session_type = ?session.session_type,
+            words = session.words.len(),
+            index = session.index,
+            "session started"
+        );
+        if let Err(e) = session.load_history(&self.conn) {
+            tracing::error!("loading review history failed: {e}");
+            self.error = Some(e.to_string());
+        }
+        session.persist = !self.read_only;
+        if session.persist
+            && let Err(e) =
+                queries::open_session_record(&self.conn, session.session_type, self.clock.now())
+        {
+            tracing::warn!("recording session start failed: {e}");
+        }
+        if session.session_type == Type::Quick {
+            // A resumed Quick Review gets the whole time again.
+            session.deadline = Some(self.clock.now() + self.quick_review.as_secs() as i64);
+        }
+        session.unsaved = true;
+        self.session = Some(session);
+        self.current_screen = screen;
+        self.autosave(Instant::now());
+    }
+
+    pub fn return_to_menu(&mut self) {
+        self.current_screen = Screen::Menu;
+        self.refresh_counts();
+        self.close_snapshot();
+    }
+
+    /// Opens a dialog offering to resume a session that was never closed,
+    /// e.g. because the app crashed or the machine lost power.
+    pub fn offer_recovery(&mut self) {
+        // Whether or not it is resumed, the crashed session's record ends
+        // here; a resumed one gets a new record.
+        match queries::close_abandoned_sessions(&self.conn) {
+            Ok(0) => {}
+            Ok(closed) => tracing::info!(closed, "closed abandoned session records"),
+            Err(e) => tracing::warn!("closing abandoned session records failed: {e}"),
+        }
+        let snapshot = match queries::fetch_snapshot(&self.conn) {
+            Ok(Some(snapshot)) if !snapshot.closed_cleanly => snapshot,
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!("reading session snapshot failed: {e}");
+                return;
+            }
+        };
+
+        let body = vec![
+            format!(
+                "{} was left open at word {} of {}.",
+                snapshot.session_type.label(),
+                snapshot.index + 1,
+                snapshot.word_ids.len()
+            ),
+            String::new(),
+            "Resume where you left off? No discards it.".into(),
+        ];
+        self.modal = Some(Modal::new("Unfinished session", body, Buttons::YesNo).size(60, 30));
+        self.pending_resume = Some(snapshot);
+    }
+
+    fn resume(&mut self, mut snapshot: SessionSnapshot) {
+        // Grades committed after the snapshot was saved are already counted;
+        // pick up after the last of them rather than grading it again.
+        match queries::fetch_last_graded(&self.conn) {
+            Ok(Some(id)) => {
+                let skipped = snapshot.word_ids[snapshot.index.min(snapshot.word_ids.len())..]
+                    .iter()
+                    .position(|w| *w == id);
+                if let Some(pos) = skipped {
+                    snapshot.index += pos + 1;
+                    snapshot.show_definition = false;
+                    snapshot.graded = None;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("reading last graded word failed: {e}"),
+        }
+        if snapshot.index >= snapshot.word_ids.len() {
+            // Every word was graded; there is nothing left to resume.
+            self.close_snapshot();
+            return;
+        }
+
+        let started = session::snapshot_session(&self.conn, &snapshot);
+        self.begin_session(started);
+
+        if let Some(session) = &mut self.session {
+            session.show_definition = snapshot.show_definition;
+            session.graded = snapshot.graded;
+        }
+    }
+
+    /// Records that the session ended normally so recovery isn't offered,
+    /// and finishes its history record. Leaves an unanswered recovery offer
+    /// alone.
+    pub fn close_snapshot(&self) {
+        if self.read_only || self.pending_resume.is_some() {
+            return;
+        }
+        if let Err(e) = queries::close_snapshot(&self.conn) {
+            tracing::warn!("closing session snapshot failed: {e}");
+        }
+        if let Err(e) = queries::finish_session_record(&self.conn, self.clock.now()) {
+            tracing::warn!("recording session end failed: {e}");
+        }
+    }
+
+    pub fn notify(&mut self, message: impl Into<String>, is_error: bool) {
+        self.toast = Some(Toast::new(message, is_error, Instant::now()));
+        self.dirty = true;
+    }
+
+    /// In read-only mode, explains with a toast that `action` would write
+    /// to the database and returns true so the caller skips it.
+    pub fn refuse_write(&mut self, action: &str) -> bool {
+        if self.read_only {
+            self.notify(format!("Read-only mode: can't {action}"), true);
+        }
+        self.read_only
+    }
+
+    /// Applies one event from the run loop. Keys and resizes always mark the
+    /// screen dirty; ticks only when a timer fired.
+    pub fn handle(&mut self, event: AppEvent) {
+        match event {
+            // Releases, and Repeats outside list navigation, are not input.
+            AppEvent::Key(key) if !self.keymap.accepts(key) => {}
+            AppEvent::Key(key) => {
+                self.handle_key(key);
+                self.update_preview();
+                self.dirty = true;
+            }
+            AppEvent::Resize(..) => self.dirty = true,
+            AppEvent::Tick => self.tick(Instant::now()),
+            AppEvent::Notify(message) => self.notify(message, false),
+            AppEvent::Terminate => self.shutdown(),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.shutdown();
+            return;
+        }
+
+        if key.code == KeyCode::Char('v')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+            && self.modal.is_none()
+            && self.focused_input().is_some()
+        {
+            // A failed read just means there's nothing to paste.
+            if let Ok(text) = self.clipboard.get_text()
+                && let Some(input) = self.focused_input()
+            {
+                input.paste(&text);
+            }
+            return;
+        }
+
+        if let Some(modal) = &self.modal {
+            let result = modal.handle_key(key);
+            if result == ModalResult::Open {
+                return;
+            }
+            self.modal = None;
+            if let Some(snapshot) = self.pending_resume.take() {
+                if result == ModalResult::Accepted {
+                    self.resume(snapshot);
+                } else {
+                    self.close_snapshot();
+                }
+            }
+            if let Some(word_id) = self.review_log.pending_delete.take()
+                && result == ModalResult::Accepted
+            {
+                review_log::delete(self, word_id, Instant::now());
+            }
+            return;
+        }
+
+        // The key that ends a pause does nothing else.
+        if matches!(self.current_screen, Screen::Practice | Screen::Test)
+            && let Some(session) = &mut self.session
+            && session.paused_at.is_some()
+        {
+            session.unpause(self.clock.now());
+            return;
+        }
+
+        match self.current_screen {
+            Screen::Splash => splash::handle_event(self, key),
+            Screen::Menu => menu::handle_event(self, key),
+            Screen::Practice => practice::handle_event(self, key),
+            Screen::Test => test::handle_event(self, key),
+            Screen::Stats => stats::handle_event(self, key),
+            Screen::ReviewLog => review_log::handle_event(self, key),
+        }
+    }
+
+    /// Text input that currently has the keyboard, if any.
+    fn focused_input(&mut self) -> Option<&mut LineEdit> {
+        match self.current_screen {
+            Screen::Splash => self.splash.path_input.as_mut(),
+            Screen::Menu => self.menu_filter.as_mut(),
+            Screen::Stats => self.stats.renaming.as_mut(),
+            Screen::Test => self
+                .session
+                .as_mut()
+                .filter(|s| s.insert_mode)
+                .map(|s| &mut s.input_buffer),
+            _ => None,
+        }
+    }
+
+    /// Drops the toast once it has been on screen long enough, stops a
+    /// Quick Review that ran out of time, takes the next read-aloud step,
+    /// picks up words added from outside and saves a modified session every
+    /// `AUTOSAVE_INTERVAL`.
+    pub fn tick(&mut self, now: Instant) {
+        if self.toast.as_ref().is_some_and(|t| t.expired(now)) {
+            self.toast = None;
+            self.dirty = true;
         }
+        if matches!(self.current_screen, Screen::Practice | Screen::Test)
+            && self
+                .session
+                .as_ref()
+                .is_some_and(|s| s.out_of_time(self.clock.now()))
+        {
+            self.stop_quick_review();
+        }
+        if self.current_screen == Screen::Practice
+            && self
+                .session
+                .as_ref()
+                .is_some_and(|s| s.read_aloud_due(self.clock.now()))
+        {
+            self.step_read_aloud();
+        }
+        if now.duration_since(self.last_data_check) >= DATA_CHECK_INTERVAL {
+            self.check_data_version(now);
+        }
+        if now.duration_since(self.last_autosave) >= AUTOSAVE_INTERVAL {
+            self.autosave(now);
+        }
+    }
+
+    /// Refreshes the menu counts and the stats table once another process,
+    /// e.g. `vocabulator seed`, has added words. An open session keeps the
+    /// words it started with and counts the new ones for its summary.
+    fn check_data_version(&mut self, now: Instant) {
+        self.last_data_check = now;
+        let version = match queries::data_version(&self.conn) {
+            Ok(version) => version,
+            Err(e) => {
+                tracing::warn!("checking for new words failed: {e}");
+                return;
+            }
+        };
+        if version == self.data_version {
+            return;
+        }
+        self.data_version = version;
+
+        let before = self.counts.words;
+        self.refresh_counts();
+        if self.current_screen == Screen::Stats && self.stats.renaming.is_none() {
+            stats::reload(self);
+        }
+        let added = self.counts.words - before;
+        tracing::info!(version, added, "database changed outside the app");
+        if added > 0 {
+            if let Some(session) = &mut self.session {
+                session.words_arrived += added;
+            }
+            self.notify(
+                match added {
+                    1 => "Database updated: +1 word".into(),
+                    n => format!("Database updated: +{n} words"),
+                },
+                false,
+            );
+        }
+        self.dirty = true;
+    }
+
+    /// Flips the mark on the current word and saves straight away so the
+    /// bookmark survives a crash.
+    pub fn toggle_mark(&mut self) {
+        if self.refuse_write("mark words") {
+            return;
+        }
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        let Some(word) = session.current_mut() else {
+            return;
+        };
+        word.marked = !word.marked;
+        session.unsaved = true;
+        self.autosave(Instant::now());
+    }
+
+    /// Writes the current word's mark and the session position, unless
+    /// nothing changed since the last save.
+    fn autosave(&mut self, now: Instant) {
+        self.last_autosave = now;
+        if !self
+            .session
+            .as_ref()
+            .is_some_and(|s| s.unsaved && s.persist)
+        {
+            return;
+        }
+
+        match self.save_session() {
+            Ok(()) => {
+                if let Some(session) = &mut self.session {
+                    session.unsaved = false;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("autosave failed: {e}");
+                self.notify(format!("Autosave failed: {e}"), true);
+            }
+        }
+    }
+
+    fn save_session(&self) -> Result<()> {
+        let Some(session) = &self.session else {
+            return Ok(());
+        };
+        if let Some(word) = session.current() {
+            queries::set_marked(&self.conn, word.id, word.marked)?;
+        }
+        if matches!(self.current_screen, Screen::Practice | Screen::Test) {
+            queries::save_snapshot(&self.conn, &session.snapshot(self.current_screen))?;
+        }
+        self.save_position()
+    }
+
+    /// Copies the current word, and its definition when `with_definition`,
+    /// to the clipboard. Test answers stay hidden until graded.
+    pub fn copy_current(&mut self, with_definition: bool) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        if self.current_screen == Screen::Test && session.graded.is_none() {
+            self.notify("Answer first to copy this word", true);
+            return;
+        }
+
+        let Some(word) = session.current() else {
+            return;
+        };
+        let (text, what) = if with_definition {
+            (
+                format!("{}\n{}", word.word, word.definition),
+                "word and definition",
+            )
+        } else {
+            (word.word.clone(), "word")
+        };
+
+        match self.clipboard.set_text(&text) {
+            Ok(()) => self.notify(format!("Copied {what}: {}", word.word), false),
+            Err(e) => self.notify(format!("Copy failed: {e}"), true),
+        }
+    }
+
+    /// Quits the way the normal exit path would: commits an answer that was
+    /// already graded, remembers the position in a Group session, and sets
+    /// `should_quit`. Shared by Ctrl+C and the signal handler.
+    pub fn shutdown(&mut self) {
+        let graded = self
+            .session
+            .as_ref()
+            .is_some_and(|s| s.show_definition && s.graded.is_some());
+
+        let saved = if graded {
+            actions::handle_enter(self)
+        } else {
+            self.save_position()
+        };
+        if let Err(e) = saved {
+            tracing::error!("saving on shutdown failed: {e}");
+            self.error = Some(e.to_string());
+        }
+
+        self.should_quit = true;
+    }
+
+    /// Starts or stops reading the practice session aloud: the word is
+    /// spoken, then after `read_aloud_pause` its definition is spoken and
+    /// shown, then the next word, with nothing graded.
+    pub fn toggle_read_aloud(&mut self) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        if session.read_aloud.take().is_some() {
+            self.notify("Read-aloud stopped", false);
+            return;
+        }
+        if self.speaker.is_none() {
+            self.notify(
+                "Read-aloud needs a speech command: set tts_command in the config",
+                true,
+            );
+            return;
+        }
+        session.show_definition = false;
+        session.graded = None;
+        let Some(word) = session.current() else {
+            return;
+        };
+        let text = word.word.clone();
+        self.read_aloud(&text);
+    }
+
+    /// Shows and speaks the definition, or moves on to the next word and
+    /// speaks that. Reading past the last word ends the session.
+    fn step_read_aloud(&mut self) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        if !session.show_definition {
+            session.show_definition = true;
+        } else if session.advance() {
+            let count = session.words.len();
+            self.session = None;
+            self.return_to_menu();
+            self.status = Some(match count {
+                1 => "Read 1 word aloud".into(),
+                n => format!("Read {n} words aloud"),
+            });
+            self.dirty = true;
+            return;
+        } else if let Err(e) = session.load_history(&self.conn) {
+            tracing::warn!("loading word details failed: {e}");
+        }
+        let Some(word) = session.current() else {
+            return;
+        };
+        let text = if session.show_definition {
+            word.definition.clone()
+        } else {
+            word.word.clone()
+        };
+        self.read_aloud(&text);
+        self.dirty = true;
     }
 
-    pub fn next(&mut self) {
-        self.selected = (self.selected + 1) % self.menu_items.len();
+    /// Speaks `text` and schedules the next read-aloud step, or stops
+    /// reading aloud when it can't be spoken.
+    fn read_aloud(&mut self, text: &str) {
+        let now = self.clock.now();
+        let (Some(session), Some(speaker)) = (&mut self.session, &mut self.speaker) else {
+            return;
+        };
+        match speaker.say(text) {
+            Ok(()) => {
+                session.read_aloud =
+                    Some(now - session.paused_secs + self.read_aloud_pause.as_secs() as i64);
+            }
+            Err(e) => {
+                tracing::warn!("speaking failed: {e}");
+                session.read_aloud = None;
+                self.notify(format!("Speaking failed: {e}"), true);
+            }
+        }
+    }
+
+    /// Ends a Quick Review mid-list: an answer already graded is committed,
+    /// an unanswered word is left as it was. The menu then shows what the
+    /// session got through.
+    fn stop_quick_review(&mut self) {
+        let graded = self
+            .session
+            .as_ref()
+            .is_some_and(|s| s.show_definition && s.graded.is_some());
+        if graded && let Err(e) = actions::handle_enter(self) {
+            tracing::error!("committing the last grade failed: {e}");
+            self.error = Some(e.to_string());
+        }
+        self.return_to_menu();
+        self.dirty = true;
+
+        let summary = match queries::fetch_recent_sessions(&self.conn, 1) {
+            Ok(records) if !self.read_only => {
+                records.first().map(|record| match record.words_reviewed {
+                    1 => format!("1 review, {} correct", record.correct),
+                    n => format!("{n} reviews, {} correct", record.correct),
+                })
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("reading the session record failed: {e}");
+                None
+            }
+        };
+        let status = match summary {
+            Some(summary) => format!("Time's up: {summary}"),
+            None => "Time's up".into(),
+        };
+        self.status = Some(match self.arrivals_note() {
+            Some(note) => format!("{status}. {note}"),
+            None => status,
+        });
     }
 
-    pub fn previous(&mut self) {
-        if self.selected == 0 {
-            self.selected = self.menu_items.len() - 1;
+    /// Says how many words were added from outside during the session, if
+    /// any were.
+    fn arrivals_note(&self) -> Option<String> {
+        match self.session.as_ref()?.words_arrived {
+            0 => None,
+            1 => Some("1 new word arrived during the session".into()),
+            n => Some(format!("{n} new words arrived during the session")),
+        }
+    }
+
+    /// After a Group Test, lists the words answered wrongly with what was
+    /// typed for each, from the answers stored with the session's reviews.
+    pub fn show_test_summary(&mut self) {
+        let loaded = (|| -> Result<_> {
+            let Some(record) = queries::fetch_recent_sessions(&self.conn, 1)?.pop() else {
+                return Ok(None);
+            };
+            let misspelled = queries::fetch_misspelled(&self.conn, record.id)?;
+            Ok(Some((record, misspelled)))
+        })();
+        let (record, misspelled) = match loaded {
+            Ok(Some(loaded)) if !self.read_only => loaded,
+            Ok(_) => {
+                self.status = Some("Test finished".into());
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("reading the test answers failed: {e}");
+                self.status = Some("Test finished".into());
+                return;
+            }
+        };
+
+        let mut body = vec![
+            format!("{} of {} right.", record.correct, record.words_reviewed),
+            String::new(),
+        ];
+        if misspelled.is_empty() {
+            body.push("No misspellings.".into());
         } else {
-            self.selected -= 1;
+            body.push("Misspelled:".into());
+            body.extend(misspelled.iter().map(|(word, answer)| match answer.trim() {
+                "" => format!("{word}: left blank"),
+                answer => format!("{word}: you typed \"{answer}\""),
+            }));
+        }
+        if let Some(note) = self.arrivals_note() {
+            body.extend([String::new(), note]);
+        }
+        self.modal = Some(Modal::new("Test finished", body, Buttons::AnyKey).size(60, 50));
+    }
+
+    fn save_position(&self) -> Result<()> {
+        let Some(session) = &self.session else {
+            return Ok(());
+        };
+        if !session.persist
+            || session.session_type != Type::Group
+            || self.current_screen == Screen::Menu
+        {
+            return Ok(());
+        }
+        match session.words.first() {
+            Some(first) => Ok(progress::save_progress(
+                &self.conn,
+                (
+                    self.current_screen,
+                    first.group_id,
+                    session.group_index(session.index),
+                ),
+            )?),
+            None => Ok(()),
         }
     }
 
     pub fn select(&mut self) {
-        match self.menu_items[self.selected] {
-            MenuAction::Exit => self.should_quit = true,
-            _ => {}
+        if self.menu_items[self.selected] == MenuAction::Exit {
+            self.should_quit = true;
         }
     }
 }
 
+#[cfg(test)]
+impl App {
+    /// App over a fresh in-memory database with the full schema.
+    pub fn new_test() -> Self {
+        Self::new(db::test_conn(), KeyMap::default())
+    }
+
+    /// App with `words` (word, definition, group) inserted in order, so
+    /// the first gets id 1, and a Group session started on the lowest
+    /// group.
+    pub fn new_test_seeded(words: &[(&str, &str, i32)]) -> Self {
+        let conn = db::test_conn();
+        for (word, definition, group_id) in words {
+            conn.execute(
+                "INSERT INTO words(word,definition,group_id) VALUES(?1,?2,?3)",
+                rusqlite::params![word, definition, group_id],
+            )
+            .unwrap();
+        }
+        let mut app = Self::new(conn, KeyMap::default());
+        let started =
+            session::start_session(&app.conn, &SystemClock, Type::Group, 0, Duration::ZERO);
+        app.begin_session(started);
+        assert!(app.session.is_some(), "{:?}", app.error);
+        app
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clipboard::MockClipboard;
+    use crate::core::clock::MockClock;
+    use crate::db::store::WordStore;
+    use crate::db::test_conn;
+    use crate::logging::testing::Captured;
+    use crate::speech::MockSpeaker;
+    use crossterm::event::KeyEventKind;
+
+    fn app() -> App {
+        let mut app = App::new_test();
+        app.counts = MenuCounts {
+            words: 1,
+            unseen: 1,
+            marked: 1,
+            weak: 1,
+            leeches: 1,
+        };
+        app
+    }
 
     #[test]
     fn test_navigation_wraps_forward() {
-        let mut app = App::new(Connection::open_in_memory().unwrap());
+        let mut app = app();
         app.selected = app.menu_items.len() - 1;
-        app.next();
+        app.navigate(NavMove::Down(1));
         assert_eq!(app.selected, 0);
     }
 
     #[test]
     fn test_navigation_wraps_backward() {
-        let mut app = App::new(Connection::open_in_memory().unwrap());
+        let mut app = app();
         app.selected = 0;
-        app.previous();
+        app.navigate(NavMove::Up(1));
         assert_eq!(app.selected, app.menu_items.len() - 1);
     }
 
     #[test]
     fn test_exit_sets_flag() {
-        let mut app = App::new(Connection::open_in_memory().unwrap());
+        let mut app = app();
         app.selected = app
             .menu_items
             .iter()
@@ -103,4 +1022,829 @@ mod tests {
         app.select();
         assert!(app.should_quit);
     }
+
+    #[test]
+    fn test_counted_navigation_clamps() {
+        let mut app = app();
+        app.navigate(NavMove::Down(10));
+        assert_eq!(app.selected, app.menu_items.len() - 1);
+        app.navigate(NavMove::Top);
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn test_navigation_skips_disabled() {
+        let mut app = app();
+        app.counts.marked = 0;
+
+        app.navigate(NavMove::Down(1));
+        assert_eq!(
+            app.menu_items[app.selected],
+            MenuAction::Session(Type::Weak)
+        );
+
+        app.navigate(NavMove::Up(1));
+        assert_eq!(
+            app.menu_items[app.selected],
+            MenuAction::Session(Type::Group)
+        );
+    }
+
+    #[test]
+    fn test_labels_show_counts() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(word,definition,group_id,marked) VALUES('a','b',1,1)",
+            [],
+        )
+        .unwrap();
+        let app = App::new(conn, KeyMap::default());
+
+        let labels: Vec<String> = app.menu_items.iter().map(|i| i.label(&app)).collect();
+        assert_eq!(labels[0], "Continue Learning (1 new)");
+        assert_eq!(labels[1], "Review Marks (1)");
+        assert_eq!(labels[2], "Revise Weak (0)");
+        assert!(!app.is_enabled(2));
+    }
+
+    #[test]
+    fn test_start_problem_messages() {
+        let mut app = App::new_test();
+        app.begin_session(session::start_session(
+            &app.conn,
+            &SystemClock,
+            Type::Group,
+            0,
+            Duration::ZERO,
+        ));
+        assert_eq!(app.error.as_deref(), Some(EMPTY_DATABASE));
+
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(word,definition,group_id) VALUES('a','b',1)",
+            [],
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        app.begin_session(session::start_session(
+            &app.conn,
+            &SystemClock,
+            Type::Marked,
+            0,
+            Duration::ZERO,
+        ));
+        assert_eq!(
+            app.error.as_deref(),
+            Some("The marked list has no words yet")
+        );
+
+        app.begin_session(Err(VocabError::ProgressOutOfRange { index: 5, len: 1 }));
+        let err = app.error.as_deref().unwrap();
+        assert!(err.contains("vocabulator doctor"));
+        assert!(!err.contains("corrupted"));
+        assert_eq!(app.current_screen, Screen::Menu);
+
+        let corrupt = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+            None,
+        );
+        app.begin_session(Err(corrupt.into()));
+        assert!(app.error.as_deref().unwrap().contains("corrupted"));
+    }
+
+    #[test]
+    fn test_shutdown_commits_graded_answer() {
+        let mut app = App::new_test_seeded(&[("a", "b", 1), ("c", "d", 1)]);
+        let session = app.session.as_mut().unwrap();
+        session.show_definition = true;
+        session.graded = Some(true);
+
+        app.shutdown();
+
+        assert!(app.should_quit);
+        let (screen, group, index) = queries::fetch_progress(&app.conn).unwrap();
+        assert_eq!((screen, group, index), (Screen::Practice, 1, 1));
+        let seen: i64 = app
+            .conn
+            .query_row("SELECT COUNT(*) FROM words WHERE times_seen>0", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn test_shutdown_discards_ungraded_answer() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES('a','b',2), ('c','d',2)",
+        )
+        .unwrap();
+        queries::save_progress(&conn, (Screen::Test, 2, 1)).unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        app.begin_session(crate::core::session::start_session(
+            &app.conn,
+            &SystemClock,
+            Type::Group,
+            0,
+            Duration::ZERO,
+        ));
+        app.session.as_mut().unwrap().input_buffer.insert('x');
+
+        app.shutdown();
+
+        assert!(app.should_quit);
+        assert_eq!(
+            queries::fetch_progress(&app.conn).unwrap(),
+            (Screen::Test, 2, 1)
+        );
+        assert_eq!(queries::count_unseen_words(&app.conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_toast_expires() {
+        let mut app = app();
+        app.notify("Copied word: abate", false);
+        let shown = app.toast.as_ref().unwrap().expires;
+
+        app.tick(shown - std::time::Duration::from_millis(1));
+        assert!(app.toast.is_some());
+        app.tick(shown);
+        assert!(app.toast.is_none());
+    }
+
+    fn group_app() -> App {
+        App::new_test_seeded(&[("a", "b", 1), ("c", "d", 1)])
+    }
+
+    #[test]
+    fn test_seeded_fixture_persists_grades() {
+        let mut app = App::new_test_seeded(&[("abate", "lessen", 1), ("abound", "plentiful", 1)]);
+        assert_eq!(app.current_screen, Screen::Practice);
+
+        press(&mut app, 's');
+        press(&mut app, 'y');
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Enter,
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        )));
+
+        let word = WordStore::new(&app.conn).fetch(1).unwrap().unwrap();
+        assert_eq!((word.times_seen, word.success_count), (1, 1));
+        assert_eq!(
+            queries::fetch_progress(&app.conn).unwrap(),
+            (Screen::Practice, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_session_recorded_from_start_to_menu() {
+        let mut app = group_app();
+        press(&mut app, 's');
+        press(&mut app, 'y');
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Enter,
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        )));
+        app.return_to_menu();
+
+        let sessions = queries::fetch_recent_sessions(&app.conn, 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_type, Type::Group);
+        assert_eq!((sessions[0].words_reviewed, sessions[0].correct), (1, 1));
+    }
+
+    fn saves_during(app: &mut App, ticks: &[u64]) -> usize {
+        let start = app.last_autosave;
+        let log = Captured::default();
+        log.run(|| {
+            for secs in ticks {
+                app.tick(start + Duration::from_secs(*secs));
+            }
+        });
+        log.text().matches("saving progress").count()
+    }
+
+    #[test]
+    fn test_autosave_skips_unmodified_session() {
+        let mut app = group_app();
+        assert_eq!(saves_during(&mut app, &[30, 60, 90]), 0);
+    }
+
+    #[test]
+    fn test_autosave_modified_session_once() {
+        let mut app = group_app();
+        app.session.as_mut().unwrap().unsaved = true;
+
+        assert_eq!(saves_during(&mut app, &[10, 29]), 0);
+        assert_eq!(saves_during(&mut app, &[30, 60, 90]), 1);
+        assert!(!app.session.as_ref().unwrap().unsaved);
+    }
+
+    #[test]
+    fn test_mark_saves_immediately() {
+        let mut app = group_app();
+
+        let log = Captured::default();
+        log.run(|| app.toggle_mark());
+
+        assert_eq!(log.text().matches("saving progress").count(), 1);
+        let marked: i64 = app
+            .conn
+            .query_row("SELECT marked FROM words WHERE id=1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(marked, 1);
+        assert!(!app.session.as_ref().unwrap().unsaved);
+    }
+
+    fn app_with_snapshot(closed_cleanly: bool) -> App {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES('a','b',1), ('c','d',1)",
+        )
+        .unwrap();
+        let snapshot = SessionSnapshot {
+            session_type: Type::Marked,
+            mode: Screen::Test,
+            word_ids: vec![2, 1],
+            index: 1,
+            show_definition: true,
+            graded: Some(false),
+            closed_cleanly,
+        };
+        queries::save_snapshot(&conn, &snapshot).unwrap();
+        conn.execute("UPDATE words SET definition='edited' WHERE id=1", [])
+            .unwrap();
+
+        let mut app = App::new(conn, KeyMap::default());
+        app.offer_recovery();
+        app
+    }
+
+    fn press(app: &mut App, c: char) {
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Char(c),
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        )));
+    }
+
+    #[test]
+    fn test_clean_exit_offers_no_recovery() {
+        let app = app_with_snapshot(true);
+        assert!(app.modal.is_none());
+        assert!(app.pending_resume.is_none());
+    }
+
+    #[test]
+    fn test_recovery_resumes_session() {
+        let mut app = app_with_snapshot(false);
+        assert_eq!(app.modal.as_ref().unwrap().buttons, Buttons::YesNo);
+
+        press(&mut app, 'y');
+
+        assert!(app.modal.is_none());
+        assert_eq!(app.current_screen, Screen::Test);
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.session_type, Type::Marked);
+        assert_eq!(session.index, 1);
+        assert!(session.show_definition);
+        assert_eq!(session.graded, Some(false));
+        assert_eq!(session.current().unwrap().definition, "edited");
+
+        let snapshot = queries::fetch_snapshot(&app.conn).unwrap().unwrap();
+        assert!(!snapshot.closed_cleanly);
+    }
+
+    #[test]
+    fn test_recovery_skips_words_graded_after_snapshot() {
+        let mut app = app_with_snapshot(false);
+        let mut word = WordStore::new(&app.conn).fetch(2).unwrap().unwrap();
+        progress::record_grade(
+            &app.conn,
+            &SystemClock,
+            LeechPolicy::default(),
+            &mut word,
+            true,
+            Screen::Test,
+            None,
+        )
+        .unwrap();
+        let mut word = WordStore::new(&app.conn).fetch(1).unwrap().unwrap();
+        progress::record_grade(
+            &app.conn,
+            &SystemClock,
+            LeechPolicy::default(),
+            &mut word,
+            false,
+            Screen::Test,
+            None,
+        )
+        .unwrap();
+
+        press(&mut app, 'y');
+
+        // Word 1 was the last in the snapshot, so the session was done.
+        assert!(app.session.is_none());
+        assert!(
+            queries::fetch_snapshot(&app.conn)
+                .unwrap()
+                .unwrap()
+                .closed_cleanly
+        );
+    }
+
+    #[test]
+    fn test_recovery_ignores_grades_before_snapshot() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES('a','b',1), ('c','d',1), ('e','f',1)",
+        )
+        .unwrap();
+        let mut word = WordStore::new(&conn).fetch(2).unwrap().unwrap();
+        progress::record_grade(
+            &conn,
+            &SystemClock,
+            LeechPolicy::default(),
+            &mut word,
+            true,
+            Screen::Practice,
+            None,
+        )
+        .unwrap();
+        let snapshot = SessionSnapshot {
+            session_type: Type::Marked,
+            mode: Screen::Practice,
+            word_ids: vec![1, 2, 3],
+            index: 0,
+            show_definition: false,
+            graded: None,
+            closed_cleanly: false,
+        };
+        queries::save_snapshot(&conn, &snapshot).unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        app.offer_recovery();
+
+        press(&mut app, 'y');
+        assert_eq!(app.session.as_ref().unwrap().index, 0);
+
+        // A grade landing after the snapshot moves the resume point past it.
+        app.session = None;
+        let mut word = WordStore::new(&app.conn).fetch(2).unwrap().unwrap();
+        progress::record_grade(
+            &app.conn,
+            &SystemClock,
+            LeechPolicy::default(),
+            &mut word,
+            true,
+            Screen::Practice,
+            None,
+        )
+        .unwrap();
+        app.offer_recovery();
+        press(&mut app, 'y');
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.index, 2);
+        assert!(session.graded.is_none());
+    }
+
+    #[test]
+    fn test_recovery_discard_closes_snapshot() {
+        let mut app = app_with_snapshot(false);
+
+        press(&mut app, 'n');
+
+        assert!(app.modal.is_none());
+        assert!(app.session.is_none());
+        assert_eq!(app.current_screen, Screen::Menu);
+        let snapshot = queries::fetch_snapshot(&app.conn).unwrap().unwrap();
+        assert!(snapshot.closed_cleanly);
+    }
+
+    /// App on the menu with three seen words and the clock at `clock`,
+    /// starting a Quick Review from the menu entry.
+    fn quick_review(clock: &MockClock) -> App {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id,times_seen,success_count,last_seen) VALUES
+                 (1,'abate','lessen',1,3,1,100), (2,'wane','decrease',1,1,1,200),
+                 (3,'laud','praise',2,1,1,300), (4,'ebb','recede',2,0,0,NULL)",
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        app.clock = Box::new(clock.clone());
+        app.selected = app
+            .menu_items
+            .iter()
+            .position(|item| *item == MenuAction::Session(Type::Quick))
+            .unwrap();
+        assert_eq!(
+            app.menu_items[app.selected].label(&app),
+            "Quick Review (5 min)"
+        );
+        enter(&mut app);
+
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.session_type, Type::Quick);
+        assert_eq!(session.words.len(), 3);
+        assert_eq!(session.deadline, Some(1_000_300));
+        assert_eq!(session.current().unwrap().id, 1);
+        app
+    }
+
+    fn seen(app: &App, id: i32) -> (i64, i64) {
+        app.conn
+            .query_row(
+                "SELECT times_seen, (SELECT COUNT(*) FROM reviews WHERE word_id=?1)
+                 FROM words WHERE id=?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_quick_review_commits_graded_word_at_cutoff() {
+        let clock = MockClock::at(1_000_000);
+        let mut app = quick_review(&clock);
+        press(&mut app, 's');
+        press(&mut app, 'y');
+
+        clock.set(1_000_299);
+        app.tick(Instant::now());
+        assert_eq!(app.current_screen, Screen::Practice);
+
+        clock.set(1_000_300);
+        app.tick(Instant::now());
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert_eq!(seen(&app, 1), (4, 1));
+        assert_eq!(seen(&app, 2), (1, 0));
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Time's up: 1 review, 1 correct")
+        );
+        let snapshot = queries::fetch_snapshot(&app.conn).unwrap().unwrap();
+        assert!(snapshot.closed_cleanly);
+
+        // Back on the menu, later ticks don't stop it again.
+        clock.set(1_000_900);
+        app.tick(Instant::now());
+        assert_eq!(seen(&app, 2), (1, 0));
+        assert!(app.status.as_deref().unwrap().contains("1 review"));
+    }
+
+    #[test]
+    fn test_quick_review_leaves_unanswered_word_at_cutoff() {
+        let clock = MockClock::at(1_000_000);
+        let mut app = quick_review(&clock);
+        press(&mut app, 's');
+
+        clock.set(1_000_400);
+        app.tick(Instant::now());
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert_eq!(seen(&app, 1), (3, 0));
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Time's up: 0 reviews, 0 correct")
+        );
+    }
+
+    #[test]
+    fn test_paused_time_does_not_count() {
+        let clock = MockClock::at(1_000_000);
+        let mut app = quick_review(&clock);
+        press(&mut app, ' ');
+
+        clock.set(1_000_400);
+        app.tick(Instant::now());
+        assert_eq!(app.current_screen, Screen::Practice);
+
+        // 400 seconds paused move the cutoff to 1_000_700.
+        press(&mut app, 'x');
+        clock.set(1_000_650);
+        app.tick(Instant::now());
+        assert_eq!(app.current_screen, Screen::Practice);
+        clock.set(1_000_700);
+        app.tick(Instant::now());
+        assert_eq!(app.current_screen, Screen::Menu);
+    }
+
+    #[test]
+    fn test_pause_swallows_keys() {
+        let mut app = App::new_test_seeded(&[("abate", "lessen", 1), ("wane", "decrease", 1)]);
+        press(&mut app, '.');
+        assert!(app.session.as_ref().unwrap().paused_at.is_some());
+        let screen = crate::ui::testing::render_to_string(&app, 60, 20);
+        assert!(screen.contains("Paused — press any key"), "{screen}");
+        assert!(!screen.contains("abate"), "{screen}");
+
+        // The key that resumes is not a Show or a Quit.
+        press(&mut app, 's');
+        let session = app.session.as_ref().unwrap();
+        assert!(session.paused_at.is_none());
+        assert!(!session.show_definition);
+        press(&mut app, ' ');
+        press(&mut app, 'q');
+        assert_eq!(app.current_screen, Screen::Practice);
+        assert_eq!(app.session.as_ref().unwrap().index, 0);
+
+        press(&mut app, 's');
+        assert!(app.session.as_ref().unwrap().show_definition);
+    }
+
+    #[test]
+    fn test_quick_review_needs_seen_words() {
+        let mut app = App::new_test_seeded(&[("abate", "lessen", 1)]);
+        app.return_to_menu();
+        let quick = MenuAction::Session(Type::Quick);
+        assert!(quick.disabled_reason(&app).is_some());
+    }
+
+    /// Seeded practice session reading aloud from `clock`'s time, with
+    /// the default three-second pause.
+    fn reading_aloud(clock: &MockClock, speaker: &MockSpeaker) -> App {
+        let mut app = App::new_test_seeded(&[("abate", "lessen", 1), ("wane", "decrease", 1)]);
+        app.clock = Box::new(clock.clone());
+        app.speaker = Some(Box::new(speaker.clone()));
+        press(&mut app, 'r');
+        app
+    }
+
+    fn tick_at(app: &mut App, clock: &MockClock, now: i64) {
+        clock.set(now);
+        app.tick(Instant::now());
+    }
+
+    #[test]
+    fn test_read_aloud_steps_through_words_on_ticks() {
+        let clock = MockClock::at(1_000);
+        let speaker = MockSpeaker::default();
+        let mut app = reading_aloud(&clock, &speaker);
+        let spoken = || speaker.spoken.borrow().clone();
+        assert_eq!(spoken(), ["abate"]);
+
+        tick_at(&mut app, &clock, 1_002);
+        assert_eq!(spoken(), ["abate"]);
+        assert!(!app.session.as_ref().unwrap().show_definition);
+
+        tick_at(&mut app, &clock, 1_003);
+        assert_eq!(spoken(), ["abate", "lessen"]);
+        assert!(app.session.as_ref().unwrap().show_definition);
+
+        tick_at(&mut app, &clock, 1_006);
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.index, 1);
+        assert!(!session.show_definition);
+        tick_at(&mut app, &clock, 1_009);
+        assert_eq!(spoken(), ["abate", "lessen", "wane", "decrease"]);
+
+        tick_at(&mut app, &clock, 1_012);
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert!(app.session.is_none());
+        assert_eq!(app.status.as_deref(), Some("Read 2 words aloud"));
+        // Nothing was graded.
+        assert_eq!(seen(&app, 1), (0, 0));
+        assert_eq!(seen(&app, 2), (0, 0));
+    }
+
+    #[test]
+    fn test_read_aloud_pauses_and_ignores_grades() {
+        let clock = MockClock::at(1_000);
+        let speaker = MockSpeaker::default();
+        let mut app = reading_aloud(&clock, &speaker);
+        press(&mut app, 's');
+        press(&mut app, 'Y');
+        let session = app.session.as_ref().unwrap();
+        assert_eq!((session.index, session.show_definition), (0, false));
+
+        clock.set(1_001);
+        press(&mut app, ' ');
+        tick_at(&mut app, &clock, 1_010);
+        assert_eq!(speaker.spoken.borrow().len(), 1);
+
+        // Nine seconds paused move the definition to 1_012.
+        press(&mut app, 'x');
+        tick_at(&mut app, &clock, 1_011);
+        assert_eq!(speaker.spoken.borrow().len(), 1);
+        tick_at(&mut app, &clock, 1_012);
+        assert_eq!(speaker.spoken.borrow().len(), 2);
+
+        press(&mut app, 'r');
+        tick_at(&mut app, &clock, 1_100);
+        assert_eq!(speaker.spoken.borrow().len(), 2);
+        assert_eq!(app.session.as_ref().unwrap().index, 0);
+    }
+
+    #[test]
+    fn test_read_aloud_needs_a_speech_command() {
+        let mut app = App::new_test_seeded(&[("abate", "lessen", 1)]);
+        press(&mut app, 'r');
+        assert!(app.session.as_ref().unwrap().read_aloud.is_none());
+        let toast = app.toast.as_ref().unwrap();
+        assert!(toast.is_error);
+        assert!(toast.message.contains("tts_command"), "{}", toast.message);
+    }
+
+    fn enter(app: &mut App) {
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Enter,
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        )));
+    }
+
+    fn dump(path: &str) -> Vec<String> {
+        let conn = init_db(path).unwrap();
+        let mut rows = Vec::new();
+        for table in [
+            "words",
+            "reviews",
+            "app_state",
+            "sessions",
+            "session_snapshot",
+        ] {
+            let mut stmt = conn.prepare(&format!("SELECT * FROM {table}")).unwrap();
+            let columns = stmt.column_count();
+            let mut query = stmt.query([]).unwrap();
+            while let Some(row) = query.next().unwrap() {
+                let values: Vec<String> = (0..columns)
+                    .map(|i| format!("{:?}", row.get::<_, rusqlite::types::Value>(i).unwrap()))
+                    .collect();
+                rows.push(format!("{table}: {}", values.join(", ")));
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn test_read_only_session_leaves_database_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        let conn = init_db(path).unwrap();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id) VALUES('abate','lessen',1), ('wane','decrease',1)",
+        )
+        .unwrap();
+        progress::save_progress(&conn, (Screen::Practice, 1, 0)).unwrap();
+        drop(conn);
+        let before = dump(path);
+
+        let mut app = App::open(path, KeyMap::default(), true).unwrap();
+        let started =
+            session::start_session(&app.conn, &SystemClock, Type::Group, 0, Duration::ZERO);
+        app.begin_session(started);
+        assert!(!app.session.as_ref().unwrap().persist);
+
+        for _ in 0..2 {
+            press(&mut app, 's');
+            press(&mut app, 'y');
+            enter(&mut app);
+        }
+        assert_eq!(app.current_screen, Screen::Test);
+        press(&mut app, 'm');
+        assert_eq!(
+            app.toast.as_ref().unwrap().message,
+            "Read-only mode: can't mark words"
+        );
+        // Through the test half with empty (wrong) answers.
+        for _ in 0..4 {
+            enter(&mut app);
+        }
+        app.return_to_menu();
+        app.shutdown();
+
+        assert!(app.error.is_none(), "{:?}", app.error);
+        assert_eq!(dump(path), before);
+    }
+
+    /// Database file holding `words`, with a second connection to it
+    /// standing in for `vocabulator seed` run alongside the app.
+    fn shared_db(dir: &tempfile::TempDir, words: &str) -> (String, Connection) {
+        let path = dir.path().join("vocab.db").to_str().unwrap().to_string();
+        let other = init_db(&path).unwrap();
+        other
+            .execute_batch(&format!(
+                "INSERT INTO words(word,definition,group_id,times_seen,success_count,last_seen)
+                 VALUES {words}"
+            ))
+            .unwrap();
+        (path, other)
+    }
+
+    #[test]
+    fn test_words_seeded_alongside_refresh_the_menu() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, other) = shared_db(&dir, "('abate','lessen',1,0,0,NULL)");
+        let mut app = App::open(&path, KeyMap::default(), false).unwrap();
+        assert_eq!(app.counts.words, 1);
+
+        crate::seed::add_word(&other, "wane", "decrease", 1).unwrap();
+        crate::seed::add_word(&other, "ebb", "recede", 2).unwrap();
+        app.tick(Instant::now());
+        assert_eq!(app.counts.words, 1);
+
+        let start = Instant::now();
+        app.tick(start + DATA_CHECK_INTERVAL);
+        assert_eq!(app.counts.words, 3);
+        assert_eq!(app.counts.unseen, 3);
+        assert_eq!(
+            app.toast.as_ref().unwrap().message,
+            "Database updated: +2 words"
+        );
+
+        // Nothing new, so no second toast.
+        app.toast = None;
+        app.tick(start + DATA_CHECK_INTERVAL * 2);
+        assert!(app.toast.is_none());
+    }
+
+    #[test]
+    fn test_session_keeps_its_words_when_more_arrive() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, other) = shared_db(
+            &dir,
+            "('abate','lessen',1,1,1,100), ('wane','decrease',1,1,0,200)",
+        );
+        let clock = MockClock::at(1_000_000);
+        let mut app = App::open(&path, KeyMap::default(), false).unwrap();
+        app.clock = Box::new(clock.clone());
+        app.selected = app
+            .menu_items
+            .iter()
+            .position(|item| *item == MenuAction::Session(Type::Quick))
+            .unwrap();
+        enter(&mut app);
+        assert_eq!(app.session.as_ref().unwrap().words.len(), 2);
+
+        crate::seed::add_word(&other, "ebb", "recede", 1).unwrap();
+        app.tick(Instant::now() + DATA_CHECK_INTERVAL);
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.words.len(), 2);
+        assert_eq!(session.words_arrived, 1);
+        assert_eq!(app.counts.words, 3);
+
+        clock.set(1_000_300);
+        app.tick(Instant::now());
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Time's up: 0 reviews, 0 correct. 1 new word arrived during the session")
+        );
+    }
+
+    #[test]
+    fn test_preview_cached_until_highlight_moves() {
+        let mut app = group_app();
+        app.return_to_menu();
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.preview.as_ref().unwrap().words, 2);
+
+        app.conn
+            .execute(
+                "INSERT INTO words(word,definition,group_id) VALUES('e','f',1)",
+                [],
+            )
+            .unwrap();
+        // Keys that leave the highlight alone don't reload it.
+        press(&mut app, 'z');
+        assert_eq!(app.preview.as_ref().unwrap().words, 2);
+
+        // Marked, Weak and Leech are disabled, so down lands on Statistics.
+        press(&mut app, 'j');
+        assert_eq!(app.menu_items[app.selected], MenuAction::Stats);
+        assert!(app.preview.is_none());
+
+        press(&mut app, 'k');
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.preview.as_ref().unwrap().words, 3);
+    }
+
+    #[test]
+    fn test_ctrl_v_pastes_into_focused_input() {
+        let mut app = app();
+        app.menu_filter = Some(LineEdit::default());
+        app.clipboard = Box::new(MockClipboard {
+            contents: Some(" stats\n".into()),
+            ..Default::default()
+        });
+        let ctrl_v = AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Char('v'),
+            KeyModifiers::CONTROL,
+            KeyEventKind::Press,
+        ));
+
+        app.handle(ctrl_v.clone());
+        assert_eq!(app.menu_filter.as_ref().unwrap().text(), "stats");
+
+        app.clipboard = Box::new(MockClipboard {
+            fail: true,
+            ..Default::default()
+        });
+        app.handle(ctrl_v);
+        assert_eq!(app.menu_filter.as_ref().unwrap().text(), "stats");
+        assert!(app.toast.is_none());
+    }
 }
@@ -0,0 +1,52 @@
+use crossterm::event::{self, Event, KeyEvent};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+/// Everything the run loop reacts to, from any source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppEvent {
+    Key(KeyEvent),
+    /// Periodic wake-up for timed logic such as toast expiry.
+    Tick,
+    Resize(u16, u16),
+    /// Message from background work, shown as a toast.
+    Notify(String),
+    /// SIGINT/SIGTERM from outside the terminal.
+    Terminate,
+}
+
+/// Forwards terminal input until the receiving end goes away. Key events of
+/// every kind are passed on; `KeyMap::accepts` decides which count.
+pub fn spawn_input(tx: Sender<AppEvent>) {
+    thread::spawn(move || {
+        loop {
+            // Poll with a timeout so the thread notices a closed channel.
+            let ready = match event::poll(Duration::from_millis(250)) {
+                Ok(ready) => ready,
+                Err(e) => {
+                    let _ = tx.send(AppEvent::Notify(format!("Input error: {e}")));
+                    return;
+                }
+            };
+            let event = match ready.then(event::read).transpose() {
+                Ok(Some(Event::Key(key))) => AppEvent::Key(key),
+                Ok(Some(Event::Resize(w, h))) => AppEvent::Resize(w, h),
+                Ok(_) => continue,
+                Err(e) => AppEvent::Notify(format!("Input error: {e}")),
+            };
+            if tx.send(event).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Sends a `Tick` every `rate` until the receiving end goes away.
+pub fn spawn_ticker(tx: Sender<AppEvent>, rate: Duration) {
+    thread::spawn(move || {
+        while tx.send(AppEvent::Tick).is_ok() {
+            thread::sleep(rate);
+        }
+    });
+}
@@ -0,0 +1,117 @@
+//! Every symbol the renderers draw that isn't plain text, so `--ascii` can
+//! swap them all at once. Renderers take these from `app.theme.glyphs`
+//! rather than writing the characters inline.
+
+use ratatui::symbols::{Marker, border};
+
+#[derive(Debug, PartialEq)]
+pub struct Glyphs {
+    pub enter: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+    pub left: &'static str,
+    pub right: &'static str,
+    /// Ends text cut to fit.
+    pub ellipsis: &'static str,
+    /// Between items on one line, spaces included.
+    pub separator: &'static str,
+    pub dash: &'static str,
+    pub sort_up: &'static str,
+    pub sort_down: &'static str,
+    pub warning: &'static str,
+    pub correct: &'static str,
+    pub wrong: &'static str,
+    pub border: border::Set,
+    /// Points plotted by charts; `None` writes the figures out instead,
+    /// since every marker is outside ASCII.
+    pub marker: Option<Marker>,
+}
+
+impl Glyphs {
+    /// `parts` on one line with `separator` between them, as help lines
+    /// list their keys.
+    pub fn join(&self, parts: &[&str]) -> String {
+        parts.join(self.separator)
+    }
+}
+
+pub const UNICODE: Glyphs = Glyphs {
+    enter: "⏎",
+    up: "↑",
+    down: "↓",
+    left: "←",
+    right: "→",
+    ellipsis: "…",
+    separator: " · ",
+    dash: "—",
+    sort_up: "▲",
+    sort_down: "▼",
+    warning: "⚠",
+    correct: "✓",
+    wrong: "✗",
+    border: border::PLAIN,
+    marker: Some(Marker::Braille),
+};
+
+pub const ASCII: Glyphs = Glyphs {
+    enter: "enter",
+    up: "up",
+    down: "down",
+    left: "left",
+    right: "right",
+    ellipsis: "...",
+    separator: " | ",
+    dash: "--",
+    sort_up: "^",
+    sort_down: "v",
+    warning: "!",
+    correct: "+",
+    wrong: "x",
+    border: border::Set {
+        top_left: "+",
+        top_right: "+",
+        bottom_left: "+",
+        bottom_right: "+",
+        vertical_left: "|",
+        vertical_right: "|",
+        horizontal_top: "-",
+        horizontal_bottom: "-",
+    },
+    marker: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_table_is_ascii() {
+        let b = ASCII.border;
+        let all = [
+            ASCII.enter,
+            ASCII.up,
+            ASCII.down,
+            ASCII.left,
+            ASCII.right,
+            ASCII.ellipsis,
+            ASCII.separator,
+            ASCII.dash,
+            ASCII.sort_up,
+            ASCII.sort_down,
+            ASCII.warning,
+            ASCII.correct,
+            ASCII.wrong,
+            b.top_left,
+            b.top_right,
+            b.bottom_left,
+            b.bottom_right,
+            b.vertical_left,
+            b.vertical_right,
+            b.horizontal_top,
+            b.horizontal_bottom,
+        ];
+        for glyph in all {
+            assert!(glyph.is_ascii(), "{glyph:?}");
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use crate::config::Binding;
+use crate::ui::glyphs::{self, Glyphs};
+use anyhow::{Result, anyhow, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Confirm,
+    Quit,
+    Show,
+    Correct,
+    Wrong,
+    Mark,
+    Insert,
+    CopyWord,
+    CopyEntry,
+    Focus,
+    Pause,
+    /// Show, grade correct and move on in one key.
+    QuickCorrect,
+    /// Show, grade wrong and move on in one key.
+    QuickWrong,
+    /// Start or stop reading the session aloud.
+    ReadAloud,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::Up,
+        Action::Down,
+        Action::Confirm,
+        Action::Quit,
+        Action::Show,
+        Action::Correct,
+        Action::Wrong,
+        Action::Mark,
+        Action::Insert,
+        Action::CopyWord,
+        Action::CopyEntry,
+        Action::Focus,
+        Action::Pause,
+        Action::QuickCorrect,
+        Action::QuickWrong,
+        Action::ReadAloud,
+    ];
+
+    /// Name used for the action in the `[keys]` table of the config file.
+    pub fn name(&self) -> &'static str {
+        use Action::*;
+        match self {
+            Up => "up",
+            Down => "down",
+            Confirm => "confirm",
+            Quit => "quit",
+            Show => "show",
+            Correct => "correct",
+            Wrong => "wrong",
+            Mark => "mark",
+            Insert => "insert",
+            CopyWord => "copy_word",
+            CopyEntry => "copy_entry",
+            Focus => "focus",
+            Pause => "pause",
+            QuickCorrect => "quick_correct",
+            QuickWrong => "quick_wrong",
+            ReadAloud => "read_aloud",
+        }
+    }
+
+    fn default_keys(&self) -> &'static [KeyCode] {
+        use Action::*;
+        match self {
+            Up => &[KeyCode::Up, KeyCode::Char('k')],
+            Down => &[KeyCode::Down, KeyCode::Char('j')],
+            Confirm => &[KeyCode::Enter],
+            Quit => &[KeyCode::Char('q'), KeyCode::Esc],
+            Show => &[KeyCode::Char('s')],
+            Correct => &[KeyCode::Char('y')],
+            Wrong => &[KeyCode::Char('n')],
+            Mark => &[KeyCode::Char('m')],
+            Insert => &[KeyCode::Char('i')],
+            CopyWord => &[KeyCode::Char('c')],
+            CopyEntry => &[KeyCode::Char('C')],
+            Focus => &[KeyCode::Char('f')],
+            Pause => &[KeyCode::Char(' '), KeyCode::Char('.')],
+            QuickCorrect => &[KeyCode::Char('Y')],
+            QuickWrong => &[KeyCode::Char('N')],
+            ReadAloud => &[KeyCode::Char('r')],
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    actions: HashMap<KeyCode, Action>,
+    keys: HashMap<Action, Vec<KeyCode>>,
+    /// Let a held-down Up/Down keep moving through lists.
+    repeat_navigation: bool,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::from_config(&HashMap::new()).expect("default bindings conflict")
+    }
+}
+
+impl KeyMap {
+    /// Builds the keymap from the `[keys]` config table. Actions missing from
+    /// the table keep their default bindings.
+    pub fn from_config(overrides: &HashMap<String, Binding>) -> Result<Self> {
+        for name in overrides.keys() {
+            if Action::from_name(name).is_none() {
+                bail!("Unknown action '{name}' in [keys]");
+            }
+        }
+
+        let mut keys = HashMap::new();
+        for action in Action::ALL {
+            let codes = match overrides.get(action.name()) {
+                Some(binding) => binding
+                    .keys()
+                    .into_iter()
+                    .map(parse_key)
+                    .collect::<Result<Vec<_>>>()?,
+                None => action.default_keys().to_vec(),
+            };
+            keys.insert(action, codes);
+        }
+
+        let mut actions = HashMap::new();
+        for action in Action::ALL {
+            for code in &keys[&action] {
+                if let Some(other) = actions.insert(*code, action)
+                    && other != action
+                {
+                    bail!(
+                        "Key '{}' is bound to both '{}' and '{}'",
+                        key_label(*code, &glyphs::UNICODE),
+                        other.name(),
+                        action.name()
+                    );
+                }
+            }
+        }
+
+        Ok(Self {
+            actions,
+            keys,
+            repeat_navigation: false,
+        })
+    }
+
+    pub fn with_repeat_navigation(mut self, on: bool) -> Self {
+        self.repeat_navigation = on;
+        self
+    }
+
+    /// Whether `key` should reach the handlers at all. Terminals that
+    /// report key kinds (Windows always does) send a Release after every
+    /// Press, and Repeats while a key is held; only Presses count, plus
+    /// navigation Repeats when enabled.
+    pub fn accepts(&self, key: KeyEvent) -> bool {
+        match key.kind {
+            KeyEventKind::Press => true,
+            KeyEventKind::Repeat => {
+                self.repeat_navigation
+                    && matches!(self.action_for(key), Some(Action::Up | Action::Down))
+            }
+            KeyEventKind::Release => false,
+        }
+    }
+
+    pub fn action_for(&self, key: KeyEvent) -> Option<Action> {
+        self.actions.get(&key.code).copied()
+    }
+
+    /// Label of the first key bound to `action`, for on-screen hints.
+    pub fn label(&self, action: Action, glyphs: &Glyphs) -> String {
+        self.keys[&action]
+            .first()
+            .map(|code| key_label(*code, glyphs))
+            .unwrap_or_default()
+    }
+}
+
+fn parse_key(name: &str) -> Result<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+
+    Ok(match name.to_ascii_lowercase().as_str() {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        _ => return Err(anyhow!("Unknown key '{name}'")),
+    })
+}
+
+fn key_label(code: KeyCode, glyphs: &Glyphs) -> String {
+    match code {
+        KeyCode::Char(' ') => "space".into(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => glyphs.enter.into(),
+        KeyCode::Esc => "esc".into(),
+        KeyCode::Tab => "tab".into(),
+        KeyCode::Backspace => "backspace".into(),
+        KeyCode::Up => glyphs.up.into(),
+        KeyCode::Down => glyphs.down.into(),
+        KeyCode::Left => glyphs.left.into(),
+        KeyCode::Right => glyphs.right.into(),
+        KeyCode::Home => "home".into(),
+        KeyCode::End => "end".into(),
+        other => format!("{other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press)
+    }
+
+    fn with_kind(code: KeyCode, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent::new_with_kind(code, KeyModifiers::NONE, kind)
+    }
+
+    #[test]
+    fn test_only_presses_by_default() {
+        let map = KeyMap::default();
+        assert!(map.accepts(key(KeyCode::Char('y'))));
+        assert!(!map.accepts(with_kind(KeyCode::Char('y'), KeyEventKind::Release)));
+        assert!(!map.accepts(with_kind(KeyCode::Down, KeyEventKind::Repeat)));
+    }
+
+    #[test]
+    fn test_repeat_only_for_navigation() {
+        let map = KeyMap::default().with_repeat_navigation(true);
+        assert!(map.accepts(with_kind(KeyCode::Down, KeyEventKind::Repeat)));
+        assert!(map.accepts(with_kind(KeyCode::Char('k'), KeyEventKind::Repeat)));
+        assert!(!map.accepts(with_kind(KeyCode::Char('y'), KeyEventKind::Repeat)));
+        assert!(!map.accepts(with_kind(KeyCode::Down, KeyEventKind::Release)));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let map = KeyMap::default();
+        assert_eq!(map.action_for(key(KeyCode::Char('s'))), Some(Action::Show));
+        assert_eq!(map.action_for(key(KeyCode::Esc)), Some(Action::Quit));
+        assert_eq!(map.action_for(key(KeyCode::Char('x'))), None);
+    }
+
+    #[test]
+    fn test_override_replaces_default() {
+        let config = config::parse("[keys]\nshow = \"d\"\nquit = [\"x\", \"esc\"]").unwrap();
+        let map = KeyMap::from_config(&config.keys).unwrap();
+
+        assert_eq!(map.action_for(key(KeyCode::Char('d'))), Some(Action::Show));
+        assert_eq!(map.action_for(key(KeyCode::Char('s'))), None);
+        assert_eq!(map.action_for(key(KeyCode::Char('x'))), Some(Action::Quit));
+        assert_eq!(map.action_for(key(KeyCode::Char('q'))), None);
+        assert_eq!(map.label(Action::Show, &glyphs::UNICODE), "d");
+    }
+
+    #[test]
+    fn test_conflict_names_both_actions() {
+        let config = config::parse("[keys]\nshow = \"m\"").unwrap();
+        let err = KeyMap::from_config(&config.keys).unwrap_err().to_string();
+
+        assert!(err.contains("'show'"));
+        assert!(err.contains("'mark'"));
+    }
+
+    #[test]
+    fn test_unknown_action_and_key() {
+        let config = config::parse("[keys]\nfly = \"f\"").unwrap();
+        assert!(KeyMap::from_config(&config.keys).is_err());
+
+        let config = config::parse("[keys]\nshow = \"hyper\"").unwrap();
+        assert!(KeyMap::from_config(&config.keys).is_err());
+    }
+}
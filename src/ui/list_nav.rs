@@ -0,0 +1,256 @@
+use crate::ui::keymap::Action;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::time::{Duration, Instant};
+
+/// How long a lone `g` waits for its second press.
+pub const PENDING_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavMove {
+    Up(usize),
+    Down(usize),
+    Top,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavResult {
+    Move(NavMove),
+    /// `g`, digits, Enter: jump to the numbered entry (e.g. a group).
+    Jump(usize),
+    /// Key was consumed as part of a count or chord.
+    Pending,
+    /// Key is not list navigation; the screen should handle it.
+    Ignored,
+}
+
+/// Vim-style count prefixes and `gg`/`G` for list screens, plus `g<n>⏎`
+/// jumps.
+#[derive(Debug, Default)]
+pub struct ListNav {
+    count: usize,
+    pending_g: Option<Instant>,
+    /// Number typed after `g`; armed until Enter or Esc.
+    jump: Option<usize>,
+}
+
+impl ListNav {
+    pub fn handle(&mut self, key: KeyEvent, action: Option<Action>, now: Instant) -> NavResult {
+        if let Some(target) = self.jump {
+            match key.code {
+                KeyCode::Char(c @ '0'..='9') => {
+                    let digit = c.to_digit(10).unwrap() as usize;
+                    self.jump = Some(target.saturating_mul(10).saturating_add(digit));
+                    return NavResult::Pending;
+                }
+                KeyCode::Backspace => {
+                    self.jump = Some(target / 10);
+                    return NavResult::Pending;
+                }
+                KeyCode::Enter => {
+                    self.reset();
+                    return NavResult::Jump(target);
+                }
+                KeyCode::Esc => {
+                    self.reset();
+                    return NavResult::Pending;
+                }
+                _ => self.reset(),
+            }
+        }
+
+        if self
+            .pending_g
+            .is_some_and(|at| now.duration_since(at) > PENDING_TIMEOUT)
+        {
+            self.pending_g = None;
+        }
+
+        match key.code {
+            KeyCode::Char(c @ '0'..='9') if self.pending_g.take().is_some() => {
+                self.count = 0;
+                self.jump = Some(c.to_digit(10).unwrap() as usize);
+                return NavResult::Pending;
+            }
+            KeyCode::Char(c @ '0'..='9') if c != '0' || self.count > 0 => {
+                let digit = c.to_digit(10).unwrap() as usize;
+                self.count = self.count.saturating_mul(10).saturating_add(digit);
+                return NavResult::Pending;
+            }
+            KeyCode::Char('g') => {
+                if self.pending_g.take().is_some() {
+                    self.count = 0;
+                    return NavResult::Move(NavMove::Top);
+                }
+                self.pending_g = Some(now);
+                return NavResult::Pending;
+            }
+            KeyCode::Char('G') => {
+                self.reset();
+                return NavResult::Move(NavMove::Bottom);
+            }
+            _ => {}
+        }
+
+        let count = self.count.max(1);
+        self.reset();
+
+        match action {
+            Some(Action::Down) => NavResult::Move(NavMove::Down(count)),
+            Some(Action::Up) => NavResult::Move(NavMove::Up(count)),
+            _ => NavResult::Ignored,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.pending_g = None;
+        self.jump = None;
+    }
+
+    /// Number typed so far in an armed `g<n>` jump.
+    pub fn jump_target(&self) -> Option<usize> {
+        self.jump
+    }
+}
+
+/// Applies `mv` to a selection in a list of `len` items. Single steps wrap
+/// around, counted steps stop at the ends.
+pub fn apply(selected: usize, len: usize, mv: NavMove) -> usize {
+    if len == 0 {
+        return 0;
+    }
+
+    match mv {
+        NavMove::Top => 0,
+        NavMove::Bottom => len - 1,
+        NavMove::Down(1) => (selected + 1) % len,
+        NavMove::Up(1) => (selected + len - 1) % len,
+        NavMove::Down(n) => (selected + n).min(len - 1),
+        NavMove::Up(n) => selected.saturating_sub(n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new_with_kind(KeyCode::Char(c), KeyModifiers::NONE, KeyEventKind::Press)
+    }
+
+    fn code(code: KeyCode) -> KeyEvent {
+        KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press)
+    }
+
+    fn action(c: char) -> Option<Action> {
+        match c {
+            'j' => Some(Action::Down),
+            'k' => Some(Action::Up),
+            _ => None,
+        }
+    }
+
+    fn feed(nav: &mut ListNav, keys: &str, now: Instant) -> NavResult {
+        let mut last = NavResult::Ignored;
+        for c in keys.chars() {
+            last = nav.handle(key(c), action(c), now);
+        }
+        last
+    }
+
+    #[test]
+    fn test_count_prefix() {
+        let mut nav = ListNav::default();
+        let now = Instant::now();
+
+        assert_eq!(feed(&mut nav, "5j", now), NavResult::Move(NavMove::Down(5)));
+        assert_eq!(feed(&mut nav, "12k", now), NavResult::Move(NavMove::Up(12)));
+        assert_eq!(feed(&mut nav, "j", now), NavResult::Move(NavMove::Down(1)));
+    }
+
+    #[test]
+    fn test_gg_and_g() {
+        let mut nav = ListNav::default();
+        let now = Instant::now();
+
+        assert_eq!(nav.handle(key('g'), None, now), NavResult::Pending);
+        assert_eq!(
+            nav.handle(key('g'), None, now),
+            NavResult::Move(NavMove::Top)
+        );
+        assert_eq!(feed(&mut nav, "G", now), NavResult::Move(NavMove::Bottom));
+    }
+
+    #[test]
+    fn test_dangling_g_then_other_key() {
+        let mut nav = ListNav::default();
+        let now = Instant::now();
+
+        feed(&mut nav, "g", now);
+        assert_eq!(nav.handle(key('x'), None, now), NavResult::Ignored);
+        // The dangling g was cleared, so a single g arms again.
+        assert_eq!(nav.handle(key('g'), None, now), NavResult::Pending);
+    }
+
+    #[test]
+    fn test_pending_g_times_out() {
+        let mut nav = ListNav::default();
+        let now = Instant::now();
+
+        feed(&mut nav, "g", now);
+        let later = now + PENDING_TIMEOUT + Duration::from_millis(1);
+        assert_eq!(nav.handle(key('g'), None, later), NavResult::Pending);
+
+        assert_eq!(
+            nav.handle(key('g'), None, later + PENDING_TIMEOUT),
+            NavResult::Move(NavMove::Top)
+        );
+    }
+
+    #[test]
+    fn test_group_jump_accumulates_digits() {
+        let mut nav = ListNav::default();
+        let now = Instant::now();
+
+        assert_eq!(feed(&mut nav, "g12", now), NavResult::Pending);
+        assert_eq!(nav.jump_target(), Some(12));
+        // Waiting doesn't disarm an explicit jump.
+        let later = now + PENDING_TIMEOUT * 2;
+        assert_eq!(feed(&mut nav, "0", later), NavResult::Pending);
+        assert_eq!(
+            nav.handle(code(KeyCode::Enter), None, later),
+            NavResult::Jump(120)
+        );
+        assert_eq!(nav.jump_target(), None);
+    }
+
+    #[test]
+    fn test_group_jump_cancel() {
+        let mut nav = ListNav::default();
+        let now = Instant::now();
+
+        feed(&mut nav, "g4", now);
+        assert_eq!(
+            nav.handle(code(KeyCode::Esc), None, now),
+            NavResult::Pending
+        );
+        assert_eq!(nav.jump_target(), None);
+
+        // Any other key cancels too and is handled normally.
+        feed(&mut nav, "g4", now);
+        assert_eq!(feed(&mut nav, "j", now), NavResult::Move(NavMove::Down(1)));
+        assert_eq!(nav.jump_target(), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(apply(3, 4, NavMove::Down(1)), 0);
+        assert_eq!(apply(0, 4, NavMove::Up(1)), 3);
+        assert_eq!(apply(1, 4, NavMove::Down(5)), 3);
+        assert_eq!(apply(2, 4, NavMove::Up(5)), 0);
+        assert_eq!(apply(2, 4, NavMove::Bottom), 3);
+        assert_eq!(apply(2, 4, NavMove::Top), 0);
+    }
+}
@@ -1,4 +1,13 @@
 pub mod app;
+pub mod event;
+pub mod glyphs;
+pub mod keymap;
+pub mod list_nav;
 pub mod run;
 pub mod screens;
 pub mod terminal;
+#[cfg(test)]
+pub mod testing;
+pub mod text;
+pub mod theme;
+pub mod widgets;
@@ -1,37 +1,403 @@
-use crate::ui::screens::{menu, practice, test};
+use crate::config::Config;
+use crate::core::session::{QUICK_REVIEW_MINUTES, READ_ALOUD_PAUSE_SECS};
+use crate::db::health::Damage;
+use crate::db::queries;
+use crate::speech::CommandSpeaker;
+use crate::ui::screens::recovery::{self, Outcome, RecoveryState};
+use crate::ui::screens::{menu, practice, review_log, splash, stats, test};
 use anyhow::Result;
-use crossterm::event::{self, Event};
-use rusqlite::Connection;
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    widgets::Paragraph,
+};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
 
 use super::{
     app::{App, Screen},
-    terminal::{init_terminal, restore_terminal},
+    event::{self, AppEvent},
+    keymap::KeyMap,
+    terminal::{AppTerminal, init_terminal, install_panic_hook, restore_terminal},
+    theme::Theme,
 };
 
-pub fn run() -> Result<()> {
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+pub fn run(
+    db_path: &str,
+    config: &Config,
+    theme: Theme,
+    read_only: bool,
+    profile: Option<String>,
+) -> Result<()> {
+    let keymap =
+        KeyMap::from_config(&config.keys)?.with_repeat_navigation(config.repeat_navigation);
+    install_panic_hook();
+
+    let (tx, rx) = mpsc::channel();
+    // SIGINT/SIGTERM from outside the terminal; in raw mode Ctrl+C arrives
+    // as a key event instead.
+    let signals = tx.clone();
+    ctrlc::set_handler(move || {
+        let _ = signals.send(AppEvent::Terminate);
+    })?;
+
     let mut terminal = init_terminal()?;
-    let mut app = App::new(Connection::open("vocab.db")?);
+    event::spawn_input(tx.clone());
+    event::spawn_ticker(tx, TICK_RATE);
+
+    // Restore the terminal before surfacing an error, not after.
+    let result = open(db_path, &keymap, theme, read_only, &mut terminal, &rx).and_then(|app| {
+        let Some(mut app) = app else {
+            return Ok(());
+        };
+        app.theme = theme;
+        app.profile = profile;
+        app.leeches = config.leeches;
+        app.mix_marked = config.mix_marked;
+        app.hide_reading = config.hide_reading;
+        let minutes = config
+            .quick_review_minutes
+            .unwrap_or(QUICK_REVIEW_MINUTES)
+            .max(1);
+        app.quick_review = Duration::from_secs(60 * u64::from(minutes));
+        if let Some(command) = &config.tts_command {
+            app.speaker = Some(Box::new(CommandSpeaker::new(command)?));
+        }
+        let pause = config
+            .read_aloud_pause_secs
+            .unwrap_or(READ_ALOUD_PAUSE_SECS);
+        app.read_aloud_pause = Duration::from_secs(u64::from(pause));
+        if let Some(dir) = &config.export_dir {
+            app.export_dir = dir.clone();
+        }
+        queries::set_word_order(&app.conn, config.order)?;
+        queries::set_include_mastered(&app.conn, config.include_mastered)?;
+        // Opening counted the words under the default settings.
+        app.refresh_counts();
+        run_loop(&mut app, &mut terminal, &rx)?;
+        app.close_snapshot();
+        Ok(())
+    });
+    restore_terminal(terminal)?;
+    result
+}
 
+/// Opens the app, showing the recovery screen for as long as the database
+/// is damaged. `None` when the user quits from there.
+fn open(
+    db_path: &str,
+    keymap: &KeyMap,
+    theme: Theme,
+    read_only: bool,
+    terminal: &mut AppTerminal,
+    events: &Receiver<AppEvent>,
+) -> Result<Option<App>> {
+    let mut recovery: Option<RecoveryState> = None;
     loop {
-        terminal.draw(|f| match app.current_screen {
-            Screen::Menu => menu::render(f, &app),
-            Screen::Practice => practice::render(f, &app),
-            Screen::Test => test::render(f, &app),
-        })?;
-
-        if let Event::Key(key) = event::read()? {
-            match app.current_screen {
-                Screen::Menu => menu::handle_event(&mut app, key),
-                Screen::Practice => practice::handle_event(&mut app, key),
-                Screen::Test => test::handle_event(&mut app, key),
+        let damage = match App::open(db_path, keymap.clone(), read_only) {
+            Ok(mut app) => {
+                if let Some(message) = recovery.and_then(|state| state.message) {
+                    app.notify(message, false);
+                }
+                return Ok(Some(app));
             }
-        }
+            Err(e) => e.downcast::<Damage>()?,
+        };
+        tracing::warn!(%damage, "database needs recovery");
+        let state = match &mut recovery {
+            Some(state) => {
+                state.refresh(damage);
+                state
+            }
+            None => recovery.insert(RecoveryState::new(db_path, damage)),
+        };
 
-        if app.should_quit {
-            break;
+        loop {
+            terminal.draw(|f| recovery::render(f, state, &theme))?;
+            let outcome = match events.recv()? {
+                AppEvent::Key(key)
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    Outcome::Quit
+                }
+                AppEvent::Key(key) if !keymap.accepts(key) => Outcome::Stay,
+                AppEvent::Key(key) => recovery::handle_event(state, keymap, key),
+                AppEvent::Terminate => Outcome::Quit,
+                _ => Outcome::Stay,
+            };
+            match outcome {
+                Outcome::Stay => {}
+                Outcome::Reopen => break,
+                Outcome::Quit => return Ok(None),
+            }
         }
     }
+}
 
-    restore_terminal(terminal)?;
+fn run_loop(app: &mut App, terminal: &mut AppTerminal, events: &Receiver<AppEvent>) -> Result<()> {
+    let mut draw = |app: &App| -> Result<()> {
+        terminal.draw(|f| render(f, app))?;
+        Ok(())
+    };
+
+    draw(app)?;
+    app.dirty = false;
+    while !app.should_quit {
+        let event = events.recv()?;
+        step(app, event, &mut draw)?;
+    }
+    Ok(())
+}
+
+/// One pass of the loop: applies `event`, then redraws only if it changed
+/// something.
+fn step(app: &mut App, event: AppEvent, draw: &mut impl FnMut(&App) -> Result<()>) -> Result<()> {
+    app.handle(event);
+
+    if app.dirty {
+        draw(app)?;
+        app.dirty = false;
+    }
     Ok(())
 }
+
+pub(crate) fn render(f: &mut Frame, app: &App) {
+    match app.current_screen {
+        Screen::Splash => splash::render(f, app),
+        Screen::Menu => menu::render(f, app),
+        Screen::Practice | Screen::Test
+            if app.session.as_ref().is_some_and(|s| s.paused_at.is_some()) =>
+        {
+            render_paused(f, &app.theme)
+        }
+        Screen::Practice => practice::render(f, app),
+        Screen::Test => test::render(f, app),
+        Screen::Stats => stats::render(f, app),
+        Screen::ReviewLog => review_log::render(f, app),
+    }
+    if app.read_only {
+        render_read_only_badge(f, &app.theme);
+    }
+    if let Some(modal) = &app.modal {
+        modal.render(f, &app.theme);
+    }
+    if let Some(toast) = &app.toast {
+        toast.render(f, &app.theme);
+    }
+}
+
+/// Stands in for the whole practice or test screen, so nothing of the word
+/// or its definition shows.
+fn render_paused(f: &mut Frame, theme: &Theme) {
+    let area = f.size();
+    let block = theme.block();
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let middle = Rect::new(
+        inner.x,
+        inner.y + inner.height / 2,
+        inner.width,
+        1.min(inner.height),
+    );
+    let text = format!("Paused {} press any key", theme.glyphs.dash);
+    f.render_widget(
+        Paragraph::new(text)
+            .style(theme.accent())
+            .alignment(Alignment::Center),
+        middle,
+    );
+}
+
+/// Drawn on the bottom border, where every screen keeps its footer, so it
+/// stays visible whatever the screen.
+fn render_read_only_badge(f: &mut Frame, theme: &Theme) {
+    const BADGE: &str = " READ-ONLY ";
+    let area = f.size();
+    if area.height == 0 || area.width < BADGE.len() as u16 + 2 {
+        return;
+    }
+    let rect = Rect::new(area.x + 1, area.bottom() - 1, BADGE.len() as u16, 1);
+    let badge = Paragraph::new(BADGE).style(theme.badge());
+    f.render_widget(badge, rect);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use crate::ui::widgets::{Buttons, Modal};
+    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+    use std::time::Instant;
+
+    fn counting_step(app: &mut App, event: AppEvent) -> usize {
+        let mut draws = 0;
+        step(app, event, &mut |_: &App| {
+            draws += 1;
+            Ok(())
+        })
+        .unwrap();
+        draws
+    }
+
+    fn idle_app() -> App {
+        let mut app = App::new_test();
+        assert_eq!(counting_step(&mut app, AppEvent::Tick), 1);
+        app
+    }
+
+    fn key(c: char) -> AppEvent {
+        AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Char(c),
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        ))
+    }
+
+    #[test]
+    fn test_idle_ticks_do_not_draw() {
+        let mut app = idle_app();
+
+        let draws: usize = (0..50)
+            .map(|_| counting_step(&mut app, AppEvent::Tick))
+            .sum();
+        assert_eq!(draws, 0);
+
+        assert_eq!(counting_step(&mut app, key('j')), 1);
+        assert_eq!(counting_step(&mut app, AppEvent::Resize(80, 24)), 1);
+        assert_eq!(counting_step(&mut app, AppEvent::Tick), 0);
+    }
+
+    #[test]
+    fn test_modal_takes_keys_first() {
+        let mut app = idle_app();
+        app.modal = Some(Modal::new("Quit?", vec![], Buttons::YesNo));
+
+        // q would quit from the menu, but the dialog swallows it.
+        app.handle(key('q'));
+        assert!(app.modal.is_some());
+        assert!(!app.should_quit);
+
+        app.handle(key('n'));
+        assert!(app.modal.is_none());
+        assert!(!app.should_quit);
+
+        app.handle(key('q'));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_ctrl_c_quits_from_any_screen() {
+        let mut app = idle_app();
+        app.modal = Some(Modal::new("Quit?", vec![], Buttons::YesNo));
+
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+            KeyEventKind::Press,
+        )));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_key_release_is_not_a_second_press() {
+        let mut app = idle_app();
+        let down = |kind| {
+            AppEvent::Key(KeyEvent::new_with_kind(
+                KeyCode::Down,
+                KeyModifiers::NONE,
+                kind,
+            ))
+        };
+
+        let start = app.selected;
+        assert_eq!(counting_step(&mut app, down(KeyEventKind::Press)), 1);
+        let moved = app.selected;
+        assert_ne!(moved, start);
+        assert_eq!(counting_step(&mut app, down(KeyEventKind::Release)), 0);
+        assert_eq!(app.selected, moved);
+
+        // Held down: ignored unless repeats are enabled for navigation.
+        counting_step(&mut app, down(KeyEventKind::Repeat));
+        assert_eq!(app.selected, moved);
+        app.keymap = KeyMap::default().with_repeat_navigation(true);
+        counting_step(&mut app, down(KeyEventKind::Repeat));
+        assert_ne!(app.selected, moved);
+    }
+
+    #[test]
+    fn test_toast_expiry_draws_once() {
+        let mut app = idle_app();
+        assert_eq!(
+            counting_step(&mut app, AppEvent::Notify("Imported".into())),
+            1
+        );
+        assert_eq!(app.toast.as_ref().unwrap().message, "Imported");
+        assert_eq!(counting_step(&mut app, AppEvent::Tick), 0);
+
+        app.toast.as_mut().unwrap().expires = Instant::now();
+        assert_eq!(counting_step(&mut app, AppEvent::Tick), 1);
+        assert!(app.toast.is_none());
+        assert_eq!(counting_step(&mut app, AppEvent::Tick), 0);
+    }
+
+    #[test]
+    fn test_scripted_session_through_channel() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES
+                 (1,'abate','lessen',1), (2,'abound','plentiful',1);",
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+
+        let (tx, rx) = mpsc::channel();
+        let enter = AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Enter,
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        ));
+        // Start the group, grade both words, then back out and quit.
+        let script = [
+            enter.clone(),
+            AppEvent::Tick,
+            key('s'),
+            key('y'),
+            enter.clone(),
+            AppEvent::Resize(100, 30),
+            key('s'),
+            key('n'),
+            enter,
+            key('q'),
+            AppEvent::Tick,
+            key('q'),
+        ];
+        for event in script {
+            tx.send(event).unwrap();
+        }
+        drop(tx);
+
+        let mut draws = 0;
+        while !app.should_quit {
+            let event = rx.recv().unwrap();
+            step(&mut app, event, &mut |_: &App| {
+                draws += 1;
+                Ok(())
+            })
+            .unwrap();
+        }
+
+        assert!(rx.try_recv().is_err());
+        // Every key and the resize redraw; neither tick does.
+        assert_eq!(draws, 10);
+        let reviews: i64 = app
+            .conn
+            .query_row("SELECT COUNT(*) FROM reviews", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(reviews, 2);
+        assert_eq!(app.counts.unseen, 0);
+    }
+}
@@ -1,81 +1,523 @@
+use crate::core::preview::{self, SessionPreview};
 use crate::core::session;
-use crate::ui::app::{App, MenuAction};
+use crate::core::session::Type;
+use crate::db::queries;
+use crate::ui::app::{App, MenuAction, Screen};
+use crate::ui::glyphs::Glyphs;
+use crate::ui::keymap::Action;
+use crate::ui::list_nav::NavResult;
+use crate::ui::screens::splash::SplashState;
+use crate::ui::screens::stats;
+use crate::ui::text;
+use crate::ui::widgets::LineEdit;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout},
     style::{Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
 };
+use std::time::Instant;
+use unicode_width::UnicodeWidthStr;
 
 pub fn handle_event(app: &mut App, key: KeyEvent) {
     app.error = None;
+    app.status = None;
+
+    if app.menu_filter.is_some() {
+        handle_filter_key(app, key);
+        return;
+    }
+    if key.code == KeyCode::Char('/') {
+        app.list_nav.reset();
+        app.menu_filter = Some(LineEdit::default());
+        return;
+    }
+
+    let action = app.keymap.action_for(key);
+
+    match app.list_nav.handle(key, action, Instant::now()) {
+        NavResult::Move(mv) => {
+            app.navigate(mv);
+            return;
+        }
+        NavResult::Jump(group) => {
+            jump_to_group(app, group);
+            return;
+        }
+        NavResult::Pending => return,
+        NavResult::Ignored => {}
+    }
+
+    match action {
+        Some(Action::Quit) => app.should_quit = true,
+        Some(Action::Confirm) => activate(app),
+        _ => {}
+    }
+}
+
+/// While filtering, every key edits the filter except Enter (activate the
+/// top match) and Esc (clear it). Backspace on an empty filter also leaves.
+fn handle_filter_key(app: &mut App, key: KeyEvent) {
+    let Some(filter) = &mut app.menu_filter else {
+        return;
+    };
+
     match key.code {
-        KeyCode::Esc | KeyCode::Char('q') => app.should_quit = true,
-        KeyCode::Down | KeyCode::Char('j') => app.next(),
-        KeyCode::Up | KeyCode::Char('k') => app.previous(),
-        KeyCode::Enter => {
-            app.select();
-
-            if let MenuAction::Session(session_type) = app.menu_items[app.selected] {
-                match session::start_session(&app.conn, session_type) {
-                    Ok((session, screen)) => {
-                        if session.index < session.words.len() {
-                            app.session = Some(session);
-                            app.current_screen = screen;
-                        } else {
-                            let err: String;
-                            if session.words.is_empty() {
-                                err = "Word list is empty".to_string();
-                            } else {
-                                err = format!(
-                                    "Index {} out of bounds for vector of length {}. Db corrupted",
-                                    session.index,
-                                    session.words.len()
-                                )
-                                .to_string();
-                            }
-                            app.error = Some(err);
-                        }
-                    }
-                    Err(e) => app.error = Some(e.to_string()),
-                }
+        KeyCode::Esc => app.menu_filter = None,
+        KeyCode::Backspace if filter.text().is_empty() => app.menu_filter = None,
+        KeyCode::Enter => match filtered_items(app).first() {
+            Some(&index) => {
+                app.menu_filter = None;
+                app.selected = index;
+                activate(app);
             }
+            None => app.status = Some("No menu entry matches the filter".into()),
+        },
+        _ => {
+            filter.handle_key(key);
         }
-        _ => {}
     }
 }
 
+fn activate(app: &mut App) {
+    if let Some(reason) = app.menu_items[app.selected].disabled_reason(app) {
+        app.status = Some(reason);
+        return;
+    }
+
+    app.select();
+
+    match app.menu_items[app.selected] {
+        // Nothing to learn yet: send the user to the import flow instead.
+        MenuAction::Session(Type::Group) if app.counts.words == 0 => {
+            app.splash = SplashState::default();
+            app.current_screen = Screen::Splash;
+        }
+        MenuAction::Session(session_type) => {
+            let started = session::start_session(
+                &app.conn,
+                app.clock.as_ref(),
+                session_type,
+                app.mix_marked,
+                app.quick_review,
+            );
+            app.begin_session(started);
+        }
+        MenuAction::Stats => stats::open(app),
+        MenuAction::Exit => {}
+    }
+}
+
+/// Indices of menu entries matching the current filter, in menu order.
+fn filtered_items(app: &App) -> Vec<usize> {
+    let query = app.menu_filter.as_ref().map(|f| f.text()).unwrap_or("");
+    (0..app.menu_items.len())
+        .filter(|&i| fuzzy_match(query, &app.menu_items[i].label(app)))
+        .collect()
+}
+
+/// True when the characters of `query` appear in `label` in order,
+/// ignoring case ("rvw" matches "Review Marks").
+fn fuzzy_match(query: &str, label: &str) -> bool {
+    let mut label = label.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|q| label.any(|c| c == q))
+}
+
+fn jump_to_group(app: &mut App, group: usize) {
+    let last = match queries::fetch_final_group(&app.conn) {
+        Ok(last) => last.unwrap_or(0),
+        Err(e) => {
+            app.error = Some(e.to_string());
+            return;
+        }
+    };
+
+    if group == 0 || group > last as usize {
+        let message = if last == 0 {
+            "No groups yet. Seed the database first".to_string()
+        } else {
+            format!("Group {group} doesn't exist (valid: 1-{last})")
+        };
+        app.notify(message, true);
+        return;
+    }
+
+    let started = session::group_session_at(
+        &app.conn,
+        app.clock.as_ref(),
+        group as i32,
+        app.mix_marked,
+        !app.read_only,
+    );
+    app.begin_session(started);
+}
+
 pub fn render(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .constraints([
+            Constraint::Length(if app.counts.words == 0 { 11 } else { 0 }),
+            Constraint::Min(0),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
         .split(f.size());
 
-    let items: Vec<ListItem> = app
-        .menu_items
+    if app.counts.words == 0 {
+        let guide = Paragraph::new(guidance_text(app))
+            .wrap(Wrap { trim: false })
+            .block(app.theme.block().title("Getting Started"));
+        f.render_widget(guide, chunks[0]);
+    }
+
+    let visible = filtered_items(app);
+    let items: Vec<ListItem> = visible
         .iter()
-        .map(|item| ListItem::new(item.label()))
+        .map(|&i| {
+            let item = &app.menu_items[i];
+            let style = if item.disabled_reason(app).is_some() {
+                app.theme.muted()
+            } else {
+                Style::default()
+            };
+            ListItem::new(item.label(app)).style(style)
+        })
         .collect();
 
     let mut state = ListState::default();
-    state.select(Some(app.selected));
+    if app.menu_filter.is_some() {
+        state.select((!visible.is_empty()).then_some(0));
+    } else {
+        state.select(Some(app.selected));
+    }
 
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Main Menu"))
+        .block(app.theme.block().title("Main Menu"))
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .highlight_symbol("> ")
         .repeat_highlight_symbol(true);
 
-    f.render_stateful_widget(list, chunks[0], &mut state);
+    let preview = app.preview.as_ref().filter(|_| app.menu_filter.is_none());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(if preview.is_some() { PREVIEW_WIDTH } else { 0 }),
+        ])
+        .split(chunks[1]);
+    f.render_stateful_widget(list, columns[0], &mut state);
+    if let Some(preview) = preview {
+        let panel = Paragraph::new(preview_text(preview, app.theme.glyphs))
+            .wrap(Wrap { trim: false })
+            .block(app.theme.block().title("Preview"));
+        f.render_widget(panel, columns[1]);
+    }
 
     if let Some(err) = &app.error {
-        let error_block = Block::default().borders(Borders::ALL).title("Error");
+        let error_block = app.theme.block().title("Error");
 
-        let paragraph = ratatui::widgets::Paragraph::new(err.clone())
+        let paragraph = Paragraph::new(err.clone())
             .block(error_block)
-            .style(Style::default().fg(ratatui::style::Color::Red));
+            .style(app.theme.bad());
+
+        f.render_widget(paragraph, chunks[2]);
+    } else if let Some(status) = status_text(app) {
+        let width = chunks[2].width.saturating_sub(2) as usize;
+        let paragraph = Paragraph::new(text::truncate_to_width(
+            &status,
+            width,
+            app.theme.glyphs.ellipsis,
+        ))
+        .block(app.theme.block().title("Status"))
+        .style(app.theme.accent());
+
+        f.render_widget(paragraph, chunks[2]);
+    }
+
+    let footer =
+        Paragraph::new(footer_text(app, chunks[3].width as usize)).style(app.theme.muted());
+    f.render_widget(footer, chunks[3]);
+}
+
+/// Width of the session preview beside the menu.
+const PREVIEW_WIDTH: u16 = 28;
+
+fn preview_text(preview: &SessionPreview, glyphs: &Glyphs) -> String {
+    let mut lines = vec![format!(
+        "{} {}",
+        preview.words,
+        if preview.words == 1 { "word" } else { "words" }
+    )];
+    if let Some((group_id, name)) = &preview.group {
+        lines.push(match name {
+            Some(name) => format!("Group {group_id}: {name}"),
+            None => format!("Group {group_id}"),
+        });
+    }
+    lines.push(format!(
+        "{} new{}{} review",
+        preview.new,
+        glyphs.separator,
+        preview.review()
+    ));
+    lines.push(format!(
+        "Time: {}",
+        preview::duration_label(preview.estimate())
+    ));
+    lines.join("\n")
+}
+
+/// How to get words into an empty database.
+fn guidance_text(app: &App) -> String {
+    format!(
+        "{} has no words yet.\n\
+         \n\
+         Seed it from the shell:   vocabulator seed <file>\n\
+         or select \"{}\" to import a file from here.\n\
+         \n\
+         Seed files list words under group headers:\n  \
+           Group 1\n  \
+           abate  to lessen in intensity",
+        app.db_path,
+        MenuAction::Session(Type::Group).label(app)
+    )
+}
+
+fn status_text(app: &App) -> Option<String> {
+    let Glyphs {
+        enter, separator, ..
+    } = app.theme.glyphs;
+    if let Some(filter) = &app.menu_filter {
+        return Some(format!(
+            "/{}  ({enter} open top match{separator}esc clear)",
+            filter.text()
+        ));
+    }
+    match app.list_nav.jump_target() {
+        Some(group) => Some(format!(
+            "Go to group: {group}  ({enter} start{separator}esc cancel)"
+        )),
+        None => app.status.clone(),
+    }
+}
+
+/// `<db path> · <n> words · v<version>`, with the path middle-truncated so
+/// the line fits in `width` columns and the rest right-aligned.
+fn footer_text(app: &App, width: usize) -> String {
+    let Glyphs {
+        separator,
+        ellipsis,
+        ..
+    } = app.theme.glyphs;
+    let rest = format!(
+        "{separator}{} words{separator}v{}",
+        app.counts.words,
+        env!("CARGO_PKG_VERSION")
+    );
+    let profile = app
+        .profile
+        .as_ref()
+        .map(|name| format!("[{name}] "))
+        .unwrap_or_default();
+    let path_width = width.saturating_sub(profile.width() + rest.width());
+    let path = text::truncate_middle(&app.db_path, path_width, ellipsis);
+    format!(
+        "{profile}{}{rest}",
+        text::pad_to_width(&path, path_width, ellipsis)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use crate::ui::keymap::KeyMap;
+    use crate::ui::screens::{practice, test};
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+
+    fn press(app: &mut App, code: KeyCode, handler: fn(&mut App, KeyEvent)) {
+        handler(
+            app,
+            KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press),
+        );
+    }
+
+    #[test]
+    fn test_enter_on_disabled_entry_explains() {
+        let mut app = App::new_test();
+        app.selected = 1;
+
+        press(&mut app, KeyCode::Enter, handle_event);
+
+        assert!(app.session.is_none());
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert!(app.status.as_deref().unwrap().contains("No marked words"));
+    }
+
+    #[test]
+    fn test_counts_refresh_after_marking() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(word,definition,group_id) VALUES('abound','plentiful',1)",
+            [],
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        assert_eq!(app.counts.marked, 0);
+
+        press(&mut app, KeyCode::Enter, handle_event);
+        assert_eq!(app.current_screen, Screen::Practice);
+
+        for code in [
+            KeyCode::Char('m'),
+            KeyCode::Char('s'),
+            KeyCode::Char('y'),
+            KeyCode::Enter,
+        ] {
+            press(&mut app, code, practice::handle_event);
+        }
+        assert_eq!(app.current_screen, Screen::Test);
+
+        press(&mut app, KeyCode::Enter, test::handle_event);
+        press(&mut app, KeyCode::Enter, test::handle_event);
+
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert_eq!(app.counts.marked, 1);
+        assert_eq!(app.counts.unseen, 0);
+    }
+
+    #[test]
+    fn test_footer_truncates_long_path() {
+        let mut app = App::new_test();
+        app.db_path = format!("/home/user/{}/vocab.db", "nested/".repeat(20));
+        app.counts.words = 42;
+
+        let footer = footer_text(&app, 50);
+        assert_eq!(footer.chars().count(), 50);
+        assert!(footer.starts_with("/home/"));
+        assert!(footer.contains("…"));
+        assert!(footer.ends_with(&format!(
+            "vocab.db · 42 words · v{}",
+            env!("CARGO_PKG_VERSION")
+        )));
+
+        app.db_path = "/tmp/vocab.db".into();
+        let footer = footer_text(&app, 50);
+        assert_eq!(footer.width(), 50);
+        assert!(footer.starts_with("/tmp/vocab.db "));
+        assert!(footer.ends_with(&format!(" · 42 words · v{}", env!("CARGO_PKG_VERSION"))));
+    }
+
+    #[test]
+    fn test_footer_shows_profile() {
+        let mut app = App::new_test();
+        app.db_path = "/data/vocabulator/alice/vocab.db".into();
+        app.profile = Some("alice".into());
+
+        let footer = footer_text(&app, 60);
+        assert_eq!(footer.width(), 60);
+        assert!(footer.starts_with("[alice] /data/vocabulator/alice/vocab.db"));
+    }
+
+    fn seeded_groups() -> App {
+        let conn = test_conn();
+        for group in 1..=12 {
+            conn.execute(
+                "INSERT INTO words(word,definition,group_id) VALUES(?1,'def',?2)",
+                rusqlite::params![format!("w{group}"), group],
+            )
+            .unwrap();
+        }
+        App::new(conn, KeyMap::default())
+    }
+
+    #[test]
+    fn test_group_jump_starts_session() {
+        let mut app = seeded_groups();
+        for c in "g12".chars() {
+            press(&mut app, KeyCode::Char(c), handle_event);
+        }
+        assert!(status_text(&app).unwrap().contains("12"));
+
+        press(&mut app, KeyCode::Enter, handle_event);
+        assert_eq!(app.current_screen, Screen::Practice);
+        assert_eq!(
+            app.session.as_ref().unwrap().current().unwrap().group_id,
+            12
+        );
+    }
+
+    #[test]
+    fn test_group_jump_invalid_group() {
+        let mut app = seeded_groups();
+        for c in "g13".chars() {
+            press(&mut app, KeyCode::Char(c), handle_event);
+        }
+        press(&mut app, KeyCode::Enter, handle_event);
+
+        assert_eq!(app.current_screen, Screen::Menu);
+        let toast = app.toast.as_ref().unwrap();
+        assert!(toast.is_error);
+        assert!(toast.message.contains("1-12"));
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("", "Exit"));
+        assert!(fuzzy_match("stat", "Statistics"));
+        assert!(fuzzy_match("rvw", "Review Marks (3)"));
+        assert!(fuzzy_match("REV weak", "Revise Weak (0)"));
+        assert!(!fuzzy_match("xit e", "Exit"));
+    }
+
+    #[test]
+    fn test_slash_filter_takes_priority_over_bindings() {
+        let mut app = seeded_groups();
+
+        // Without the filter, j and q are bindings.
+        press(&mut app, KeyCode::Char('j'), handle_event);
+        assert_eq!(app.menu_items[app.selected], MenuAction::Stats);
+
+        press(&mut app, KeyCode::Char('/'), handle_event);
+        for c in "quit".chars() {
+            press(&mut app, KeyCode::Char(c), handle_event);
+        }
+        assert!(!app.should_quit);
+        assert!(filtered_items(&app).is_empty());
+
+        press(&mut app, KeyCode::Esc, handle_event);
+        assert!(app.menu_filter.is_none());
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn test_enter_activates_top_match() {
+        let mut app = seeded_groups();
+
+        press(&mut app, KeyCode::Char('/'), handle_event);
+        for c in "stat".chars() {
+            press(&mut app, KeyCode::Char(c), handle_event);
+        }
+        press(&mut app, KeyCode::Enter, handle_event);
+
+        assert!(app.menu_filter.is_none());
+        assert_eq!(app.current_screen, Screen::Stats);
+    }
+
+    #[test]
+    fn test_empty_database_opens_import() {
+        let mut app = App::new_test();
+        app.db_path = "/data/vocab.db".into();
+        assert!(guidance_text(&app).contains("/data/vocab.db has no words"));
+        assert!(guidance_text(&app).contains("vocabulator seed <file>"));
 
-        f.render_widget(paragraph, chunks[1]);
+        press(&mut app, KeyCode::Enter, handle_event);
+        assert_eq!(app.current_screen, Screen::Splash);
+        assert!(app.error.is_none());
     }
 }
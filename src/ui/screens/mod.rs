@@ -1,3 +1,7 @@
 pub mod menu;
 pub mod practice;
+pub mod recovery;
+pub mod review_log;
+pub mod splash;
+pub mod stats;
 pub mod test;
@@ -1,11 +1,16 @@
+use crate::core::session::Session;
+use crate::core::stats::outcome_glyphs;
 use crate::core::{actions, utils};
-use crate::ui::app::{App, Screen};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::ui::app::App;
+use crate::ui::keymap::Action;
+use crate::ui::text;
+use crate::ui::theme::Theme;
+use crossterm::event::KeyEvent;
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     prelude::*,
     text::{Line, Span},
-    widgets::{Block, Borders, Padding, Paragraph},
+    widgets::{Block, Padding, Paragraph, Wrap},
 };
 
 pub fn handle_event(app: &mut App, key: KeyEvent) {
@@ -14,47 +19,72 @@ pub fn handle_event(app: &mut App, key: KeyEvent) {
         None => return,
     };
 
-    match key.code {
-        KeyCode::Char('q') | KeyCode::Esc => {
+    match app.keymap.action_for(key) {
+        Some(Action::Quit) => {
             app.session = None;
-            app.current_screen = Screen::Menu;
+            app.return_to_menu();
         }
-        KeyCode::Char('s') => {
+        // Read-aloud shows and moves on by itself.
+        Some(
+            Action::Show
+            | Action::Correct
+            | Action::Wrong
+            | Action::Confirm
+            | Action::QuickCorrect
+            | Action::QuickWrong,
+        ) if session.read_aloud.is_some() => {}
+        Some(Action::ReadAloud) => app.toggle_read_aloud(),
+        Some(Action::Show) => {
             session.show_definition = true;
         }
-        KeyCode::Char('y') => {
-            if session.show_definition {
-                session.graded = Some(true);
-            }
+        Some(Action::Correct) if session.show_definition => {
+            session.graded = Some(true);
         }
-        KeyCode::Char('n') => {
-            if session.show_definition {
-                session.graded = Some(false);
-            }
+        Some(Action::Wrong) if session.show_definition => {
+            session.graded = Some(false);
         }
-        KeyCode::Char('m') => {
-            let word = session.current_mut();
-            word.marked = !word.marked;
+        Some(Action::Mark) => app.toggle_mark(),
+        Some(Action::Focus) => app.focus_mode = !app.focus_mode,
+        Some(Action::Pause) => session.pause(app.clock.now()),
+        Some(Action::CopyWord) => app.copy_current(false),
+        Some(Action::CopyEntry) => app.copy_current(true),
+        Some(Action::Confirm) if session.show_definition && session.graded.is_some() => {
+            advance(app);
         }
-        KeyCode::Enter => {
-            if session.show_definition && session.graded.is_some() {
-                if let Err(e) = actions::handle_enter(app) {
-                    app.error = Some(e.to_string());
-                    app.current_screen = Screen::Menu;
-                }
-            }
+        // Show, grade and Confirm in one go. Not once the definition is
+        // up, where the word may already be graded.
+        Some(action @ (Action::QuickCorrect | Action::QuickWrong)) if !session.show_definition => {
+            session.show_definition = true;
+            session.graded = Some(action == Action::QuickCorrect);
+            advance(app);
         }
         _ => {}
     }
 }
 
+fn advance(app: &mut App) {
+    if let Err(e) = actions::handle_enter(app) {
+        tracing::error!("advancing session failed: {e}");
+        app.return_to_menu();
+        app.error = Some(e.to_string());
+    }
+}
+
 pub fn render(frame: &mut Frame, app: &App) {
     let session = match &app.session {
         Some(s) => s,
         None => return,
     };
 
-    let word = session.current();
+    let theme = &app.theme;
+    if app.focus_mode {
+        render_focus(frame, session, theme);
+        return;
+    }
+
+    let Some(word) = session.current() else {
+        return;
+    };
     let area = frame.size();
 
     let layout = Layout::default()
@@ -64,7 +94,7 @@ pub fn render(frame: &mut Frame, app: &App) {
             Constraint::Length(3), // Header
             Constraint::Length(5), // Word
             Constraint::Length(5), // Definition
-            Constraint::Length(4), // Stats
+            Constraint::Length(5), // Stats
             Constraint::Length(5), // Actions
         ])
         .split(area);
@@ -75,40 +105,52 @@ pub fn render(frame: &mut Frame, app: &App) {
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(layout[0]);
 
-    let left_header = Paragraph::new(format!(
-        "{} WORD [{}/{}]",
-        if word.marked { "*" } else { " " },
-        session.index + 1,
-        session.words.len()
+    // Header blocks have borders and one column of padding on each side.
+    let header_width = |area: Rect| area.width.saturating_sub(4) as usize;
+
+    let left_header = Paragraph::new(text::truncate_to_width(
+        &format!(
+            "{} WORD [{}/{}]{}{}",
+            if word.marked { "*" } else { " " },
+            session.index + 1,
+            session.words.len(),
+            // Marked word from another group, mixed into this one.
+            if session.is_mixed_in(session.index) {
+                format!("{}marked", theme.glyphs.separator)
+            } else {
+                String::new()
+            },
+            if session.read_aloud.is_some() {
+                format!("{}reading aloud", theme.glyphs.separator)
+            } else {
+                String::new()
+            }
+        ),
+        header_width(header_chunks[0]),
+        theme.glyphs.ellipsis,
     ))
     .alignment(Alignment::Center)
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .padding(Padding::horizontal(1)),
-    );
+    .block(theme.block().padding(Padding::horizontal(1)));
 
-    let right_header = Paragraph::new(format!("Group {} | Id {}", word.group_id, word.id))
-        .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .padding(Padding::horizontal(1)),
-        );
+    let right_header = Paragraph::new(text::truncate_to_width(
+        &format!("Group {} | Id {}", word.group_id, word.id),
+        header_width(header_chunks[1]),
+        theme.glyphs.ellipsis,
+    ))
+    .alignment(Alignment::Center)
+    .block(theme.block().padding(Padding::horizontal(1)));
 
     frame.render_widget(left_header, header_chunks[0]);
     frame.render_widget(right_header, header_chunks[1]);
 
     // ───────── WORD ─────────
     let word_style = match session.graded {
-        Some(true) => Style::default().fg(Color::Green),
-        Some(false) => Style::default().fg(Color::Red),
+        Some(true) => theme.good(),
+        Some(false) => theme.bad(),
         None => Style::default(),
     };
 
-    let word_block = Block::default()
-        .borders(Borders::ALL)
-        .padding(Padding::horizontal(1));
+    let word_block = theme.block().padding(Padding::horizontal(1));
 
     let inner = word_block.inner(layout[1]);
     frame.render_widget(word_block, layout[1]);
@@ -122,49 +164,55 @@ pub fn render(frame: &mut Frame, app: &App) {
         ])
         .split(inner);
 
-    let word_para = Paragraph::new(word.word.clone())
-        .style(word_style)
-        .alignment(Alignment::Center)
-        .bold();
+    let ipa = session.ipa.as_deref().filter(|_| session.show_definition);
+    let word_para =
+        Paragraph::new(word_line(&word.word, ipa, word_style, theme)).alignment(Alignment::Center);
 
     frame.render_widget(word_para, vertical[1]);
+    let reading = session
+        .reading
+        .as_deref()
+        .filter(|_| session.show_definition || !app.hide_reading);
+    render_reading(frame, reading, vertical[2], theme);
 
     // ───────── DEFINITION ─────────
     let def_text = if session.show_definition {
-        word.definition.clone()
+        word.definition.as_str()
     } else {
-        "(hidden)".into()
+        "(hidden)"
     };
 
     let definition = Paragraph::new(def_text).alignment(Alignment::Center).block(
-        Block::default()
+        theme
+            .block()
             .title("Definition")
-            .borders(Borders::ALL)
             .padding(Padding::horizontal(1)),
     );
 
     frame.render_widget(definition, layout[2]);
 
     // ───────── STATS ─────────
+    let recent = session
+        .history
+        .as_deref()
+        .map(|outcomes| outcome_glyphs(outcomes, theme.glyphs))
+        .unwrap_or_else(|| "-".into());
+
     let stats = Paragraph::new(format!(
-        "Last Seen: {}\nAccuracy: {}/{}",
-        utils::relative_time(word.last_seen),
+        "Last Seen: {}\nAccuracy: {}/{}\nRecent: {}",
+        utils::relative_time(word.last_seen, app.clock.now()),
         word.success_count,
-        word.times_seen
+        word.times_seen,
+        recent
     ))
-    .block(
-        Block::default()
-            .title("Stats")
-            .borders(Borders::ALL)
-            .padding(Padding::horizontal(1)),
-    );
+    .block(stats_block(session.leech, theme));
 
     frame.render_widget(stats, layout[3]);
 
     // ───────── ACTION BUTTONS ─────────
-    let actions_block = Block::default()
+    let actions_block = theme
+        .block()
         .title("Actions")
-        .borders(Borders::ALL)
         .padding(Padding::horizontal(1));
 
     let inner_actions = actions_block.inner(layout[4]);
@@ -181,23 +229,389 @@ pub fn render(frame: &mut Frame, app: &App) {
         ])
         .split(inner_actions);
 
-    render_button(frame, buttons[0], "Show", "s");
-    render_button(frame, buttons[1], "Correct", "y");
-    render_button(frame, buttons[2], "Wrong", "n");
-    render_button(frame, buttons[3], "Mark", "m");
-    render_button(frame, buttons[4], "Next", "⏎");
+    let key = |action| app.keymap.label(action, theme.glyphs);
+    render_button(frame, buttons[0], "Show", &key(Action::Show), theme);
+    render_button(frame, buttons[1], "Correct", &key(Action::Correct), theme);
+    render_button(frame, buttons[2], "Wrong", &key(Action::Wrong), theme);
+    render_button(frame, buttons[3], "Mark", &key(Action::Mark), theme);
+    render_button(frame, buttons[4], "Next", &key(Action::Confirm), theme);
+}
+
+/// Word and definition alone, centered on an otherwise empty screen.
+fn render_focus(frame: &mut Frame, session: &Session, theme: &Theme) {
+    let Some(word) = session.current() else {
+        return;
+    };
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Percentage(35),
+            Constraint::Length(2), // Word
+            Constraint::Min(0),    // Definition
+        ])
+        .split(frame.size());
+
+    let word_style = match session.graded {
+        Some(true) => theme.good(),
+        Some(false) => theme.bad(),
+        None => Style::default(),
+    };
+
+    let word_para = Paragraph::new(word.word.as_str())
+        .style(word_style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED))
+        .alignment(Alignment::Center);
+
+    frame.render_widget(word_para, layout[1]);
+
+    let def_text = if session.show_definition {
+        word.definition.as_str()
+    } else {
+        ""
+    };
+
+    let definition = Paragraph::new(def_text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .style(theme.soft());
+
+    frame.render_widget(definition, layout[2]);
+}
+
+/// The word in bold, followed by its IPA dimmed when given.
+pub fn word_line<'a>(word: &'a str, ipa: Option<&str>, style: Style, theme: &Theme) -> Line<'a> {
+    let mut spans = vec![Span::styled(word, style.bold())];
+    if let Some(ipa) = ipa {
+        spans.push(Span::styled(format!("  /{ipa}/"), theme.muted()));
+    }
+    Line::from(spans)
 }
 
-fn render_button(frame: &mut Frame, area: Rect, label: &str, key: &str) {
+/// The reading dimmed on the line under the word, at the top of `below`.
+pub fn render_reading(frame: &mut Frame, reading: Option<&str>, below: Rect, theme: &Theme) {
+    let Some(reading) = reading else {
+        return;
+    };
+    let line = Rect {
+        height: below.height.min(1),
+        ..below
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(reading.to_string(), theme.muted()))
+            .alignment(Alignment::Center),
+        line,
+    );
+}
+
+/// Stats block, titled with a warning badge while the word is a leech.
+fn stats_block(leech: bool, theme: &Theme) -> Block<'static> {
+    let block = theme.block().padding(Padding::horizontal(1));
+    if leech {
+        block
+            .title(Line::from(vec![
+                Span::raw("Stats "),
+                Span::styled(
+                    format!("{} leech", theme.glyphs.warning),
+                    theme.accent().bold(),
+                ),
+            ]))
+            .border_style(theme.accent())
+    } else {
+        block.title("Stats")
+    }
+}
+
+fn render_button(frame: &mut Frame, area: Rect, label: &str, key: &str, theme: &Theme) {
     let content = Line::from(vec![
         Span::styled(label, Style::default().bold()),
         Span::raw("\n"),
-        Span::styled(format!("[{}]", key), Style::default().fg(Color::Yellow)),
+        Span::styled(format!("[{}]", key), theme.accent()),
     ]);
 
     let button = Paragraph::new(content)
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+        .block(theme.block());
 
     frame.render_widget(button, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::MockClipboard;
+    use crate::config;
+    use crate::core::session::Type;
+    use crate::db::models::Word;
+    use crate::db::test_conn;
+    use crate::ui::app::Screen;
+    use crate::ui::keymap::KeyMap;
+    use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn app_with_keys(keys: &str) -> App {
+        let config = config::parse(keys).unwrap();
+        let keymap = KeyMap::from_config(&config.keys).unwrap();
+        let mut app = App::new(test_conn(), keymap);
+        let word = Word {
+            id: 1,
+            word: "abound".into(),
+            definition: "be present in large quantities".into(),
+            group_id: 1,
+            marked: false,
+            last_seen: None,
+            times_seen: 0,
+            success_count: 0,
+        };
+        app.session = Some(Session::new(vec![word], 0, Type::Group));
+        app.current_screen = Screen::Practice;
+        app
+    }
+
+    fn press(app: &mut App, c: char) {
+        handle_event(
+            app,
+            KeyEvent::new_with_kind(KeyCode::Char(c), KeyModifiers::NONE, KeyEventKind::Press),
+        );
+    }
+
+    /// With a 10 KB definition showing, a frame allocates about 17 KB, mostly
+    /// ratatui's own buffer work; cloning the definition took it to 37 KB.
+    /// The bound sits between the two, so a copy of it per frame fails.
+    /// The header and stats strings are small and still formatted each
+    /// frame rather than cached.
+    #[test]
+    fn test_render_allocations() {
+        use crate::ui::testing::allocations;
+
+        let mut app = app_with_keys("");
+        let session = app.session.as_mut().unwrap();
+        session.words[0].definition = "to be present in large quantities ".repeat(300);
+        session.show_definition = true;
+        let definition_len = session.words[0].definition.len() as u64;
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| render(f, &app)).unwrap();
+
+        let frames = 100;
+        let (_, bytes) = allocations();
+        for _ in 0..frames {
+            terminal.draw(|f| render(f, &app)).unwrap();
+        }
+        let (_, bytes_after) = allocations();
+        let per_frame = (bytes_after - bytes) / frames;
+        assert!(
+            per_frame < 2 * definition_len,
+            "{per_frame} bytes per frame for a {definition_len}-byte definition"
+        );
+    }
+
+    #[test]
+    fn test_remapped_show_key() {
+        let mut app = app_with_keys("[keys]\nshow = \"d\"");
+
+        press(&mut app, 's');
+        assert!(!app.session.as_ref().unwrap().show_definition);
+
+        press(&mut app, 'd');
+        assert!(app.session.as_ref().unwrap().show_definition);
+    }
+
+    #[test]
+    fn test_grade_requires_definition() {
+        let mut app = app_with_keys("");
+
+        press(&mut app, 'y');
+        assert_eq!(app.session.as_ref().unwrap().graded, None);
+
+        press(&mut app, 's');
+        press(&mut app, 'n');
+        assert_eq!(app.session.as_ref().unwrap().graded, Some(false));
+    }
+
+    #[test]
+    fn test_copy_word_and_definition() {
+        let mut app = app_with_keys("");
+        let clipboard = MockClipboard::default();
+        app.clipboard = Box::new(clipboard.clone());
+
+        press(&mut app, 'c');
+        press(&mut app, 'C');
+
+        assert_eq!(
+            *clipboard.copied.borrow(),
+            vec!["abound", "abound\nbe present in large quantities"]
+        );
+        assert!(!app.toast.as_ref().unwrap().is_error);
+    }
+
+    #[test]
+    fn test_copy_failure_shows_error_toast() {
+        let mut app = app_with_keys("");
+        app.clipboard = Box::new(MockClipboard {
+            fail: true,
+            ..Default::default()
+        });
+
+        press(&mut app, 'c');
+
+        let toast = app.toast.as_ref().unwrap();
+        assert!(toast.is_error);
+        assert!(toast.message.contains("clipboard unavailable"));
+    }
+
+    fn draw(app: &App) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(60, 30)).unwrap();
+        terminal.draw(|f| render(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn row_of(lines: &[String], needle: &str) -> Option<usize> {
+        lines.iter().position(|l| l.contains(needle))
+    }
+
+    #[test]
+    fn test_focus_mode_hides_blocks() {
+        let mut app = app_with_keys("");
+        press(&mut app, 's');
+
+        let normal = draw(&app);
+        for title in ["WORD [1/1]", "Stats", "Actions", "Definition"] {
+            assert!(row_of(&normal, title).is_some(), "{title} missing");
+        }
+
+        press(&mut app, 'f');
+        let focused = draw(&app);
+        for title in ["WORD [1/1]", "Stats", "Actions", "Definition"] {
+            assert!(row_of(&focused, title).is_none(), "{title} still shown");
+        }
+
+        let word_row = row_of(&focused, "abound").unwrap();
+        let def_row = row_of(&focused, "be present").unwrap();
+        assert!(word_row > row_of(&normal, "abound").unwrap());
+        assert!(def_row > word_row);
+    }
+
+    #[test]
+    fn test_leech_badge() {
+        let mut app = app_with_keys("");
+        assert!(row_of(&draw(&app), "leech").is_none());
+
+        app.session.as_mut().unwrap().leech = true;
+        let screen = draw(&app);
+        assert_eq!(row_of(&screen, "⚠ leech"), row_of(&screen, "Stats"));
+    }
+
+    #[test]
+    fn test_ipa_follows_the_definition() {
+        let mut app = app_with_keys("");
+        app.session.as_mut().unwrap().ipa = Some("əˈbaʊnd".into());
+        assert!(row_of(&draw(&app), "əˈbaʊnd").is_none());
+
+        press(&mut app, 's');
+        let screen = draw(&app);
+        assert_eq!(
+            row_of(&screen, "abound  /əˈbaʊnd/"),
+            row_of(&screen, "abound")
+        );
+    }
+
+    #[test]
+    fn test_reading_under_the_word() {
+        let mut app = app_with_keys("");
+        app.session.as_mut().unwrap().reading = Some("uh-bownd".into());
+        let screen = draw(&app);
+        assert_eq!(
+            row_of(&screen, "uh-bownd"),
+            row_of(&screen, "abound").map(|row| row + 1)
+        );
+
+        app.hide_reading = true;
+        assert!(row_of(&draw(&app), "uh-bownd").is_none());
+        press(&mut app, 's');
+        assert!(row_of(&draw(&app), "uh-bownd").is_some());
+    }
+
+    /// What a grade leaves behind: the session's place and every row it
+    /// writes, times aside.
+    fn graded_state(app: &App) -> (usize, bool, Option<bool>, Vec<String>) {
+        let session = app.session.as_ref().unwrap();
+        let rows = app
+            .conn
+            .prepare(
+                "SELECT 'word ' || id || ' ' || times_seen || '/' || success_count FROM words
+                 UNION ALL SELECT 'review ' || word_id || ' ' || correct || ' ' || mode FROM reviews
+                 UNION ALL SELECT 'state ' || key || '=' || value FROM app_state",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        (session.index, session.show_definition, session.graded, rows)
+    }
+
+    #[test]
+    fn test_quick_grades_match_the_manual_keys() {
+        let words = [
+            ("abate", "lessen", 1),
+            ("abound", "plentiful", 1),
+            ("wan", "pale", 1),
+        ];
+        let mut manual = App::new_test_seeded(&words);
+        let mut quick = App::new_test_seeded(&words);
+
+        for c in ['s', 'y', '\n', 's', 'n', '\n'] {
+            match c {
+                '\n' => handle_event(
+                    &mut manual,
+                    KeyEvent::new_with_kind(
+                        KeyCode::Enter,
+                        KeyModifiers::NONE,
+                        KeyEventKind::Press,
+                    ),
+                ),
+                c => press(&mut manual, c),
+            }
+        }
+        press(&mut quick, 'Y');
+        press(&mut quick, 'N');
+
+        let state = graded_state(&quick);
+        assert_eq!(state, graded_state(&manual));
+        assert_eq!((state.0, state.1, state.2), (2, false, None));
+        assert_eq!(
+            quick.session.as_ref().unwrap().history,
+            manual.session.as_ref().unwrap().history
+        );
+    }
+
+    #[test]
+    fn test_quick_grades_wait_while_the_definition_shows() {
+        let mut app = App::new_test_seeded(&[("abate", "lessen", 1), ("wan", "pale", 1)]);
+        press(&mut app, 's');
+        press(&mut app, 'y');
+        let before = graded_state(&app);
+
+        press(&mut app, 'N');
+        press(&mut app, 'Y');
+        assert_eq!(graded_state(&app), before);
+        assert_eq!(before.2, Some(true));
+    }
+
+    #[test]
+    fn test_focus_mode_keeps_bindings_and_persists() {
+        let mut app = app_with_keys("");
+        press(&mut app, 'f');
+
+        press(&mut app, 's');
+        press(&mut app, 'y');
+        assert_eq!(app.session.as_ref().unwrap().graded, Some(true));
+        assert!(app.focus_mode);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::core::clock::SystemClock;
+use crate::db::health::{self, Damage};
+use crate::doctor;
+use crate::ui::keymap::{Action, KeyMap};
+use crate::ui::list_nav::{self, NavMove};
+use crate::ui::theme::Theme;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecoveryOption {
+    Repair,
+    Restore,
+    Quit,
+}
+
+impl RecoveryOption {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RecoveryOption::Repair => "Run repair (doctor --fix)",
+            RecoveryOption::Restore => "Restore from latest backup",
+            RecoveryOption::Quit => "Quit",
+        }
+    }
+}
+
+/// What the run loop should do after a key on the recovery screen.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Stay,
+    /// Something was changed; try opening the database again.
+    Reopen,
+    Quit,
+}
+
+/// Shown instead of the app when `init_db` finds the database damaged.
+#[derive(Debug)]
+pub struct RecoveryState {
+    pub db_path: String,
+    pub damage: Damage,
+    /// Newest first.
+    pub backups: Vec<PathBuf>,
+    pub options: Vec<RecoveryOption>,
+    pub selected: usize,
+    /// Result of the last repair or restore, kept across reopen attempts.
+    pub message: Option<String>,
+}
+
+impl RecoveryState {
+    pub fn new(db_path: &str, damage: Damage) -> Self {
+        let mut state = Self {
+            db_path: db_path.to_string(),
+            damage: damage.clone(),
+            backups: Vec::new(),
+            options: Vec::new(),
+            selected: 0,
+            message: None,
+        };
+        state.refresh(damage);
+        state
+    }
+
+    /// Picks up the damage found by the latest open attempt.
+    pub fn refresh(&mut self, damage: Damage) {
+        self.damage = damage;
+        self.backups = health::backups(&self.db_path);
+        self.options = vec![RecoveryOption::Repair];
+        if !self.backups.is_empty() {
+            self.options.push(RecoveryOption::Restore);
+        }
+        self.options.push(RecoveryOption::Quit);
+        self.selected = self.selected.min(self.options.len() - 1);
+    }
+}
+
+pub fn handle_event(state: &mut RecoveryState, keymap: &KeyMap, key: KeyEvent) -> Outcome {
+    let len = state.options.len();
+    match keymap.action_for(key) {
+        Some(Action::Down) => {
+            state.selected = list_nav::apply(state.selected, len, NavMove::Down(1))
+        }
+        Some(Action::Up) => state.selected = list_nav::apply(state.selected, len, NavMove::Up(1)),
+        Some(Action::Quit) => return Outcome::Quit,
+        Some(Action::Confirm) => return choose(state),
+        _ => {}
+    }
+    Outcome::Stay
+}
+
+fn choose(state: &mut RecoveryState) -> Outcome {
+    let result = match state.options[state.selected] {
+        RecoveryOption::Repair => {
+            doctor::repair(&state.db_path, &SystemClock).map(|report| report.join("\n"))
+        }
+        RecoveryOption::Restore => {
+            let backup = &state.backups[0];
+            health::restore(&state.db_path, backup).map(|kept| {
+                format!(
+                    "Restored {}. The damaged file was kept as {}",
+                    backup.display(),
+                    kept.display()
+                )
+            })
+        }
+        RecoveryOption::Quit => return Outcome::Quit,
+    };
+    match result {
+        Ok(message) => {
+            state.message = Some(message);
+            Outcome::Reopen
+        }
+        Err(e) => {
+            state.message = Some(format!("Failed: {e}"));
+            Outcome::Stay
+        }
+    }
+}
+
+pub fn render(f: &mut Frame, state: &RecoveryState, theme: &Theme) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(5),
+            Constraint::Length(state.options.len() as u16 + 2),
+            Constraint::Min(3),
+            Constraint::Length(if state.message.is_some() { 6 } else { 0 }),
+        ])
+        .split(f.size());
+
+    let intro = Paragraph::new(format!(
+        "{} can't be opened: {}",
+        state.db_path, state.damage
+    ))
+    .style(theme.bad())
+    .wrap(Wrap { trim: false })
+    .block(theme.block().title("Database needs attention"));
+    f.render_widget(intro, chunks[0]);
+
+    let items: Vec<ListItem> = state
+        .options
+        .iter()
+        .map(|option| ListItem::new(option.label()))
+        .collect();
+    let mut list_state = ListState::default();
+    list_state.select(Some(state.selected));
+    let list = List::new(items)
+        .block(theme.block().title("Options"))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, chunks[1], &mut list_state);
+
+    let backups: Vec<ListItem> = if state.backups.is_empty() {
+        vec![ListItem::new("None found next to the database")]
+    } else {
+        state
+            .backups
+            .iter()
+            .map(|path| ListItem::new(path.display().to_string()))
+            .collect()
+    };
+    f.render_widget(
+        List::new(backups).block(theme.block().title("Backups")),
+        chunks[2],
+    );
+
+    if let Some(message) = &state.message {
+        let message = Paragraph::new(message.as_str())
+            .wrap(Wrap { trim: false })
+            .block(theme.block().title("Last attempt"));
+        f.render_widget(message, chunks[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_db;
+    use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+    use std::fs;
+
+    fn press(state: &mut RecoveryState, code: KeyCode) -> Outcome {
+        handle_event(
+            state,
+            &KeyMap::default(),
+            KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press),
+        )
+    }
+
+    fn damaged(dir: &tempfile::TempDir) -> RecoveryState {
+        let path = dir.path().join("vocab.db");
+        let path = path.to_str().unwrap();
+        fs::write(path, "not a database ".repeat(100)).unwrap();
+        let damage = init_db(path).unwrap_err().downcast().unwrap();
+        RecoveryState::new(path, damage)
+    }
+
+    #[test]
+    fn test_options_without_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = damaged(&dir);
+        assert_eq!(
+            state.options,
+            vec![RecoveryOption::Repair, RecoveryOption::Quit]
+        );
+
+        // Garbage can't be repaired; reopening fails again and comes back
+        // here with the report.
+        assert_eq!(press(&mut state, KeyCode::Enter), Outcome::Reopen);
+        assert!(
+            state
+                .message
+                .as_deref()
+                .unwrap()
+                .contains("restore a backup")
+        );
+
+        press(&mut state, KeyCode::Down);
+        assert_eq!(press(&mut state, KeyCode::Enter), Outcome::Quit);
+    }
+
+    #[test]
+    fn test_restore_latest_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let backup = dir.path().join("vocab-20240309.db");
+        init_db(backup.to_str().unwrap())
+            .unwrap()
+            .execute(
+                "INSERT INTO words(word,definition,group_id) VALUES('a','b',1)",
+                [],
+            )
+            .unwrap();
+        let mut state = damaged(&dir);
+        assert_eq!(state.backups, vec![backup]);
+
+        press(&mut state, KeyCode::Down);
+        assert_eq!(press(&mut state, KeyCode::Enter), Outcome::Reopen);
+
+        let conn = init_db(&state.db_path).unwrap();
+        let words: i64 = conn
+            .query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(words, 1);
+        assert!(dir.path().join("vocab.db.damaged").exists());
+    }
+}
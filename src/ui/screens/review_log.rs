@@ -0,0 +1,412 @@
+use crate::core::{progress, utils};
+use crate::db::models::{DeletedWord, ReviewEntry};
+use crate::db::queries;
+use crate::ui::app::{App, Screen};
+use crate::ui::keymap::Action;
+use crate::ui::widgets::{Buttons, Modal, Toast};
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    widgets::{Paragraph, Row, Table, TableState},
+};
+use std::time::{Duration, Instant};
+
+/// Rows fetched per page; more are loaded as the selection nears the end.
+const PAGE_SIZE: usize = 100;
+/// How long after a delete `u` still brings the word back.
+pub const UNDO_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+pub struct ReviewLogState {
+    pub entries: Vec<ReviewEntry>,
+    pub selected: usize,
+    /// Start of the day being shown, as a unix timestamp.
+    pub since: i64,
+    /// Set once a page comes back short, so no further queries are made.
+    pub exhausted: bool,
+    /// Word the open confirmation modal would delete.
+    pub pending_delete: Option<i32>,
+    /// Last deleted word and when the chance to undo it runs out. Another
+    /// delete replaces it.
+    pub undo: Option<(DeletedWord, Instant)>,
+}
+
+pub fn open(app: &mut App) {
+    app.review_log = ReviewLogState {
+        since: utils::start_of_day(Local::now()),
+        ..Default::default()
+    };
+    load_more(app);
+    if app.error.is_none() {
+        app.current_screen = Screen::ReviewLog;
+    }
+}
+
+fn load_more(app: &mut App) {
+    let log = &mut app.review_log;
+    if log.exhausted {
+        return;
+    }
+
+    match queries::fetch_reviews_since(&app.conn, log.since, PAGE_SIZE, log.entries.len()) {
+        Ok(page) => {
+            log.exhausted = page.len() < PAGE_SIZE;
+            log.entries.extend(page);
+        }
+        Err(e) => app.error = Some(e.to_string()),
+    }
+}
+
+/// Reloads the pages shown so far after rows were removed or put back,
+/// keeping the selection where it was as far as possible.
+fn reload(app: &mut App) {
+    let selected = app.review_log.selected;
+    app.review_log.entries.clear();
+    app.review_log.exhausted = false;
+    while app.review_log.entries.len() <= selected + 10 && !app.review_log.exhausted {
+        let before = app.review_log.entries.len();
+        load_more(app);
+        if app.review_log.entries.len() == before {
+            break;
+        }
+    }
+    let len = app.review_log.entries.len();
+    app.review_log.selected = selected.min(len.saturating_sub(1));
+}
+
+pub fn handle_event(app: &mut App, key: KeyEvent) {
+    if key.code == KeyCode::Char('d') {
+        confirm_delete(app);
+        return;
+    }
+    if key.code == KeyCode::Char('u') {
+        undo(app, Instant::now());
+        return;
+    }
+
+    let log = &mut app.review_log;
+    match app.keymap.action_for(key) {
+        Some(Action::Quit) => app.current_screen = Screen::Stats,
+        Some(Action::Down) if log.selected + 1 < log.entries.len() => {
+            log.selected += 1;
+            if log.selected + 10 >= log.entries.len() {
+                load_more(app);
+            }
+        }
+        Some(Action::Up) => log.selected = log.selected.saturating_sub(1),
+        Some(Action::Confirm) => {
+            if let Some(entry) = log.entries.get(log.selected) {
+                let body = entry.definition.lines().map(String::from).collect();
+                app.modal = Some(Modal::new(&entry.word, body, Buttons::AnyKey).size(60, 40));
+            }
+        }
+        Some(Action::Mark) => toggle_mark(app),
+        _ => {}
+    }
+}
+
+fn confirm_delete(app: &mut App) {
+    if app.refuse_write("delete words") {
+        return;
+    }
+    let log = &mut app.review_log;
+    let Some(entry) = log.entries.get(log.selected) else {
+        return;
+    };
+    let body = vec![
+        format!("Delete '{}' with its stats and review history?", entry.word),
+        String::new(),
+        format!("u undoes it for {} seconds.", UNDO_WINDOW.as_secs()),
+    ];
+    log.pending_delete = Some(entry.word_id);
+    app.modal = Some(Modal::new("Delete word", body, Buttons::YesNo).size(60, 30));
+}
+
+/// Deletes the word once the modal is accepted and keeps it for `undo`.
+pub fn delete(app: &mut App, word_id: i32, now: Instant) {
+    match progress::delete_word(&app.conn, word_id) {
+        Ok(Some(deleted)) => {
+            let expires = now + UNDO_WINDOW;
+            let message = format!(
+                "Deleted '{}' {} press u to undo",
+                deleted.word, app.theme.glyphs.dash
+            );
+            app.toast = Some(Toast::new(message, false, now).until(expires));
+            app.review_log.undo = Some((deleted, expires));
+            reload(app);
+            app.refresh_counts();
+        }
+        Ok(None) => {}
+        Err(e) => app.notify(format!("Deleting failed: {e}"), true),
+    }
+}
+
+pub fn undo(app: &mut App, now: Instant) {
+    let Some((deleted, expires)) = app.review_log.undo.take() else {
+        app.notify("Nothing to undo", false);
+        return;
+    };
+    if now >= expires {
+        app.notify("Too late to undo", false);
+        return;
+    }
+
+    match progress::restore_word(&app.conn, &deleted) {
+        Ok(()) => {
+            app.notify(format!("Restored '{}'", deleted.word), false);
+            reload(app);
+            app.refresh_counts();
+        }
+        Err(e) => {
+            app.notify(format!("Undo failed: {e}"), true);
+            app.review_log.undo = Some((deleted, expires));
+        }
+    }
+}
+
+fn toggle_mark(app: &mut App) {
+    if app.refuse_write("mark words") {
+        return;
+    }
+    let log = &mut app.review_log;
+    let Some(entry) = log.entries.get(log.selected) else {
+        return;
+    };
+    let (word_id, marked) = (entry.word_id, !entry.marked);
+
+    if let Err(e) = queries::set_marked(&app.conn, word_id, marked) {
+        app.notify(format!("Marking failed: {e}"), true);
+        return;
+    }
+
+    for entry in log.entries.iter_mut().filter(|e| e.word_id == word_id) {
+        entry.marked = marked;
+    }
+}
+
+pub fn render(f: &mut Frame, app: &App) {
+    let log = &app.review_log;
+    let theme = &app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(f.size());
+
+    let header = Row::new(["Time", "Word", "Result", "Mode"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = log.entries.iter().map(|entry| {
+        let (result, style) = if entry.correct {
+            (theme.glyphs.correct, theme.good())
+        } else {
+            (theme.glyphs.wrong, theme.bad())
+        };
+        let mode = match entry.mode {
+            Screen::Test => "Test",
+            _ => "Practice",
+        };
+        Row::new(vec![
+            utils::clock_time(entry.reviewed_at),
+            format!("{}{}", if entry.marked { "* " } else { "" }, entry.word),
+            result.to_string(),
+            mode.to_string(),
+        ])
+        .style(style)
+    });
+
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Min(10),
+        Constraint::Length(8),
+        Constraint::Length(10),
+    ];
+
+    let title = if log.exhausted {
+        format!("Today ({} reviews)", log.entries.len())
+    } else {
+        format!("Today ({}+ reviews)", log.entries.len())
+    };
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(theme.block().title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    let mut state = TableState::default().with_selected(Some(log.selected));
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let help = Paragraph::new(theme.glyphs.join(&[
+        "j/k move",
+        "m mark",
+        "d delete",
+        "u undo",
+        &format!("{} definition", theme.glyphs.enter),
+        "q back",
+    ]))
+    .style(theme.muted())
+    .block(theme.block());
+
+    f.render_widget(help, chunks[1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_conn;
+    use crate::ui::event::AppEvent;
+    use crate::ui::keymap::KeyMap;
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_event(
+            app,
+            KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press),
+        );
+    }
+
+    fn log_app() -> App {
+        let conn = test_conn();
+        let now = Local::now().timestamp();
+        let yesterday = utils::start_of_day(Local::now()) - 60;
+        conn.execute_batch(&format!(
+            "INSERT INTO words(id,word,definition,group_id) VALUES(1,'abate','lessen',1), (2,'abound','plentiful',1);
+             INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES
+                 (1,{yesterday},1,0), (1,{now},0,1), (2,{now},1,0), (1,{now},1,0);"
+        ))
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        open(&mut app);
+        app
+    }
+
+    #[test]
+    fn test_lists_only_today_newest_first() {
+        let app = log_app();
+        assert_eq!(app.current_screen, Screen::ReviewLog);
+        let words: Vec<&str> = app
+            .review_log
+            .entries
+            .iter()
+            .map(|e| e.word.as_str())
+            .collect();
+        assert_eq!(words, vec!["abate", "abound", "abate"]);
+        assert!(app.review_log.exhausted);
+    }
+
+    #[test]
+    fn test_mark_from_list() {
+        let mut app = log_app();
+
+        press(&mut app, KeyCode::Char('m'));
+
+        let marked: i64 = app
+            .conn
+            .query_row("SELECT marked FROM words WHERE id=1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(marked, 1);
+        let entries = &app.review_log.entries;
+        assert!(entries[0].marked && entries[2].marked);
+        assert!(!entries[1].marked);
+    }
+
+    #[test]
+    fn test_enter_peeks_definition() {
+        let mut app = log_app();
+
+        press(&mut app, KeyCode::Enter);
+        let modal = app.modal.as_ref().unwrap();
+        assert_eq!(modal.title, "abate");
+        assert_eq!(modal.body, vec!["lessen"]);
+        assert_eq!(modal.buttons, Buttons::AnyKey);
+    }
+
+    fn rows(app: &App, sql: &str) -> Vec<String> {
+        let mut stmt = app.conn.prepare(sql).unwrap();
+        let columns = stmt.column_count();
+        stmt.query_map([], |row| {
+            Ok((0..columns)
+                .map(|i| format!("{:?}", row.get::<_, rusqlite::types::Value>(i).unwrap()))
+                .collect::<Vec<_>>()
+                .join(","))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_delete_after_confirming_and_undo() {
+        let mut app = log_app();
+        app.conn
+            .execute_batch(
+                "UPDATE words SET times_seen=5, success_count=3, marked=1, last_seen=1700000000
+                 WHERE id=1",
+            )
+            .unwrap();
+        let words = "SELECT * FROM words ORDER BY id";
+        let reviews = "SELECT * FROM reviews ORDER BY id";
+        let (words_before, reviews_before) = (rows(&app, words), rows(&app, reviews));
+
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.modal.as_ref().unwrap().buttons, Buttons::YesNo);
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Char('y'),
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        )));
+
+        assert_eq!(rows(&app, words).len(), 1);
+        assert_eq!(rows(&app, reviews).len(), 1);
+        let listed: Vec<&str> = app
+            .review_log
+            .entries
+            .iter()
+            .map(|e| e.word.as_str())
+            .collect();
+        assert_eq!(listed, vec!["abound"]);
+        assert_eq!(
+            app.toast.as_ref().unwrap().message,
+            "Deleted 'abate' — press u to undo"
+        );
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(rows(&app, words), words_before);
+        assert_eq!(rows(&app, reviews), reviews_before);
+        assert_eq!(app.review_log.entries.len(), 3);
+        assert!(app.review_log.undo.is_none());
+    }
+
+    #[test]
+    fn test_declining_keeps_the_word() {
+        let mut app = log_app();
+        press(&mut app, KeyCode::Char('d'));
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            KeyCode::Char('n'),
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        )));
+
+        assert_eq!(rows(&app, "SELECT id FROM words").len(), 2);
+        assert!(app.review_log.pending_delete.is_none());
+    }
+
+    #[test]
+    fn test_undo_window_expires() {
+        let mut app = log_app();
+        let now = Instant::now();
+        delete(&mut app, 1, now);
+        assert_eq!(app.toast.as_ref().unwrap().expires, now + UNDO_WINDOW);
+
+        undo(&mut app, now + UNDO_WINDOW);
+        assert_eq!(app.toast.as_ref().unwrap().message, "Too late to undo");
+        assert_eq!(rows(&app, "SELECT id FROM words"), vec!["Integer(2)"]);
+
+        // A second delete replaces the first, which can't be undone any more.
+        delete(&mut app, 2, now);
+        undo(&mut app, now);
+        assert_eq!(rows(&app, "SELECT id FROM words"), vec!["Integer(2)"]);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::error::VocabError;
+use crate::seed::{seed_bundled, seed_from_file};
+use crate::ui::app::App;
+use crate::ui::glyphs::Glyphs;
+use crate::ui::keymap::Action;
+use crate::ui::list_nav::{self, NavMove};
+use crate::ui::widgets::LineEdit;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplashOption {
+    Bundled,
+    Import,
+    Quit,
+}
+
+impl SplashOption {
+    pub fn label(&self, glyphs: &Glyphs) -> String {
+        match self {
+            SplashOption::Bundled => "Use the built-in starter list".into(),
+            SplashOption::Import => format!("Import a file{}", glyphs.ellipsis),
+            SplashOption::Quit => "Quit".into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SplashState {
+    pub options: Vec<SplashOption>,
+    pub selected: usize,
+    /// Path being typed while importing; `None` when the option list has focus.
+    pub path_input: Option<LineEdit>,
+    pub error: Option<String>,
+}
+
+impl Default for SplashState {
+    fn default() -> Self {
+        Self {
+            options: vec![
+                SplashOption::Bundled,
+                SplashOption::Import,
+                SplashOption::Quit,
+            ],
+            selected: 0,
+            path_input: None,
+            error: None,
+        }
+    }
+}
+
+pub fn handle_event(app: &mut App, key: KeyEvent) {
+    if let Some(path) = &mut app.splash.path_input {
+        match key.code {
+            KeyCode::Esc => app.splash.path_input = None,
+            KeyCode::Enter => import(app),
+            _ => {
+                path.handle_key(key);
+            }
+        }
+        return;
+    }
+
+    let len = app.splash.options.len();
+    match app.keymap.action_for(key) {
+        Some(Action::Down) => {
+            app.splash.selected = list_nav::apply(app.splash.selected, len, NavMove::Down(1));
+        }
+        Some(Action::Up) => {
+            app.splash.selected = list_nav::apply(app.splash.selected, len, NavMove::Up(1));
+        }
+        Some(Action::Quit) => app.should_quit = true,
+        Some(Action::Confirm) => match app.splash.options[app.splash.selected] {
+            SplashOption::Bundled => seed_starter(app),
+            SplashOption::Import => {
+                app.splash.error = None;
+                app.splash.path_input = Some(LineEdit::default());
+            }
+            SplashOption::Quit => app.should_quit = true,
+        },
+        _ => {}
+    }
+}
+
+fn seed_starter(app: &mut App) {
+    if app.refuse_write("seed words") {
+        return;
+    }
+    if let Err(e) = seed_bundled(&app.conn) {
+        app.splash.error = Some(format!("Seeding the starter list failed: {e}"));
+        return;
+    }
+    app.refresh_counts();
+    app.splash = SplashState::default();
+    app.return_to_menu();
+}
+
+fn import(app: &mut App) {
+    let path = match &app.splash.path_input {
+        Some(input) => input.text().trim().to_string(),
+        None => return,
+    };
+    if app.refuse_write("import words") {
+        return;
+    }
+
+    if let Err(e) = seed_from_file(&app.conn, &path) {
+        app.splash.error = Some(match e {
+            VocabError::Parse { line, msg } => {
+                format!("Import failed at line {line} of {path}: {msg}")
+            }
+            VocabError::Io(e) => format!("Import failed, couldn't read {path}: {e}"),
+            e => format!("Import of {path} failed: {e}"),
+        });
+        return;
+    }
+
+    app.refresh_counts();
+    if app.counts.words == 0 {
+        app.splash.error = Some(format!("{path} contained no words"));
+        return;
+    }
+
+    app.splash = SplashState::default();
+    app.return_to_menu();
+}
+
+pub fn render(f: &mut Frame, app: &App) {
+    let splash = &app.splash;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(6),
+            Constraint::Min(4),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    let intro = Paragraph::new(format!(
+        "Your word database is empty.\n\nDatabase: {}\nUse the starter list or import a seed file to get started.",
+        app.db_path
+    ))
+    .wrap(Wrap { trim: false })
+    .block(app.theme.block().title("Welcome"));
+
+    f.render_widget(intro, chunks[0]);
+
+    let items: Vec<ListItem> = splash
+        .options
+        .iter()
+        .map(|option| ListItem::new(option.label(app.theme.glyphs)))
+        .collect();
+
+    let mut state = ListState::default();
+    state.select(Some(splash.selected));
+
+    let list = List::new(items)
+        .block(app.theme.block().title("Get Started"))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    if let Some(path) = &splash.path_input {
+        let block = app
+            .theme
+            .block()
+            .title("Seed file path (Enter to import, Esc to cancel)");
+        let width = block.inner(chunks[2]).width;
+        let input = Paragraph::new(path.line(width, true))
+            .style(app.theme.accent())
+            .block(block);
+
+        f.render_widget(input, chunks[2]);
+    }
+
+    if let Some(err) = &splash.error {
+        let error = Paragraph::new(err.clone())
+            .style(app.theme.bad())
+            .block(app.theme.block().title("Error"));
+
+        f.render_widget(error, chunks[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::app::Screen;
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn splash_app() -> App {
+        let mut app = App::new_test();
+        app.current_screen = Screen::Splash;
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_event(
+            app,
+            KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press),
+        );
+    }
+
+    fn type_str(app: &mut App, s: &str) {
+        for c in s.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_import_from_path() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "Group 1\nabound be present in large quantities\n").unwrap();
+
+        let mut app = splash_app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.splash.path_input.is_some());
+
+        type_str(&mut app, file.path().to_str().unwrap());
+        press(&mut app, KeyCode::Enter);
+
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert_eq!(app.counts.words, 1);
+        assert!(app.splash.path_input.is_none());
+    }
+
+    #[test]
+    fn test_import_failure_stays_on_splash() {
+        let mut app = splash_app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        type_str(&mut app, "/nonexistent/vocab.txt");
+        press(&mut app, KeyCode::Enter);
+
+        assert_eq!(app.current_screen, Screen::Splash);
+        assert!(app.splash.error.as_deref().unwrap().contains("failed"));
+        assert!(app.splash.path_input.is_some());
+    }
+
+    #[test]
+    fn test_import_reports_bad_line() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "Group 1\nabound plentiful\nGroup one\n").unwrap();
+
+        let mut app = splash_app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        type_str(&mut app, file.path().to_str().unwrap());
+        press(&mut app, KeyCode::Enter);
+
+        let error = app.splash.error.as_deref().unwrap();
+        assert!(error.starts_with("Import failed at line 3 of"), "{error}");
+        assert!(error.ends_with("Invalid group line: Group one"));
+    }
+
+    #[test]
+    fn test_quit_option() {
+        let mut app = splash_app();
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_starter_list_option() {
+        let mut app = splash_app();
+        press(&mut app, KeyCode::Enter);
+
+        assert_eq!(app.current_screen, Screen::Menu);
+        assert!(app.counts.words > 0);
+    }
+}
@@ -0,0 +1,761 @@
+use crate::core::session;
+use crate::core::stats::{
+    self, GroupColumn, TREND_WEEKS, WEEK_SECS, WeekAccuracy, session_line, sort_groups,
+};
+use crate::core::utils;
+use crate::db::models::{GroupStats, SessionRecord};
+use crate::db::queries;
+use crate::db::store::{WordFilter, WordStore};
+use crate::duplicates;
+use crate::export;
+use crate::groups;
+use crate::ui::app::{App, Screen};
+use crate::ui::keymap::Action;
+use crate::ui::screens::review_log;
+use crate::ui::theme::Theme;
+use crate::ui::widgets::LineEdit;
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Axis, Cell, Chart, Dataset, GraphType, List, ListItem, Paragraph, Row, Table, TableState,
+        Wrap,
+    },
+};
+
+/// Rows the accuracy trend needs, borders included.
+const TREND_HEIGHT: u16 = 10;
+
+/// Sessions listed under the group table.
+pub const RECENT_SESSIONS: usize = 10;
+
+#[derive(Debug, Default)]
+pub struct StatsState {
+    pub groups: Vec<GroupStats>,
+    /// Newest first.
+    pub sessions: Vec<SessionRecord>,
+    pub column: GroupColumn,
+    pub descending: bool,
+    pub selected: usize,
+    /// New name for the selected group while `r` is editing it.
+    pub renaming: Option<LineEdit>,
+    /// Words sharing their definition with a different word.
+    pub duplicates: usize,
+    /// List archived groups too, toggled by `A`.
+    pub show_archived: bool,
+    /// Words retired from rotation as mastered.
+    pub mastered: i64,
+    /// Accuracy over the last `TREND_WEEKS` weeks, oldest first.
+    pub trend: Vec<WeekAccuracy>,
+}
+
+impl StatsState {
+    /// Sorts by `column`, flipping the direction when it is already active.
+    pub fn sort_by(&mut self, column: GroupColumn) {
+        if self.column == column {
+            self.descending = !self.descending;
+        } else {
+            self.column = column;
+            self.descending = false;
+        }
+        sort_groups(&mut self.groups, self.column, !self.descending);
+        self.selected = 0;
+    }
+}
+
+pub fn open(app: &mut App) {
+    let now = app.clock.now();
+    let loaded = (|| -> anyhow::Result<_> {
+        Ok((
+            queries::fetch_group_stats(&app.conn, app.stats.show_archived)?,
+            queries::fetch_recent_sessions(&app.conn, RECENT_SESSIONS)?,
+            duplicates::count(&app.conn)?,
+            WordStore::new(&app.conn).count(WordFilter::Mastered)?,
+            queries::fetch_outcomes_since(&app.conn, now - TREND_WEEKS as i64 * WEEK_SECS)?,
+        ))
+    })();
+    match loaded {
+        Ok((mut groups, sessions, duplicates, mastered, reviews)) => {
+            let stats = &mut app.stats;
+            sort_groups(&mut groups, stats.column, !stats.descending);
+            stats.groups = groups;
+            stats.sessions = sessions;
+            stats.duplicates = duplicates;
+            stats.mastered = mastered;
+            stats.trend = stats::weekly_accuracy(&reviews, now, TREND_WEEKS);
+            stats.selected = 0;
+            app.current_screen = Screen::Stats;
+        }
+        Err(e) => app.error = Some(e.to_string()),
+    }
+}
+
+/// Reloads the table, keeping the same group selected.
+pub fn reload(app: &mut App) {
+    let selected = app.stats.groups.get(app.stats.selected).map(|g| g.group_id);
+    open(app);
+    if let Some(index) = app
+        .stats
+        .groups
+        .iter()
+        .position(|g| Some(g.group_id) == selected)
+    {
+        app.stats.selected = index;
+    }
+}
+
+pub fn handle_event(app: &mut App, key: KeyEvent) {
+    if app.stats.renaming.is_some() {
+        handle_rename_key(app, key);
+        return;
+    }
+    if let KeyCode::Char(c @ '1'..='5') = key.code {
+        let index = c.to_digit(10).unwrap() as usize - 1;
+        app.stats.sort_by(GroupColumn::ALL[index]);
+        return;
+    }
+    if key.code == KeyCode::Char('T') {
+        start_test(app);
+        return;
+    }
+    if key.code == KeyCode::Char('t') {
+        review_log::open(app);
+        return;
+    }
+    if key.code == KeyCode::Char('x') {
+        export(app);
+        return;
+    }
+    if key.code == KeyCode::Char('r') {
+        start_rename(app);
+        return;
+    }
+    if key.code == KeyCode::Char('a') {
+        toggle_archived(app);
+        return;
+    }
+    if key.code == KeyCode::Char('A') {
+        app.stats.show_archived = !app.stats.show_archived;
+        reload(app);
+        return;
+    }
+
+    let stats = &mut app.stats;
+    match app.keymap.action_for(key) {
+        Some(Action::Quit) => app.return_to_menu(),
+        Some(Action::Down) if stats.selected + 1 < stats.groups.len() => stats.selected += 1,
+        Some(Action::Up) => stats.selected = stats.selected.saturating_sub(1),
+        Some(Action::Confirm) => {
+            if let Some(group) = stats.groups.get(stats.selected) {
+                let started = session::group_session_at(
+                    &app.conn,
+                    app.clock.as_ref(),
+                    group.group_id,
+                    app.mix_marked,
+                    !app.read_only,
+                );
+                app.begin_session(started);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A typed test over the selected group, in shuffled order.
+fn start_test(app: &mut App) {
+    let Some(group) = app.stats.groups.get(app.stats.selected) else {
+        return;
+    };
+    let started = session::group_test_session(&app.conn, group.group_id, app.clock.now() as u64);
+    app.begin_session(started);
+}
+
+fn start_rename(app: &mut App) {
+    if app.refuse_write("rename groups") {
+        return;
+    }
+    let Some(group) = app.stats.groups.get(app.stats.selected) else {
+        return;
+    };
+    let mut input = LineEdit::default();
+    input.paste(group.name.as_deref().unwrap_or_default());
+    app.stats.renaming = Some(input);
+}
+
+fn toggle_archived(app: &mut App) {
+    if app.refuse_write("archive groups") {
+        return;
+    }
+    let Some(group) = app.stats.groups.get(app.stats.selected) else {
+        return;
+    };
+    match groups::set_archived(&app.conn, group.group_id, !group.archived) {
+        Ok(report) => {
+            app.notify(report.join("; "), false);
+            reload(app);
+        }
+        Err(e) => app.notify(e.to_string(), true),
+    }
+}
+
+/// While renaming, every key edits the name except Enter (save it) and Esc
+/// (leave it as it was).
+fn handle_rename_key(app: &mut App, key: KeyEvent) {
+    let Some(input) = &mut app.stats.renaming else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.stats.renaming = None,
+        KeyCode::Enter => {
+            let Some(group) = app.stats.groups.get(app.stats.selected) else {
+                return;
+            };
+            match groups::rename(&app.conn, group.group_id, input.text()) {
+                Ok(report) => {
+                    app.stats.renaming = None;
+                    app.notify(report.join("; "), false);
+                    reload(app);
+                }
+                Err(e) => app.notify(e.to_string(), true),
+            }
+        }
+        _ => {
+            input.handle_key(key);
+        }
+    }
+}
+
+fn export(app: &mut App) {
+    match export::export_stats(&app.conn, &app.export_dir, Local::now().date_naive()) {
+        Ok(path) => {
+            let path = std::path::absolute(&path).unwrap_or(path);
+            app.notify(format!("Exported to {}", path.display()), false);
+        }
+        Err(e) => app.notify(format!("Export failed: {e}"), true),
+    }
+}
+
+pub fn render(f: &mut Frame, app: &App) {
+    let stats = &app.stats;
+    let theme = &app.theme;
+    let glyphs = theme.glyphs;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length((stats.sessions.len().max(1) as u16 + 2).max(TREND_HEIGHT)),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+
+    let header = Row::new(GroupColumn::ALL.iter().enumerate().map(|(i, column)| {
+        let arrow = match (*column == stats.column, stats.descending) {
+            (true, false) => glyphs.sort_up,
+            (true, true) => glyphs.sort_down,
+            _ => "",
+        };
+        format!("{} {} {}", i + 1, column.label(), arrow)
+            .trim_end()
+            .to_string()
+    }))
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let name_width = chunks[0].width.saturating_sub(4) / 5;
+    let rows = stats.groups.iter().enumerate().map(|(i, group)| {
+        let name = match &stats.renaming {
+            Some(input) if i == stats.selected => Cell::from(input.line(name_width, true)),
+            _ => {
+                let name = group
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("Group {}", group.group_id));
+                if group.archived {
+                    Cell::from(format!("{name} (archived)"))
+                } else {
+                    Cell::from(name)
+                }
+            }
+        };
+        let style = if group.archived {
+            theme.muted()
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            name,
+            Cell::from(group.words.to_string()),
+            Cell::from(format!("{:.0}%", group.seen_ratio() * 100.0)),
+            Cell::from(
+                group
+                    .accuracy()
+                    .map(|a| format!("{:.0}%", a * 100.0))
+                    .unwrap_or_else(|| "-".into()),
+            ),
+            Cell::from(utils::relative_time(group.last_seen, app.clock.now())),
+        ])
+        .style(style)
+    });
+
+    let widths = [
+        Constraint::Percentage(20),
+        Constraint::Percentage(15),
+        Constraint::Percentage(15),
+        Constraint::Percentage(20),
+        Constraint::Percentage(30),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(theme.block().title(groups_title(stats, theme)))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    let mut state = TableState::default().with_selected(Some(stats.selected));
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let now = Local::now();
+    let sessions: Vec<ListItem> = if stats.sessions.is_empty() {
+        vec![ListItem::new("No finished sessions yet")]
+    } else {
+        stats
+            .sessions
+            .iter()
+            .map(|record| ListItem::new(session_line(record, &now, glyphs)))
+            .collect()
+    };
+    f.render_widget(
+        List::new(sessions).block(theme.block().title("Recent sessions")),
+        bottom[0],
+    );
+    render_trend(f, bottom[1], &stats.trend, theme);
+
+    let enter = glyphs.enter;
+    let help = Paragraph::new(if stats.renaming.is_some() {
+        glyphs.join(&["Renaming group", &format!("{enter} save"), "esc cancel"])
+    } else {
+        glyphs.join(&[
+            "1-5 sort",
+            "j/k move",
+            &format!("{enter} practice group"),
+            "r rename",
+            "a archive",
+            if stats.show_archived {
+                "A hide archived"
+            } else {
+                "A show archived"
+            },
+            "T test group",
+            "t today's reviews",
+            "x export CSV",
+            "q back",
+        ])
+    })
+    .style(theme.muted())
+    .block(theme.block());
+
+    f.render_widget(help, chunks[2]);
+}
+
+/// The weeks with reviews as chart points, one run per stretch without a
+/// gap, so an empty week breaks the line instead of dropping it to 0%.
+fn trend_runs(weeks: &[WeekAccuracy]) -> Vec<Vec<(f64, f64)>> {
+    let mut runs = vec![Vec::new()];
+    for (i, week) in weeks.iter().enumerate() {
+        match week.accuracy() {
+            Some(accuracy) => runs.last_mut().unwrap().push((i as f64, accuracy * 100.0)),
+            None if runs.last().is_some_and(|run| !run.is_empty()) => runs.push(Vec::new()),
+            None => {}
+        }
+    }
+    runs.retain(|run| !run.is_empty());
+    runs
+}
+
+fn render_trend(f: &mut Frame, area: Rect, weeks: &[WeekAccuracy], theme: &Theme) {
+    let block = theme
+        .block()
+        .title(format!("Accuracy, last {TREND_WEEKS} weeks"));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+    f.render_widget(
+        Paragraph::new(stats::trend_legend(weeks, theme.glyphs)).style(theme.muted()),
+        rows[1],
+    );
+
+    let Some(marker) = theme.glyphs.marker else {
+        let figures: Vec<String> = weeks
+            .iter()
+            .map(|week| match week.accuracy() {
+                Some(accuracy) => format!("{:.0}%", accuracy * 100.0),
+                None => "-".into(),
+            })
+            .collect();
+        f.render_widget(
+            Paragraph::new(figures.join(" ")).wrap(Wrap { trim: true }),
+            rows[0],
+        );
+        return;
+    };
+    let runs = trend_runs(weeks);
+    let datasets = runs
+        .iter()
+        .map(|points| {
+            Dataset::default()
+                .marker(marker)
+                .graph_type(if points.len() == 1 {
+                    GraphType::Scatter
+                } else {
+                    GraphType::Line
+                })
+                .style(theme.good())
+                .data(points)
+        })
+        .collect();
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, weeks.len().saturating_sub(1) as f64])
+                .labels(vec![
+                    Span::raw(format!("{}w ago", weeks.len().saturating_sub(1))),
+                    Span::raw("this week"),
+                ])
+                .style(theme.muted()),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, 100.0])
+                .labels(vec![Span::raw("0%"), Span::raw("50%"), Span::raw("100%")])
+                .style(theme.muted()),
+        );
+    f.render_widget(chart, rows[0]);
+}
+
+fn groups_title(stats: &StatsState, theme: &Theme) -> Line<'static> {
+    let mut spans = vec![Span::raw("Groups")];
+    if stats.mastered > 0 {
+        spans.push(Span::raw(format!(
+            "{}{} mastered",
+            theme.glyphs.separator, stats.mastered
+        )));
+    }
+    if stats.duplicates > 0 {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(
+                "{} {} words share a definition (doctor --duplicates)",
+                theme.glyphs.warning, stats.duplicates
+            ),
+            theme.accent(),
+        ));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::MockClock;
+    use crate::db::test_conn;
+    use crate::ui::keymap::KeyMap;
+    use crate::ui::testing::render_to_string;
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_event(
+            app,
+            KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press),
+        );
+    }
+
+    fn seeded_app() -> App {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(word,definition,group_id,times_seen,success_count)
+             VALUES('a','b',1,2,2), ('c','d',2,4,1), ('e','f',2,0,0)",
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        open(&mut app);
+        app
+    }
+
+    #[test]
+    fn test_trend_breaks_at_empty_weeks() {
+        let week = |reviews, correct| WeekAccuracy { reviews, correct };
+        let weeks = [
+            week(0, 0),
+            week(4, 2),
+            week(2, 2),
+            week(0, 0),
+            week(0, 0),
+            week(4, 1),
+            week(0, 0),
+        ];
+        assert_eq!(
+            trend_runs(&weeks),
+            vec![vec![(1.0, 50.0), (2.0, 100.0)], vec![(5.0, 25.0)]]
+        );
+        assert!(trend_runs(&[week(0, 0); 3]).is_empty());
+    }
+
+    #[test]
+    fn test_trend_drawn_from_review_history() {
+        let clock = MockClock::at(100 * WEEK_SECS);
+        let mut app = seeded_app();
+        app.clock = Box::new(clock.clone());
+        for (back, correct) in [(0, true), (0, true), (0, false), (4, false), (20, true)] {
+            app.conn
+                .execute(
+                    "INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES(1,?1,?2,0)",
+                    rusqlite::params![100 * WEEK_SECS - back * WEEK_SECS, correct],
+                )
+                .unwrap();
+        }
+        open(&mut app);
+        assert_eq!(app.stats.trend.len(), TREND_WEEKS);
+        assert_eq!(
+            app.stats.trend[TREND_WEEKS - 1],
+            WeekAccuracy {
+                reviews: 3,
+                correct: 2
+            }
+        );
+
+        let screen = render_to_string(&app, 120, 40);
+        assert!(screen.contains("Accuracy, last 12 weeks"), "{screen}");
+        assert!(
+            screen.contains("This week 67% (2 of 3) · +67 pts vs 4 weeks ago"),
+            "{screen}"
+        );
+        assert!(screen.contains("100%"), "{screen}");
+
+        app.theme = Theme::new(true, true);
+        let screen = render_to_string(&app, 120, 40);
+        assert!(screen.contains("- 0% - - - 67%"), "{screen}");
+    }
+
+    #[test]
+    fn test_sort_keys_toggle_direction() {
+        let mut app = seeded_app();
+        assert_eq!(app.current_screen, Screen::Stats);
+
+        press(&mut app, KeyCode::Char('2'));
+        assert_eq!(app.stats.groups[0].group_id, 1);
+
+        press(&mut app, KeyCode::Char('2'));
+        assert!(app.stats.descending);
+        assert_eq!(app.stats.groups[0].group_id, 2);
+    }
+
+    #[test]
+    fn test_enter_starts_group_session() {
+        let mut app = seeded_app();
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+
+        assert_eq!(app.current_screen, Screen::Practice);
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.current().unwrap().group_id, 2);
+        assert_eq!(session.index, 0);
+    }
+
+    #[test]
+    fn test_export_key_reports_path_or_error() {
+        let mut app = seeded_app();
+        let dir = tempfile::TempDir::new().unwrap();
+        app.export_dir = dir.path().to_path_buf();
+
+        press(&mut app, KeyCode::Char('x'));
+        let toast = app.toast.as_ref().unwrap();
+        assert!(!toast.is_error, "{}", toast.message);
+        let written = dir
+            .path()
+            .join(export::file_name(Local::now().date_naive()));
+        assert!(written.exists());
+        assert!(toast.message.contains(&written.display().to_string()));
+
+        app.export_dir = dir.path().join("missing");
+        press(&mut app, KeyCode::Char('x'));
+        let toast = app.toast.as_ref().unwrap();
+        assert!(toast.is_error);
+        assert!(toast.message.starts_with("Export failed"));
+    }
+
+    #[test]
+    fn test_rename_selected_group_inline() {
+        let mut app = seeded_app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.stats.renaming.as_ref().unwrap().text(), "");
+
+        // Bindings are off while typing: q and 1 are part of the name.
+        for c in "q1 words".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.stats.renaming.is_none());
+        assert_eq!(app.current_screen, Screen::Stats);
+        let group = &app.stats.groups[app.stats.selected];
+        assert_eq!(
+            (group.group_id, group.name.as_deref()),
+            (2, Some("q1 words"))
+        );
+        assert!(!app.toast.as_ref().unwrap().is_error);
+
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.stats.renaming.as_ref().unwrap().text(), "q1 words");
+        press(&mut app, KeyCode::Esc);
+        assert!(app.stats.renaming.is_none());
+        assert_eq!(
+            app.stats.groups[app.stats.selected].name.as_deref(),
+            Some("q1 words")
+        );
+    }
+
+    #[test]
+    fn test_typed_test_over_a_group() {
+        use crate::core::session::Type;
+        use crate::ui::event::AppEvent;
+
+        let mut app = seeded_app();
+        let send = |app: &mut App, code: KeyCode| {
+            app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+                code,
+                KeyModifiers::NONE,
+                KeyEventKind::Press,
+            )))
+        };
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('T'));
+        assert_eq!(app.current_screen, Screen::Test);
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.session_type, Type::GroupTest);
+        let words: Vec<String> = session.words.iter().map(|w| w.word.clone()).collect();
+        let mut sorted = words.clone();
+        sorted.sort();
+        assert_eq!(sorted, ["c", "e"]);
+
+        // The first word right, the second misspelled.
+        for typed in [words[0].as_str(), "x"] {
+            send(&mut app, KeyCode::Char('i'));
+            for c in typed.chars() {
+                send(&mut app, KeyCode::Char(c));
+            }
+            send(&mut app, KeyCode::Enter);
+            send(&mut app, KeyCode::Enter);
+        }
+
+        assert_eq!(app.current_screen, Screen::Menu);
+        let modal = app.modal.as_ref().unwrap();
+        assert_eq!(modal.title, "Test finished");
+        assert_eq!(modal.body[0], "1 of 2 right.");
+        assert_eq!(modal.body[3..], [format!("{}: you typed \"x\"", words[1])]);
+
+        let reviews: Vec<(String, i64, bool, Option<String>)> = app
+            .conn
+            .prepare(
+                "SELECT w.word, r.mode, r.correct, r.answer
+                 FROM reviews r JOIN words w ON w.id = r.word_id ORDER BY r.id",
+            )
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            reviews,
+            vec![
+                (words[0].clone(), 1, true, Some(words[0].clone())),
+                (words[1].clone(), 1, false, Some("x".into())),
+            ]
+        );
+        let record = &queries::fetch_recent_sessions(&app.conn, 1).unwrap()[0];
+        assert_eq!(
+            (record.session_type, record.words_reviewed, record.correct),
+            (Type::GroupTest, 2, 1)
+        );
+        // A side test doesn't move saved progress on.
+        let saved: i64 = app
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM app_state WHERE key='group_id'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(saved, 0);
+    }
+
+    #[test]
+    fn test_archive_hides_group_until_shown() {
+        let mut app = seeded_app();
+        press(&mut app, KeyCode::Char('a'));
+        assert!(!app.toast.as_ref().unwrap().is_error);
+        let ids = |app: &App| {
+            app.stats
+                .groups
+                .iter()
+                .map(|g| g.group_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&app), [2]);
+
+        press(&mut app, KeyCode::Char('A'));
+        assert_eq!(ids(&app), [1, 2]);
+        assert!(app.stats.groups[0].archived);
+
+        // Restoring it keeps it selected.
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.stats.selected, 0);
+        assert!(!app.stats.groups[0].archived);
+    }
+
+    #[test]
+    fn test_counts_shared_definitions() {
+        let mut app = seeded_app();
+        assert_eq!(app.stats.duplicates, 0);
+        assert_eq!(groups_title(&app.stats, &app.theme).to_string(), "Groups");
+
+        app.conn
+            .execute(
+                "INSERT INTO words(word,definition,group_id) VALUES('g',' B ',3)",
+                [],
+            )
+            .unwrap();
+        open(&mut app);
+        assert_eq!(app.stats.duplicates, 2);
+        assert!(
+            groups_title(&app.stats, &app.theme)
+                .to_string()
+                .contains("2 words share")
+        );
+
+        app.conn
+            .execute("UPDATE words SET mastered_at=1 WHERE word='g'", [])
+            .unwrap();
+        open(&mut app);
+        assert_eq!(app.stats.mastered, 1);
+        assert!(
+            groups_title(&app.stats, &app.theme)
+                .to_string()
+                .starts_with("Groups · 1 mastered ")
+        );
+    }
+}
@@ -1,11 +1,17 @@
+use crate::core::grading::{self, DiffKind, DiffSegment};
+use crate::core::stats::outcome_glyphs;
 use crate::core::{actions, utils};
-use crate::ui::app::{App, Screen};
+use crate::ui::app::App;
+use crate::ui::keymap::Action;
+use crate::ui::screens::practice::{render_reading, word_line};
+use crate::ui::text;
+use crate::ui::theme::Theme;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     prelude::*,
     text::{Line, Span},
-    widgets::{Block, Borders, Padding, Paragraph},
+    widgets::{Padding, Paragraph},
 };
 
 pub fn handle_event(app: &mut App, key: KeyEvent) {
@@ -14,64 +20,87 @@ pub fn handle_event(app: &mut App, key: KeyEvent) {
         None => return,
     };
 
-    match key.code {
-        KeyCode::Char('q') | KeyCode::Esc if !session.insert_mode => {
+    if session.insert_mode {
+        match key.code {
+            KeyCode::Esc => session.insert_mode = false,
+            KeyCode::Enter => submit(app),
+            _ => {
+                session.input_buffer.handle_key(key);
+            }
+        }
+        return;
+    }
+
+    match app.keymap.action_for(key) {
+        Some(Action::Quit) => {
             app.session = None;
-            app.current_screen = Screen::Menu;
+            app.return_to_menu();
         }
-        KeyCode::Char('i') if !session.insert_mode => {
+        Some(Action::Insert) => {
             session.insert_mode = true;
         }
-        KeyCode::Esc if session.insert_mode => {
-            session.insert_mode = false;
-        }
-        KeyCode::Char(c) if session.insert_mode => {
-            session.input_buffer.push(c);
-        }
-        KeyCode::Backspace if session.insert_mode => {
-            session.input_buffer.pop();
-        }
-        KeyCode::Char('m') => {
-            let word = session.current_mut();
-            word.marked = !word.marked;
-        }
-        KeyCode::Enter => {
-            if session.graded.is_none() {
-                let word = session.current();
-                let correct = session.input_buffer.trim().eq_ignore_ascii_case(&word.word);
-                session.graded = Some(correct);
-                session.show_definition = true;
-                session.insert_mode = false;
-            } else {
-                if let Err(e) = actions::handle_enter(app) {
-                    app.error = Some(e.to_string());
-                    app.current_screen = Screen::Menu;
-                }
-            }
-        }
+        Some(Action::Mark) => app.toggle_mark(),
+        Some(Action::CopyWord) => app.copy_current(false),
+        Some(Action::CopyEntry) => app.copy_current(true),
+        Some(Action::Confirm) => submit(app),
+        Some(Action::Pause) => session.pause(app.clock.now()),
         _ => {}
     }
 }
 
+fn submit(app: &mut App) {
+    let session = match &mut app.session {
+        Some(s) => s,
+        None => return,
+    };
+
+    if session.graded.is_none() {
+        let Some(word) = session.current() else {
+            return;
+        };
+        let correct = grading::is_correct(
+            session.input_buffer.text(),
+            &word.word,
+            session.reading.as_deref(),
+        );
+        if !correct {
+            session.answer_diff = Some(grading::diff_answer(
+                &word.word,
+                session.input_buffer.text(),
+            ));
+        }
+        session.graded = Some(correct);
+        session.show_definition = true;
+        session.insert_mode = false;
+    } else if let Err(e) = actions::handle_enter(app) {
+        tracing::error!("advancing session failed: {e}");
+        app.return_to_menu();
+        app.error = Some(e.to_string());
+    }
+}
+
 pub fn render(frame: &mut Frame, app: &App) {
     let session = match &app.session {
         Some(s) => s,
         None => return,
     };
 
-    let word = session.current();
+    let Some(word) = session.current() else {
+        return;
+    };
+    let theme = &app.theme;
     let area = frame.size();
 
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
-            Constraint::Length(3), // Header
-            Constraint::Length(5), // Word reveal
-            Constraint::Length(5), // Definition
-            Constraint::Length(3), // Input
-            Constraint::Length(4), // Stats
-            Constraint::Length(5), // Actions
+            Constraint::Length(3),                                                 // Header
+            Constraint::Length(5),                                                 // Word reveal
+            Constraint::Length(if session.answer_diff.is_some() { 7 } else { 5 }), // Definition
+            Constraint::Length(3),                                                 // Input
+            Constraint::Length(5),                                                 // Stats
+            Constraint::Length(5),                                                 // Actions
         ])
         .split(area);
 
@@ -81,46 +110,53 @@ pub fn render(frame: &mut Frame, app: &App) {
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(layout[0]);
 
-    let left_header = Paragraph::new(format!(
-        "{} WORD [{}/{}]",
-        if word.marked { "*" } else { " " },
-        session.index + 1,
-        session.words.len()
+    // Header blocks have borders and one column of padding on each side.
+    let header_width = |area: Rect| area.width.saturating_sub(4) as usize;
+
+    let left_header = Paragraph::new(text::truncate_to_width(
+        &format!(
+            "{} WORD [{}/{}]{}",
+            if word.marked { "*" } else { " " },
+            session.index + 1,
+            session.words.len(),
+            // Marked word from another group, mixed into this one.
+            if session.is_mixed_in(session.index) {
+                format!("{}marked", theme.glyphs.separator)
+            } else {
+                String::new()
+            }
+        ),
+        header_width(header_chunks[0]),
+        theme.glyphs.ellipsis,
     ))
     .alignment(Alignment::Center)
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .padding(Padding::horizontal(1)),
-    );
+    .block(theme.block().padding(Padding::horizontal(1)));
 
-    let right_header = Paragraph::new(format!("Group {} | Id {}", word.group_id, word.id))
-        .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .padding(Padding::horizontal(1)),
-        );
+    let right_header = Paragraph::new(text::truncate_to_width(
+        &format!("Group {} | Id {}", word.group_id, word.id),
+        header_width(header_chunks[1]),
+        theme.glyphs.ellipsis,
+    ))
+    .alignment(Alignment::Center)
+    .block(theme.block().padding(Padding::horizontal(1)));
 
     frame.render_widget(left_header, header_chunks[0]);
     frame.render_widget(right_header, header_chunks[1]);
 
     // ───────── WORD ─────────
     let word_text = if session.graded.is_some() {
-        word.word.clone()
+        word.word.as_str()
     } else {
-        "(hidden)".into()
+        "(hidden)"
     };
 
     let style = match session.graded {
-        Some(true) => Style::default().fg(Color::Green),
-        Some(false) => Style::default().fg(Color::Red),
+        Some(true) => theme.good(),
+        Some(false) => theme.bad(),
         None => Style::default(),
     };
 
-    let word_block = Block::default()
-        .borders(Borders::ALL)
-        .padding(Padding::horizontal(1));
+    let word_block = theme.block().padding(Padding::horizontal(1));
 
     let inner = word_block.inner(layout[1]);
     frame.render_widget(word_block, layout[1]);
@@ -134,20 +170,34 @@ pub fn render(frame: &mut Frame, app: &App) {
         ])
         .split(inner);
 
-    let word_para = Paragraph::new(word_text)
-        .style(style)
-        .alignment(Alignment::Center)
-        .bold();
+    // Only once answered: the transcription gives the word away.
+    let ipa = session.ipa.as_deref().filter(|_| session.graded.is_some());
+    let word_para =
+        Paragraph::new(word_line(word_text, ipa, style, theme)).alignment(Alignment::Center);
 
     frame.render_widget(word_para, vertical[1]);
+    // The reading is an accepted answer, so it waits as well.
+    let reading = session
+        .reading
+        .as_deref()
+        .filter(|_| session.graded.is_some());
+    render_reading(frame, reading, vertical[2], theme);
 
     // ───────── DEFINITION ─────────
-    let definition = Paragraph::new(word.definition.clone())
+    let mut def_lines = Vec::new();
+    if let Some(diff) = &session.answer_diff {
+        def_lines.push(diff_line("expected: ", &diff.expected, theme));
+        def_lines.push(diff_line("yours:    ", &diff.answer, theme));
+        def_lines.push(Line::raw(""));
+    }
+    def_lines.extend(word.definition.lines().map(Line::raw));
+
+    let definition = Paragraph::new(def_lines)
         .alignment(Alignment::Center)
         .block(
-            Block::default()
+            theme
+                .block()
                 .title("Definition")
-                .borders(Borders::ALL)
                 .padding(Padding::horizontal(1)),
         );
 
@@ -155,42 +205,49 @@ pub fn render(frame: &mut Frame, app: &App) {
 
     // ───────── INPUT ─────────
     let input_style = if session.insert_mode {
-        Style::default().fg(Color::Yellow)
+        theme.accent()
     } else {
         Style::default()
     };
 
-    let input = Paragraph::new(format!("> {}", session.input_buffer))
+    let input_block = theme.block().title("Input").padding(Padding::horizontal(1));
+
+    let prompt = "> ";
+    let edit_width = input_block
+        .inner(layout[3])
+        .width
+        .saturating_sub(prompt.len() as u16);
+    let mut input_line = session.input_buffer.line(edit_width, session.insert_mode);
+    input_line.spans.insert(0, Span::raw(prompt));
+
+    let input = Paragraph::new(input_line)
         .style(input_style)
-        .block(
-            Block::default()
-                .title("Input")
-                .borders(Borders::ALL)
-                .padding(Padding::horizontal(1)),
-        );
+        .block(input_block);
 
     frame.render_widget(input, layout[3]);
 
     // ───────── STATS ─────────
+    let recent = session
+        .history
+        .as_deref()
+        .map(|outcomes| outcome_glyphs(outcomes, theme.glyphs))
+        .unwrap_or_else(|| "-".into());
+
     let stats = Paragraph::new(format!(
-        "Last Seen: {}\nAccuracy: {}/{}",
-        utils::relative_time(word.last_seen),
+        "Last Seen: {}\nAccuracy: {}/{}\nRecent: {}",
+        utils::relative_time(word.last_seen, app.clock.now()),
         word.success_count,
-        word.times_seen
+        word.times_seen,
+        recent
     ))
-    .block(
-        Block::default()
-            .title("Stats")
-            .borders(Borders::ALL)
-            .padding(Padding::horizontal(1)),
-    );
+    .block(theme.block().title("Stats").padding(Padding::horizontal(1)));
 
     frame.render_widget(stats, layout[4]);
 
     // ───────── ACTION BUTTONS ─────────
-    let actions_block = Block::default()
+    let actions_block = theme
+        .block()
         .title("Actions")
-        .borders(Borders::ALL)
         .padding(Padding::horizontal(1));
 
     let inner_actions = actions_block.inner(layout[5]);
@@ -206,22 +263,180 @@ pub fn render(frame: &mut Frame, app: &App) {
         ])
         .split(inner_actions);
 
-    render_button(frame, buttons[0], "Insert", "i");
-    render_button(frame, buttons[1], "Mark", "m");
-    render_button(frame, buttons[2], "Submit", "⏎");
-    render_button(frame, buttons[3], "Quit", "q");
+    let key = |action| app.keymap.label(action, theme.glyphs);
+    render_button(frame, buttons[0], "Insert", &key(Action::Insert), theme);
+    render_button(frame, buttons[1], "Mark", &key(Action::Mark), theme);
+    render_button(frame, buttons[2], "Submit", &key(Action::Confirm), theme);
+    render_button(frame, buttons[3], "Quit", &key(Action::Quit), theme);
+}
+
+fn diff_line<'a>(label: &'a str, segments: &'a [DiffSegment], theme: &Theme) -> Line<'a> {
+    let mut spans = vec![Span::styled(label, theme.muted())];
+    spans.extend(segments.iter().map(|seg| {
+        let style = match seg.kind {
+            DiffKind::Match => Style::default().add_modifier(Modifier::DIM),
+            DiffKind::Missing => theme.good().bold().underlined(),
+            DiffKind::Extra => theme.bad().bold().crossed_out(),
+        };
+        Span::styled(seg.text.as_str(), style)
+    }));
+    Line::from(spans)
 }
 
-fn render_button(frame: &mut Frame, area: Rect, label: &str, key: &str) {
+fn render_button(frame: &mut Frame, area: Rect, label: &str, key: &str, theme: &Theme) {
     let content = Line::from(vec![
         Span::styled(label, Style::default().bold()),
         Span::raw("\n"),
-        Span::styled(format!("[{}]", key), Style::default().fg(Color::Yellow)),
+        Span::styled(format!("[{}]", key), theme.accent()),
     ]);
 
     let button = Paragraph::new(content)
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+        .block(theme.block());
 
     frame.render_widget(button, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::MockClipboard;
+    use crate::core::session::{Session, Type};
+    use crate::db::models::Word;
+    use crate::db::test_conn;
+    use crate::ui::app::Screen;
+    use crate::ui::keymap::KeyMap;
+    use crate::ui::testing::render_to_string;
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+
+    fn word(id: i32, word: &str) -> Word {
+        Word {
+            id,
+            word: word.into(),
+            definition: "def".into(),
+            group_id: 1,
+            marked: false,
+            last_seen: None,
+            times_seen: 0,
+            success_count: 0,
+        }
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_event(
+            app,
+            KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press),
+        );
+    }
+
+    #[test]
+    fn test_wrong_answer_shows_diff_until_advance() {
+        let mut app = App::new_test_seeded(&[("abate", "def", 1), ("abound", "def", 1)]);
+        app.current_screen = Screen::Test;
+
+        press(&mut app, KeyCode::Char('i'));
+        for c in "abbate".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.graded, Some(false));
+        assert!(session.answer_diff.is_some());
+
+        press(&mut app, KeyCode::Enter);
+        let session = app.session.as_ref().unwrap();
+        assert_eq!(session.index, 1);
+        assert!(session.answer_diff.is_none());
+    }
+
+    #[test]
+    fn test_copy_waits_for_grading() {
+        let mut app = App::new_test_seeded(&[("abate", "def", 1)]);
+        app.current_screen = Screen::Test;
+        let clipboard = MockClipboard::default();
+        app.clipboard = Box::new(clipboard.clone());
+
+        press(&mut app, KeyCode::Char('c'));
+        assert!(clipboard.copied.borrow().is_empty());
+        assert!(app.toast.as_ref().unwrap().is_error);
+
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(*clipboard.copied.borrow(), vec!["abate"]);
+    }
+
+    #[test]
+    fn test_ipa_waits_for_the_answer() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(id,word,definition,group_id,ipa) VALUES(1,'laconic','def',1,'ləˈkɒnɪk')",
+            [],
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        let session = Session::new(vec![word(1, "laconic")], 0, Type::Marked);
+        app.begin_session(Ok((session, Screen::Test)));
+        assert_eq!(
+            app.session.as_ref().unwrap().ipa.as_deref(),
+            Some("ləˈkɒnɪk")
+        );
+
+        // The transcription would give the word away.
+        let screen = render_to_string(&app, 80, 24);
+        assert!(!screen.contains("ləˈkɒnɪk"), "{screen}");
+
+        press(&mut app, KeyCode::Enter);
+        let screen = render_to_string(&app, 80, 24);
+        assert!(screen.contains("laconic  /ləˈkɒnɪk/"), "{screen}");
+    }
+
+    #[test]
+    fn test_reading_is_an_answer() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO words(id,word,definition,group_id,reading) VALUES(1,'京都','Kyoto',1,'kyouto')",
+            [],
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        let session = Session::new(vec![word(1, "京都")], 0, Type::Marked);
+        app.begin_session(Ok((session, Screen::Test)));
+        // Shown under the word only once answered, like the IPA.
+        let screen = render_to_string(&app, 120, 40);
+        assert!(!screen.contains("kyouto"), "{screen}");
+
+        app.session.as_mut().unwrap().input_buffer.paste("KYOUTO");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.session.as_ref().unwrap().graded, Some(true));
+        let screen = render_to_string(&app, 120, 40);
+        assert!(screen.contains("kyouto"), "{screen}");
+    }
+
+    #[test]
+    fn test_history_cached_until_advance() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO words(id,word,definition,group_id) VALUES(1,'abate','def',1), (2,'abound','def',1);
+             INSERT INTO reviews(word_id,reviewed_at,correct,mode) VALUES(1,10,1,0), (1,20,0,1), (2,30,1,0);",
+        )
+        .unwrap();
+        let mut app = App::new(conn, KeyMap::default());
+        let session = Session::new(vec![word(1, "abate"), word(2, "abound")], 0, Type::Marked);
+        app.begin_session(Ok((session, Screen::Test)));
+        assert_eq!(
+            app.session.as_ref().unwrap().history,
+            Some(vec![true, false])
+        );
+
+        // Grading writes a review, but the shown history stays as loaded
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(
+            app.session.as_ref().unwrap().history,
+            Some(vec![true, false])
+        );
+
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.session.as_ref().unwrap().history, Some(vec![true]));
+    }
+}
@@ -4,9 +4,12 @@ use crossterm::{
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::io::{self, Stdout};
+use std::panic::{self, PanicHookInfo};
 
 pub type AppTerminal = Terminal<CrosstermBackend<Stdout>>;
 
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static>;
+
 pub fn init_terminal() -> anyhow::Result<AppTerminal> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -21,3 +24,47 @@ pub fn restore_terminal(mut terminal: AppTerminal) -> anyhow::Result<()> {
     terminal.show_cursor()?;
     Ok(())
 }
+
+/// Makes a panic leave raw mode and the alternate screen before the
+/// default hook prints the message and backtrace.
+pub fn install_panic_hook() {
+    let hook = chain_panic_hook(panic::take_hook(), || {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+    });
+    panic::set_hook(hook);
+}
+
+fn chain_panic_hook(previous: PanicHook, restore: impl Fn() + Send + Sync + 'static) -> PanicHook {
+    Box::new(move |info| {
+        restore();
+        previous(info);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_panic_hook_restores_before_previous() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let previous_calls = Arc::clone(&calls);
+        let previous: PanicHook =
+            Box::new(move |_| previous_calls.lock().unwrap().push("previous"));
+        let restore_calls = Arc::clone(&calls);
+        let hook = chain_panic_hook(previous, move || {
+            restore_calls.lock().unwrap().push("restore")
+        });
+
+        let default_hook = panic::take_hook();
+        panic::set_hook(hook);
+        let result = panic::catch_unwind(|| panic!("boom"));
+        panic::set_hook(default_hook);
+
+        assert!(result.is_err());
+        assert_eq!(*calls.lock().unwrap(), vec!["restore", "previous"]);
+    }
+}
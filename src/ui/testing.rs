@@ -0,0 +1,201 @@
+//! Rendering snapshots. Each test draws a screen into a `TestBackend` at
+//! every size in `SIZES` and compares it with `src/ui/snapshots/`.
+//!
+//! To accept a change on purpose, rerun with `UPDATE_SNAPSHOTS=1` and
+//! review the diff of the snapshot files before committing.
+
+use crate::db::test_conn;
+use crate::ui::app::App;
+use crate::ui::keymap::KeyMap;
+use crate::ui::run::render;
+use ratatui::{Terminal, backend::TestBackend};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+
+const SIZES: [(u16, u16); 2] = [(80, 24), (120, 40)];
+
+/// App over an in-memory database with two small groups and nothing seen.
+pub fn seeded_app() -> App {
+    let conn = test_conn();
+    conn.execute_batch(
+        "INSERT INTO words(id,word,definition,group_id) VALUES
+             (1,'abate','become less intense or widespread',1),
+             (2,'abound','be present in large quantities',1),
+             (3,'laconic','using very few words',2);",
+    )
+    .unwrap();
+    let mut app = App::new(conn, KeyMap::default());
+    app.db_path = "/home/user/.local/share/vocabulator/vocab.db".into();
+    app
+}
+
+/// The whole frame as text, one line per row.
+pub fn render_to_string(app: &App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| render(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..height)
+        .map(|y| {
+            let row: String = (0..width).map(|x| buffer.get(x, y).symbol()).collect();
+            format!("{}\n", row.trim_end())
+        })
+        .collect()
+}
+
+/// Compares `app` against the `name` snapshot at every size, or rewrites
+/// the snapshots when `UPDATE_SNAPSHOTS` is set.
+pub fn assert_snapshot(name: &str, app: &App) {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    for (width, height) in SIZES {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/ui/snapshots")
+            .join(format!("{name}_{width}x{height}.txt"));
+        let actual = render_to_string(app, width, height);
+
+        if update {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &actual).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "missing snapshot {}; run with UPDATE_SNAPSHOTS=1",
+                path.display()
+            )
+        });
+        assert!(
+            actual == expected,
+            "{name} at {width}x{height} differs from {}\n--- expected\n{expected}--- actual\n{actual}",
+            path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::app::Screen;
+    use crate::ui::event::AppEvent;
+    use crate::ui::screens::{review_log, stats};
+    use crate::ui::theme::Theme;
+    use crate::ui::widgets::{Buttons, Modal};
+    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle(AppEvent::Key(KeyEvent::new_with_kind(
+            code,
+            KeyModifiers::NONE,
+            KeyEventKind::Press,
+        )));
+    }
+
+    #[test]
+    fn test_menu_snapshot() {
+        assert_snapshot("menu", &seeded_app());
+    }
+
+    #[test]
+    fn test_splash_snapshot() {
+        let mut app = App::new_test();
+        app.db_path = "/home/user/.local/share/vocabulator/vocab.db".into();
+        app.current_screen = Screen::Splash;
+        assert_snapshot("splash", &app);
+    }
+
+    #[test]
+    fn test_practice_snapshot() {
+        let mut app = seeded_app();
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('s'));
+        assert_snapshot("practice", &app);
+    }
+
+    #[test]
+    fn test_modal_snapshot() {
+        let mut app = seeded_app();
+        app.modal = Some(Modal::new(
+            "abate",
+            vec!["become less intense or widespread".into()],
+            Buttons::AnyKey,
+        ));
+        assert_snapshot("modal", &app);
+    }
+
+    #[test]
+    fn test_ascii_mode_draws_only_ascii() {
+        let mut app = seeded_app();
+        app.theme = Theme::new(true, true);
+        let mut frames = vec![render_to_string(&app, 80, 24)];
+
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('s'));
+        frames.push(render_to_string(&app, 80, 24));
+        press(&mut app, KeyCode::Char('n'));
+        app.notify("Deleted 'abate' -- press u to undo", false);
+        frames.push(render_to_string(&app, 80, 24));
+        app.current_screen = Screen::Test;
+        frames.push(render_to_string(&app, 80, 24));
+
+        stats::open(&mut app);
+        frames.push(render_to_string(&app, 120, 40));
+        review_log::open(&mut app);
+        frames.push(render_to_string(&app, 120, 40));
+        app.modal = Some(Modal::new(
+            "abate",
+            vec!["become less intense or widespread".into()],
+            Buttons::AnyKey,
+        ));
+        frames.push(render_to_string(&app, 80, 24));
+
+        app.modal = None;
+        app.current_screen = Screen::Splash;
+        frames.push(render_to_string(&app, 80, 24));
+
+        for frame in frames {
+            assert!(frame.is_ascii(), "{frame}");
+        }
+    }
+}
+
+/// System allocator that counts what the current thread allocates, so
+/// allocation benches aren't skewed by tests running alongside them.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+fn count(bytes: usize) {
+    // Fails only while the thread is being torn down.
+    let _ = ALLOCATED.try_with(|n| {
+        let (count, total) = n.get();
+        n.set((count + 1, total + bytes as u64));
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+/// Allocations and bytes allocated so far on this thread.
+pub fn allocations() -> (u64, u64) {
+    ALLOCATED.with(Cell::get)
+}
@@ -0,0 +1,135 @@
+//! Display-width aware string helpers. Lengths here are terminal columns,
+//! not chars, and graphemes are never split.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Cuts `s` to at most `width` columns, ending in `ellipsis` when anything
+/// was dropped and there is room for it.
+pub fn truncate_to_width(s: &str, width: usize, ellipsis: &str) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let Some(budget) = width.checked_sub(ellipsis.width()) else {
+        return take_width(s.graphemes(true), width);
+    };
+
+    let mut out = take_width(s.graphemes(true), budget);
+    out.push_str(ellipsis);
+    out
+}
+
+/// Pads `s` with spaces to exactly `width` columns, truncating first if it
+/// is too wide.
+pub fn pad_to_width(s: &str, width: usize, ellipsis: &str) -> String {
+    let mut out = truncate_to_width(s, width, ellipsis);
+    let used = out.width();
+    out.extend(std::iter::repeat_n(' ', width.saturating_sub(used)));
+    out
+}
+
+/// Shortens `s` to at most `width` columns by replacing its middle with
+/// `ellipsis`, keeping both ends readable (useful for paths).
+pub fn truncate_middle(s: &str, width: usize, ellipsis: &str) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let Some(budget) = width.checked_sub(ellipsis.width()) else {
+        return take_width(s.graphemes(true), width);
+    };
+
+    let tail_budget = budget / 2;
+
+    let mut out = take_width(s.graphemes(true), budget - tail_budget);
+    let tail: Vec<&str> = s.graphemes(true).rev().collect();
+    let tail = take_width(tail.into_iter(), tail_budget);
+    let tail: String = tail.graphemes(true).rev().collect();
+
+    out.push_str(ellipsis);
+    out.push_str(&tail);
+    out
+}
+
+/// Concatenates graphemes while the result still fits in `width` columns.
+fn take_width<'a>(graphemes: impl Iterator<Item = &'a str>, width: usize) -> String {
+    let mut out = String::new();
+    for g in graphemes {
+        let before = out.len();
+        out.push_str(g);
+        if out.width() > width {
+            out.truncate(before);
+            break;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::glyphs::{ASCII, UNICODE};
+
+    const PIECES: [&str; 6] = ["a", "日", "👨\u{200d}👩\u{200d}👧", "e\u{301}", " ", "ｗ"];
+
+    /// Every string of up to four pieces.
+    fn samples() -> Vec<String> {
+        let mut out = vec![String::new()];
+        let mut frontier = vec![String::new()];
+        for _ in 0..4 {
+            frontier = frontier
+                .iter()
+                .flat_map(|s| PIECES.iter().map(move |p| format!("{s}{p}")))
+                .collect();
+            out.extend(frontier.iter().cloned());
+        }
+        out
+    }
+
+    #[test]
+    fn test_truncate_never_exceeds_width() {
+        for s in samples() {
+            for (width, ellipsis) in
+                (0..10).flat_map(|w| [(w, UNICODE.ellipsis), (w, ASCII.ellipsis)])
+            {
+                let cut = truncate_to_width(&s, width, ellipsis);
+                assert!(cut.width() <= width, "{s:?} at {width}: {cut:?}");
+                assert_eq!(cut == s, s.width() <= width);
+                if cut != s && width >= ellipsis.width() {
+                    assert!(cut.ends_with(ellipsis));
+                }
+
+                let mid = truncate_middle(&s, width, ellipsis);
+                assert!(mid.width() <= width, "{s:?} at {width}: {mid:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_pad_is_exact_width() {
+        for s in samples() {
+            for width in 0..10 {
+                let padded = pad_to_width(&s, width, UNICODE.ellipsis);
+                // A wide grapheme that doesn't fit leaves one column short
+                // of an exact cut, which the padding fills.
+                assert_eq!(padded.width(), width, "{s:?} at {width}: {padded:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        let e = UNICODE.ellipsis;
+        assert_eq!(truncate_to_width("abound", 4, e), "abo…");
+        assert_eq!(truncate_to_width("日本語", 4, e), "日…");
+        assert_eq!(truncate_to_width("cafe\u{301}s", 5, e), "cafe\u{301}s");
+        assert_eq!(truncate_to_width("cafe\u{301}s", 4, e), "caf…");
+        assert_eq!(pad_to_width("日", 4, e), "日  ");
+        assert_eq!(
+            truncate_middle("/home/user/words/vocab.db", 11, e),
+            "/home…ab.db"
+        );
+        assert_eq!(truncate_middle("/tmp/vocab.db", 20, e), "/tmp/vocab.db");
+        assert_eq!(truncate_to_width("abound", 5, ASCII.ellipsis), "ab...");
+        assert_eq!(truncate_to_width("abound", 2, ASCII.ellipsis), "ab");
+    }
+}
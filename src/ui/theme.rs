@@ -0,0 +1,122 @@
+//! Styles the renderers draw with, picked once at startup. Without color
+//! each role falls back to bold or reverse video, so nothing depends on
+//! telling colors apart.
+
+use crate::ui::glyphs::{self, Glyphs};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub color: bool,
+    pub glyphs: &'static Glyphs,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::new(true, false)
+    }
+}
+
+impl Theme {
+    /// `ascii` implies no color too: terminals that mangle the glyphs
+    /// tend to mangle colors as well.
+    pub fn new(color: bool, ascii: bool) -> Theme {
+        Theme {
+            color: color && !ascii,
+            glyphs: if ascii {
+                &glyphs::ASCII
+            } else {
+                &glyphs::UNICODE
+            },
+        }
+    }
+
+    /// The theme for this run: color unless `no_color` or a non-empty
+    /// `NO_COLOR` (https://no-color.org) says otherwise.
+    pub fn from_env(ascii: bool, no_color: bool) -> Theme {
+        let env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Theme::new(!no_color && !env, ascii)
+    }
+
+    fn pick(&self, color: Style, plain: Style) -> Style {
+        if self.color { color } else { plain }
+    }
+
+    /// Right answers and successes.
+    pub fn good(&self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Green),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    }
+
+    /// Wrong answers and errors.
+    pub fn bad(&self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Red),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )
+    }
+
+    /// Warnings, key hints and text being typed.
+    pub fn accent(&self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Yellow),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    }
+
+    /// Help lines and anything disabled.
+    pub fn muted(&self) -> Style {
+        self.pick(Style::default().fg(Color::DarkGray), Style::default())
+    }
+
+    /// Secondary text that still has to be read, like a definition.
+    pub fn soft(&self) -> Style {
+        self.pick(Style::default().fg(Color::Gray), Style::default())
+    }
+
+    /// A label that has to stand out from everything around it.
+    pub fn badge(&self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )
+        .add_modifier(Modifier::BOLD)
+    }
+
+    /// A bordered block drawn with the theme's border glyphs.
+    pub fn block(&self) -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(self.glyphs.border)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_theme_has_no_colors() {
+        let plain = Theme::new(false, false);
+        for style in [
+            plain.good(),
+            plain.bad(),
+            plain.accent(),
+            plain.muted(),
+            plain.soft(),
+            plain.badge(),
+        ] {
+            assert_eq!((style.fg, style.bg), (None, None), "{style:?}");
+        }
+        assert_ne!(plain.good(), plain.bad());
+        assert_eq!(plain.glyphs, &glyphs::UNICODE);
+
+        let ascii = Theme::new(true, true);
+        assert!(!ascii.color);
+        assert_eq!(ascii.glyphs, &glyphs::ASCII);
+        assert_eq!(Theme::default().good().fg, Some(Color::Green));
+    }
+}
@@ -0,0 +1,339 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Single-line text input with a grapheme-aware cursor.
+#[derive(Debug, Default, Clone)]
+pub struct LineEdit {
+    text: String,
+    /// Byte offset into `text`, always on a grapheme boundary.
+    cursor: usize,
+}
+
+impl LineEdit {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        // A combining mark joins the previous grapheme; keep the cursor after it.
+        self.cursor = self.next_boundary(self.prev_boundary(self.cursor));
+    }
+
+    /// Inserts pasted text at the cursor, trimmed and with line breaks
+    /// flattened to spaces since the input is a single line.
+    pub fn paste(&mut self, text: &str) {
+        for c in text.trim().chars() {
+            self.insert(if c.is_control() { ' ' } else { c });
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        let start = self.prev_boundary(self.cursor);
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete(&mut self) {
+        let end = self.next_boundary(self.cursor);
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.prev_boundary(self.cursor);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = self.next_boundary(self.cursor);
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// Deletes back to the start of the previous word, skipping any
+    /// whitespace directly before the cursor first.
+    pub fn delete_word_back(&mut self) {
+        let before = &self.text[..self.cursor];
+        let trimmed = before.trim_end();
+        let start = trimmed
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn clear_to_start(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    /// Applies an editing key. Returns false when the key isn't an edit so
+    /// the caller can handle it.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.home(),
+            KeyCode::Char('e') if ctrl => self.end(),
+            KeyCode::Char('w') if ctrl => self.delete_word_back(),
+            KeyCode::Char('u') if ctrl => self.clear_to_start(),
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Renders the text into `width` columns, scrolling horizontally so the
+    /// cursor stays visible. The cursor cell is drawn reversed when `focused`.
+    pub fn line(&self, width: u16, focused: bool) -> Line<'_> {
+        let width = width as usize;
+        if width == 0 {
+            return Line::default();
+        }
+
+        let graphemes: Vec<(usize, &str)> = self.text.grapheme_indices(true).collect();
+        let cursor_index = graphemes
+            .iter()
+            .position(|(i, _)| *i >= self.cursor)
+            .unwrap_or(graphemes.len());
+
+        // Walk back from the cursor until the window is full, leaving room
+        // for the cursor cell itself.
+        let cursor_width = graphemes
+            .get(cursor_index)
+            .map(|(_, g)| g.width().max(1))
+            .unwrap_or(1);
+        let mut start = cursor_index;
+        let mut used = cursor_width;
+        while start > 0 {
+            let w = graphemes[start - 1].1.width();
+            if used + w > width {
+                break;
+            }
+            used += w;
+            start -= 1;
+        }
+
+        let mut spans = Vec::new();
+        let mut used = 0;
+        for (idx, (_, g)) in graphemes.iter().enumerate().skip(start) {
+            let w = g.width();
+            if used + w > width {
+                break;
+            }
+            used += w;
+            let style = if focused && idx == cursor_index {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(*g, style));
+        }
+
+        if focused && cursor_index == graphemes.len() && used < width {
+            spans.push(Span::styled(
+                " ",
+                Style::default().add_modifier(Modifier::REVERSED),
+            ));
+        }
+
+        Line::from(spans)
+    }
+
+    fn prev_boundary(&self, from: usize) -> usize {
+        self.text[..from]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self, from: usize) -> usize {
+        self.text[from..]
+            .graphemes(true)
+            .next()
+            .map(|g| from + g.len())
+            .unwrap_or(self.text.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventKind;
+
+    fn edit(s: &str) -> LineEdit {
+        let mut e = LineEdit::default();
+        for c in s.chars() {
+            e.insert(c);
+        }
+        e
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new_with_kind(KeyCode::Char(c), KeyModifiers::CONTROL, KeyEventKind::Press)
+    }
+
+    fn rendered(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_insert_in_middle() {
+        let mut e = edit("abte");
+        e.left();
+        e.left();
+        e.insert('a');
+        assert_eq!(e.text(), "abate");
+        assert_eq!(e.cursor, 3);
+    }
+
+    #[test]
+    fn test_paste_at_cursor() {
+        let mut e = edit("a  word");
+        e.home();
+        e.right();
+        e.right();
+        e.paste("\n  perspicacious\r\n");
+        assert_eq!(e.text(), "a perspicacious word");
+        assert_eq!(e.cursor, "a perspicacious".len());
+
+        e.paste("two\nlines");
+        assert_eq!(e.text(), "a perspicacioustwo lines word");
+    }
+
+    #[test]
+    fn test_multibyte_movement_and_backspace() {
+        let mut e = edit("日本語");
+        assert_eq!(e.cursor, 9);
+
+        e.left();
+        assert_eq!(e.cursor, 6);
+        e.backspace();
+        assert_eq!(e.text(), "日語");
+        assert_eq!(e.cursor, 3);
+
+        e.right();
+        e.right();
+        assert_eq!(e.cursor, 6);
+    }
+
+    #[test]
+    fn test_combining_mark_moves_as_one() {
+        let mut e = edit("cafe\u{301}");
+        assert_eq!(e.cursor, e.text().len());
+
+        e.left();
+        assert_eq!(e.cursor, 3);
+        e.delete();
+        assert_eq!(e.text(), "caf");
+    }
+
+    #[test]
+    fn test_home_end_and_delete() {
+        let mut e = edit("émoi");
+        assert!(e.handle_key(ctrl('a')));
+        assert_eq!(e.cursor, 0);
+        e.delete();
+        assert_eq!(e.text(), "moi");
+
+        assert!(e.handle_key(ctrl('e')));
+        assert_eq!(e.cursor, 3);
+        e.delete();
+        assert_eq!(e.text(), "moi");
+    }
+
+    #[test]
+    fn test_delete_word_back() {
+        let mut e = edit("laissez faire  ");
+        e.handle_key(ctrl('w'));
+        assert_eq!(e.text(), "laissez ");
+
+        e.handle_key(ctrl('w'));
+        assert_eq!(e.text(), "");
+
+        let mut e = edit("über straße");
+        e.left();
+        e.left();
+        e.handle_key(ctrl('w'));
+        assert_eq!(e.text(), "über ße");
+        assert_eq!(e.cursor, "über ".len());
+    }
+
+    #[test]
+    fn test_clear_to_start() {
+        let mut e = edit("ne'er-do-well");
+        for _ in 0..4 {
+            e.left();
+        }
+        e.handle_key(ctrl('u'));
+        assert_eq!(e.text(), "well");
+        assert_eq!(e.cursor, 0);
+    }
+
+    #[test]
+    fn test_unbound_ctrl_key_is_not_consumed() {
+        let mut e = edit("a");
+        assert!(!e.handle_key(ctrl('x')));
+        assert_eq!(e.text(), "a");
+    }
+
+    #[test]
+    fn test_line_scrolls_to_cursor() {
+        let e = edit("abcdefgh");
+        let line = e.line(5, true);
+        assert_eq!(rendered(&line), "efgh ");
+        assert!(
+            line.spans
+                .last()
+                .unwrap()
+                .style
+                .add_modifier
+                .contains(Modifier::REVERSED)
+        );
+
+        let mut e = e;
+        e.home();
+        assert_eq!(rendered(&e.line(5, true)), "abcde");
+    }
+
+    #[test]
+    fn test_line_with_wide_characters() {
+        let e = edit("日本語です");
+        // Each character is two columns wide and the cursor cell takes one,
+        // so only two characters fit beside it.
+        assert_eq!(rendered(&e.line(6, true)), "です ");
+        assert_eq!(rendered(&e.line(6, false)), "です");
+
+        let mut e = e;
+        e.home();
+        let line = e.line(6, true);
+        assert_eq!(rendered(&line), "日本語");
+        assert!(rendered(&line).width() <= 6);
+    }
+}
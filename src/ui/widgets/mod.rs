@@ -0,0 +1,7 @@
+pub mod line_edit;
+pub mod modal;
+pub mod toast;
+
+pub use line_edit::LineEdit;
+pub use modal::{Buttons, Modal, ModalResult};
+pub use toast::Toast;
@@ -0,0 +1,161 @@
+use crate::ui::glyphs::Glyphs;
+use crate::ui::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::Line,
+    widgets::{Clear, Paragraph, Wrap},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Buttons {
+    #[allow(dead_code)]
+    OkCancel,
+    YesNo,
+    /// Informational; any key closes it.
+    AnyKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalResult {
+    /// Key didn't close the modal.
+    Open,
+    Accepted,
+    Rejected,
+}
+
+/// Centered dialog drawn over the current screen. While one is open on
+/// `App`, it receives every key before the screen does.
+#[derive(Debug, Clone)]
+pub struct Modal {
+    pub title: String,
+    pub body: Vec<String>,
+    pub buttons: Buttons,
+    /// Size as a percentage of the screen.
+    pub width_percent: u16,
+    pub height_percent: u16,
+}
+
+impl Modal {
+    pub fn new(title: impl Into<String>, body: Vec<String>, buttons: Buttons) -> Self {
+        Self {
+            title: title.into(),
+            body,
+            buttons,
+            width_percent: 50,
+            height_percent: 30,
+        }
+    }
+
+    pub fn size(mut self, width_percent: u16, height_percent: u16) -> Self {
+        self.width_percent = width_percent.min(100);
+        self.height_percent = height_percent.min(100);
+        self
+    }
+
+    pub fn handle_key(&self, key: KeyEvent) -> ModalResult {
+        match (self.buttons, key.code) {
+            (Buttons::AnyKey, _) => ModalResult::Accepted,
+            (Buttons::OkCancel, KeyCode::Enter | KeyCode::Char('o')) => ModalResult::Accepted,
+            (Buttons::OkCancel, KeyCode::Esc | KeyCode::Char('c' | 'q')) => ModalResult::Rejected,
+            (Buttons::YesNo, KeyCode::Enter | KeyCode::Char('y' | 'Y')) => ModalResult::Accepted,
+            (Buttons::YesNo, KeyCode::Esc | KeyCode::Char('n' | 'N')) => ModalResult::Rejected,
+            _ => ModalResult::Open,
+        }
+    }
+
+    fn hint(&self, glyphs: &Glyphs) -> String {
+        match self.buttons {
+            Buttons::OkCancel => format!("[{}] OK   [esc] Cancel", glyphs.enter),
+            Buttons::YesNo => "[y] Yes   [n] No".into(),
+            Buttons::AnyKey => "Press any key".into(),
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame, theme: &Theme) {
+        let area = centered_rect(f.size(), self.width_percent, self.height_percent);
+        let block = theme.block().title(self.title.as_str());
+        let inner = block.inner(area);
+
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(inner);
+
+        let body: Vec<Line> = self.body.iter().map(|l| Line::raw(l.as_str())).collect();
+        f.render_widget(Paragraph::new(body).wrap(Wrap { trim: true }), chunks[0]);
+        f.render_widget(
+            Paragraph::new(self.hint(theme.glyphs)).style(theme.muted()),
+            chunks[1],
+        );
+    }
+}
+
+pub fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Press)
+    }
+
+    fn results(buttons: Buttons, codes: &[KeyCode]) -> Vec<ModalResult> {
+        let modal = Modal::new("t", vec![], buttons);
+        codes.iter().map(|c| modal.handle_key(key(*c))).collect()
+    }
+
+    #[test]
+    fn test_any_key() {
+        use ModalResult::*;
+        assert_eq!(
+            results(Buttons::AnyKey, &[KeyCode::Char('x'), KeyCode::Esc]),
+            vec![Accepted, Accepted]
+        );
+    }
+
+    #[test]
+    fn test_ok_cancel() {
+        use ModalResult::*;
+        assert_eq!(
+            results(
+                Buttons::OkCancel,
+                &[KeyCode::Enter, KeyCode::Esc, KeyCode::Char('y')]
+            ),
+            vec![Accepted, Rejected, Open]
+        );
+    }
+
+    #[test]
+    fn test_yes_no() {
+        use ModalResult::*;
+        assert_eq!(
+            results(
+                Buttons::YesNo,
+                &[KeyCode::Char('y'), KeyCode::Char('n'), KeyCode::Char('q')]
+            ),
+            vec![Accepted, Rejected, Open]
+        );
+    }
+
+    #[test]
+    fn test_centered_rect() {
+        let r = centered_rect(Rect::new(0, 0, 100, 40), 50, 50);
+        assert_eq!(r, Rect::new(25, 10, 50, 20));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::ui::text;
+use crate::ui::theme::Theme;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    widgets::{Clear, Paragraph},
+};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
+
+pub const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+/// Short-lived notice drawn over the bottom-right corner of the screen.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub is_error: bool,
+    pub expires: Instant,
+}
+
+impl Toast {
+    pub fn new(message: impl Into<String>, is_error: bool, now: Instant) -> Self {
+        Self {
+            message: message.into(),
+            is_error,
+            expires: now + TOAST_DURATION,
+        }
+    }
+
+    /// Keeps the toast up until `expires` instead of `TOAST_DURATION`.
+    pub fn until(mut self, expires: Instant) -> Self {
+        self.expires = expires;
+        self
+    }
+
+    pub fn expired(&self, now: Instant) -> bool {
+        now >= self.expires
+    }
+
+    pub fn render(&self, f: &mut Frame, theme: &Theme) {
+        let area = f.size();
+        let width = (self.message.width() as u16 + 4).min(area.width);
+        let height = 3.min(area.height);
+        let rect = Rect {
+            x: area.width - width,
+            y: area.height - height,
+            width,
+            height,
+        };
+
+        let style = if self.is_error {
+            theme.bad()
+        } else {
+            theme.good()
+        };
+        let message = text::truncate_to_width(
+            &self.message,
+            width.saturating_sub(2) as usize,
+            theme.glyphs.ellipsis,
+        );
+        let toast = Paragraph::new(message).style(style).block(theme.block());
+
+        f.render_widget(Clear, rect);
+        f.render_widget(toast, rect);
+    }
+}